@@ -0,0 +1,160 @@
+//! Bearer tokens for the write operations. Reads stay public, a route or field only asks for a
+//! token when it changes something.
+use crate::{AppState, routes::problem::Problem};
+use core_victorhqc_com::{
+    models::api_token::{ApiToken, Scope, db::Error as ApiTokenDbError},
+    sqlx::Error as SqlxError,
+};
+use rocket::{
+    Request,
+    request::{FromRequest, Outcome},
+};
+use snafu::prelude::*;
+use std::marker::PhantomData;
+use time::OffsetDateTime;
+
+/// A scope a route asks for, as a type so it is part of the route's signature.
+pub trait RequiredScope: Send + Sync + 'static {
+    const SCOPE: Scope;
+}
+
+/// Any valid token.
+pub struct Read;
+
+impl RequiredScope for Read {
+    const SCOPE: Scope = Scope::Read;
+}
+
+pub struct WritePhotos;
+
+impl RequiredScope for WritePhotos {
+    const SCOPE: Scope = Scope::WritePhotos;
+}
+
+/// Tokens with every scope.
+pub struct Admin;
+
+impl RequiredScope for Admin {
+    const SCOPE: Scope = Scope::Admin;
+}
+
+/// Request guard of the routes that need a token with the scope `S`. Requests without one get a
+/// 401 and tokens without the scope a 403, both as problem details.
+pub struct Authorized<S: RequiredScope> {
+    pub token: ApiToken,
+    scope: PhantomData<S>,
+}
+
+#[rocket::async_trait]
+impl<'r, S: RequiredScope> FromRequest<'r> for Authorized<S> {
+    type Error = Error;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match authenticate(req).await.and_then(|t| authorize(t, S::SCOPE)) {
+            Ok(token) => Outcome::Success(Authorized {
+                token,
+                scope: PhantomData,
+            }),
+            Err(e) => {
+                // The catchers of /v1 send it, guards can't send a body themselves.
+                let problem = Problem::from(&e);
+                let status = problem.status();
+                req.local_cache(|| Some(problem));
+
+                Outcome::Error((status, e))
+            }
+        }
+    }
+}
+
+/// The token of the request, or why there isn't a valid one. It never fails, so the GraphQL
+/// guards can reject only the fields that need a token.
+pub struct Credentials(Result<ApiToken, Error>);
+
+impl Credentials {
+    /// The token when it grants the scope, otherwise why it doesn't.
+    pub fn authorize(&self, scope: Scope) -> Result<&ApiToken, String> {
+        let token = self.0.as_ref().map_err(|e| e.to_string())?;
+
+        if !token.allows(scope) {
+            return Err(Error::Forbidden { scope }.to_string());
+        }
+
+        Ok(token)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Credentials {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(Credentials(authenticate(req).await))
+    }
+}
+
+async fn authenticate(req: &Request<'_>) -> Result<ApiToken, Error> {
+    let header = req
+        .headers()
+        .get_one("Authorization")
+        .context(MissingSnafu)?;
+    let secret = header.strip_prefix("Bearer ").context(MalformedSnafu)?;
+
+    let state = req
+        .rocket()
+        .state::<AppState>()
+        .expect("AppState is managed");
+    let mut conn = state.db_pool.acquire().await.context(ConnectionSnafu)?;
+
+    let token = ApiToken::find_by_secret(&mut conn, secret.trim())
+        .await
+        .context(LookupSnafu)?
+        .context(UnknownSnafu)?;
+
+    ensure!(!token.is_expired(OffsetDateTime::now_utc()), ExpiredSnafu);
+
+    Ok(token)
+}
+
+fn authorize(token: ApiToken, scope: Scope) -> Result<ApiToken, Error> {
+    ensure!(token.allows(scope), ForbiddenSnafu { scope });
+
+    Ok(token)
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("A bearer token is required"))]
+    Missing,
+
+    #[snafu(display("The Authorization header must be 'Bearer <token>'"))]
+    Malformed,
+
+    #[snafu(display("The token does not exist or was revoked"))]
+    Unknown,
+
+    #[snafu(display("The token expired"))]
+    Expired,
+
+    #[snafu(display("The token lacks the {} scope", scope))]
+    Forbidden { scope: Scope },
+
+    #[snafu(display("Failed to get connection: {}", source))]
+    Connection { source: SqlxError },
+
+    #[snafu(display("Failed to look the token up: {}", source))]
+    Lookup { source: ApiTokenDbError },
+}
+
+impl From<&Error> for Problem {
+    fn from(error: &Error) -> Self {
+        match error {
+            Error::Missing | Error::Malformed | Error::Unknown | Error::Expired => {
+                Problem::Unauthorized(error.to_string())
+            }
+            Error::Forbidden { .. } => Problem::Forbidden(error.to_string()),
+            Error::Connection { source } => Problem::from_connection(source),
+            _ => Problem::Internal(error.to_string()),
+        }
+    }
+}
@@ -1,19 +1,12 @@
-use crate::AppState;
+use crate::{AppState, metrics::METRICS};
 use core_victorhqc_com::aws::image_size::{ImageSize, ImageType};
 use core_victorhqc_com::models::{photo::Photo, tag::Tag};
-use log::{debug, info, warn};
 use rocket::futures::future::join_all;
 use rocket::tokio;
 use std::collections::HashSet;
+use tracing::{debug, info, warn};
 
 pub fn prepare_images(state: AppState, tags: Vec<String>) -> tokio::task::JoinHandle<AppState> {
-    let sizes = [
-        ImageSize::HdPlus,
-        ImageSize::Hd,
-        ImageSize::Md,
-        ImageSize::Sm,
-    ];
-
     info!("Preparing images...");
     tokio::spawn(async move {
         let mut conn = state.db_pool.acquire().await.unwrap();
@@ -47,17 +40,14 @@ pub fn prepare_images(state: AppState, tags: Vec<String>) -> tokio::task::JoinHa
         let download_futures = photos_set
             .iter()
             .flat_map(|photo| {
-                sizes.iter().map(|img_size| {
+                ImageSize::renditions().map(|img_size| {
                     let state = state.clone();
                     let photo = photo.clone();
 
                     async move {
-                        let response = match state
-                            .img_cache
-                            .s3
-                            .download_from_aws_s3((&photo, img_size, kind))
-                            .await
-                        {
+                        let s3 = &state.img_cache.s3;
+                        let download = s3.download_from_aws_s3((&photo, &img_size, kind));
+                        let response = match METRICS.s3("get_object", download).await {
                             Ok(r) => r,
                             Err(e) => {
                                 warn!(
@@ -80,7 +70,7 @@ pub fn prepare_images(state: AppState, tags: Vec<String>) -> tokio::task::JoinHa
 
                         state
                             .img_cache
-                            .save(&photo.id, kind, img_size, bytes.clone())
+                            .save(&photo.id, kind, &img_size, bytes.clone())
                             .await;
 
                         debug!("Cached photo {} in {}", &photo.id, img_size);
@@ -1,3 +1,4 @@
+use crate::metrics::METRICS;
 use core_victorhqc_com::aws::{
     S3,
     image_size::{ImageSize, ImageType},
@@ -8,6 +9,7 @@ use rocket::futures::lock::{Mutex, MutexGuard};
 use snafu::prelude::*;
 use std::iter::Iterator;
 use std::sync::Arc;
+use tracing::debug;
 
 pub struct CachedImage {
     id: PhotoId,
@@ -31,6 +33,7 @@ impl CachedImage {
     }
 
     pub fn save_bytes(&mut self, bytes: Vec<u8>) {
+        self.md5_hash = format!("{:x}", md5::compute(&bytes));
         self.bytes = bytes;
     }
 
@@ -55,10 +58,27 @@ impl ImageCache {
         }
     }
 
-    pub async fn md5_exists(&self, value: &str) -> bool {
+    /// MD5 and length of a cached image, `None` when it wasn't downloaded yet.
+    pub async fn md5_of(
+        &self,
+        id: &PhotoId,
+        kind: &ImageType,
+        size: &ImageSize,
+    ) -> Option<(String, u64)> {
+        let images = self.images.lock().await;
+
+        images
+            .iter()
+            .find(|p| &p.id == id && &p.size == size && &p.kind == kind)
+            .map(|p| (p.get_md5(), p.bytes.len() as u64))
+    }
+
+    /// How many images are cached and their size in bytes.
+    pub async fn stats(&self) -> (usize, u64) {
         let images = self.images.lock().await;
+        let bytes = images.iter().map(|p| p.bytes.len() as u64).sum();
 
-        images.iter().any(|p| p.md5_hash == value)
+        (images.len(), bytes)
     }
 
     pub async fn get(
@@ -72,13 +92,15 @@ impl ImageCache {
             .iter()
             .position(|p| p.id == photo.id && &p.size == size && &p.kind == kind);
 
+        METRICS.image_cache(index.is_some());
+
         match index {
             None => {
                 debug!("Image not found in cache, downloading...");
 
-                let response = self
-                    .s3
-                    .download_from_aws_s3((&photo, size, kind))
+                let download = self.s3.download_from_aws_s3((&photo, size, kind));
+                let response = METRICS
+                    .s3("get_object", download)
                     .await
                     .map_err(Box::new)
                     .context(GetAWSObjectSnafu)?;
@@ -115,7 +137,9 @@ impl ImageCache {
         data: Vec<u8>,
         images: &mut MutexGuard<'_, Vec<CachedImage>>,
     ) -> String {
-        let index = images.iter().position(|p| &p.id == id && &p.size == size);
+        let index = images
+            .iter()
+            .position(|p| &p.id == id && &p.size == size && &p.kind == kind);
 
         match index {
             Some(i) => {
@@ -123,7 +147,7 @@ impl ImageCache {
                 images[i].get_md5()
             }
             None => {
-                let cached = CachedImage::new(id.clone(), size.clone(), kind.clone(), data);
+                let cached = CachedImage::new(id.clone(), *size, kind.clone(), data);
                 let hash = cached.get_md5();
 
                 images.push(cached);
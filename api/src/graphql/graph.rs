@@ -1,9 +1,79 @@
-use crate::graphql::queries::RootQuery;
-use async_graphql::{EmptyMutation, EmptySubscription, Schema};
+use crate::{
+    graphql::{
+        persisted_queries::PersistedQueries, queries::RootQuery, response_cache::CollectShown,
+    },
+    metrics::GraphQLMetrics,
+};
+use async_graphql::{EmptyMutation, EmptySubscription, Schema, SchemaBuilder};
 
 pub type RootSchema = Schema<RootQuery, EmptyMutation, EmptySubscription>;
 
-// #[Object]
-// impl QueryRoot {
-//     async fn photo
-// }
+/// How deep a query can nest fields, the portfolio query needs 6 levels.
+pub const MAX_DEPTH: usize = 8;
+
+/// The cost a query can reach. Every field costs 1 and lists multiply the cost of their items, so
+/// the portfolio query with 50 photos costs about 8000.
+pub const MAX_COMPLEXITY: usize = 10_000;
+
+/// How many items a list without a limit is expected to have.
+pub const LIST_COST: usize = 20;
+
+/// The cost of a list with an optional limit, `LIST_COST` items when there isn't one.
+pub fn list_cost(limit: Option<i32>, child_complexity: usize) -> usize {
+    limit
+        .map_or(LIST_COST, |l| l.max(0) as usize)
+        .saturating_mul(child_complexity)
+}
+
+/// The schema with its limits, the data the resolvers use is added by the caller.
+pub fn build(persisted_only: bool) -> SchemaBuilder<RootQuery, EmptyMutation, EmptySubscription> {
+    Schema::build(RootQuery::default(), EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .extension(PersistedQueries::new(persisted_only))
+        .extension(CollectShown)
+        .extension(GraphQLMetrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{Request, Variables};
+    use rocket::serde::json::json;
+
+    /// The errors of a query run without data, the resolvers fail but the limits are checked
+    /// before they run.
+    async fn errors(request: impl Into<Request>) -> Vec<String> {
+        let response = build(false).finish().execute(request).await;
+
+        response.errors.into_iter().map(|e| e.message).collect()
+    }
+
+    #[rocket::async_test]
+    async fn it_limits_the_depth() {
+        // Lists of a single item, so only the depth is over its limit.
+        let query = r#"{ photo(id: "1") { similar(limit: 1) { similar(limit: 1) {
+            similar(limit: 1) { similar(limit: 1) { similar(limit: 1) { similar(limit: 1) {
+            similar(limit: 1) { id } } } } } } } } }"#;
+
+        let errors = errors(query).await;
+        assert!(errors[0].contains("nested too deep"), "{:?}", errors);
+    }
+
+    #[rocket::async_test]
+    async fn it_limits_the_complexity() {
+        let query = r#"{ photos(input: { tag: "portfolio" }) { tags { photos { id title } } } }"#;
+
+        let errors = errors(query).await;
+        assert!(errors[0].contains("too complex"), "{:?}", errors);
+    }
+
+    #[rocket::async_test]
+    async fn it_accepts_the_web_queries() {
+        let portfolio = include_str!("../../../web/src/gql/queries/get-portfolio.graphql");
+        let variables = Variables::from_json(json!({ "tag": "portfolio", "max": 50 }));
+
+        let errors = errors(Request::new(portfolio).variables(variables)).await;
+        assert!(errors[0].contains("Context"), "{:?}", errors);
+    }
+}
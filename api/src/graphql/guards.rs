@@ -0,0 +1,24 @@
+use crate::auth::Credentials;
+use async_graphql::{Context, Guard, Result};
+use core_victorhqc_com::models::api_token::Scope;
+
+/// Rejects the field unless the request has a token with the scope, e.g.
+/// `#[graphql(guard = "ScopeGuard::new(Scope::WritePhotos)")]`.
+pub struct ScopeGuard {
+    scope: Scope,
+}
+
+impl ScopeGuard {
+    pub fn new(scope: Scope) -> Self {
+        ScopeGuard { scope }
+    }
+}
+
+impl Guard for ScopeGuard {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        let credentials = ctx.data::<Credentials>()?;
+        credentials.authorize(self.scope)?;
+
+        Ok(())
+    }
+}
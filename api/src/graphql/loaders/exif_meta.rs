@@ -11,11 +11,17 @@ use std::{
     hash::{Hash, Hasher},
     sync::Arc,
 };
+use tracing::{debug, instrument};
 
 impl Loader<ExifMetaByPhotoId> for AppLoader {
     type Value = GqlExifMeta;
     type Error = Arc<Error>;
 
+    #[instrument(
+        name = "dataloader",
+        skip_all,
+        fields(loader = "ExifMetaByPhotoId", keys = ids.len())
+    )]
     async fn load(
         &self,
         ids: &[ExifMetaByPhotoId],
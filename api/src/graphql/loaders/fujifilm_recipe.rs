@@ -11,11 +11,17 @@ use std::{
     hash::{Hash, Hasher},
     sync::Arc,
 };
+use tracing::{debug, instrument};
 
 impl Loader<FujifilmRecipeByExifMetaId> for AppLoader {
     type Value = GqlFujifilmRecipe;
     type Error = Arc<Error>;
 
+    #[instrument(
+        name = "dataloader",
+        skip_all,
+        fields(loader = "FujifilmRecipeByExifMetaId", keys = ids.len())
+    )]
     async fn load(
         &self,
         ids: &[FujifilmRecipeByExifMetaId],
@@ -0,0 +1,117 @@
+use crate::{graphql::loaders::AppLoader, graphql::models::GearStats as GqlGearStats};
+use async_graphql::{Result, dataloader::Loader};
+use core_victorhqc_com::{
+    models::gear_stats::{GearStats, db::Error as DbError},
+    sqlx::Error as SqlxError,
+};
+use snafu::prelude::*;
+use std::{
+    cmp::{Eq, PartialEq},
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+use tracing::instrument;
+
+impl Loader<GearStatsByCameraId> for AppLoader {
+    type Value = GqlGearStats;
+    type Error = Arc<Error>;
+
+    #[instrument(
+        name = "dataloader",
+        skip_all,
+        fields(loader = "GearStatsByCameraId", keys = ids.len())
+    )]
+    async fn load(
+        &self,
+        ids: &[GearStatsByCameraId],
+    ) -> Result<HashMap<GearStatsByCameraId, Self::Value>, Self::Error> {
+        let ids: Vec<String> = ids.iter().map(|i| i.0.clone()).collect();
+
+        let mut conn = self.pool.acquire().await.context(ConnectionSnafu)?;
+
+        let values = GearStats::find_by_camera_ids(&mut conn, &ids)
+            .await
+            .context(QuerySnafu)?;
+
+        let mut grouped: HashMap<GearStatsByCameraId, Self::Value> = HashMap::new();
+        for (camera_id, stats) in values.into_iter() {
+            let id = GearStatsByCameraId::new(&camera_id);
+
+            grouped.insert(id, stats.into());
+        }
+
+        Ok(grouped)
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct GearStatsByCameraId(String);
+
+impl GearStatsByCameraId {
+    pub fn new(id: &str) -> Self {
+        Self(String::from(id))
+    }
+}
+
+impl Hash for GearStatsByCameraId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl Loader<GearStatsByLensId> for AppLoader {
+    type Value = GqlGearStats;
+    type Error = Arc<Error>;
+
+    #[instrument(
+        name = "dataloader",
+        skip_all,
+        fields(loader = "GearStatsByLensId", keys = ids.len())
+    )]
+    async fn load(
+        &self,
+        ids: &[GearStatsByLensId],
+    ) -> Result<HashMap<GearStatsByLensId, Self::Value>, Self::Error> {
+        let ids: Vec<String> = ids.iter().map(|i| i.0.clone()).collect();
+
+        let mut conn = self.pool.acquire().await.context(ConnectionSnafu)?;
+
+        let values = GearStats::find_by_lens_ids(&mut conn, &ids)
+            .await
+            .context(QuerySnafu)?;
+
+        let mut grouped: HashMap<GearStatsByLensId, Self::Value> = HashMap::new();
+        for (lens_id, stats) in values.into_iter() {
+            let id = GearStatsByLensId::new(&lens_id);
+
+            grouped.insert(id, stats.into());
+        }
+
+        Ok(grouped)
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct GearStatsByLensId(String);
+
+impl GearStatsByLensId {
+    pub fn new(id: &str) -> Self {
+        Self(String::from(id))
+    }
+}
+
+impl Hash for GearStatsByLensId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to query for gear stats in loader: {}", source))]
+    QueryError { source: DbError },
+
+    #[snafu(display("Failed to acquire connection in gear stats loader: {}", source))]
+    Connection { source: SqlxError },
+}
@@ -2,7 +2,9 @@ use core_victorhqc_com::sqlx::{Pool, Sqlite};
 
 pub mod exif_meta;
 pub mod fujifilm_recipe;
+pub mod gear_stats;
 pub mod photo;
+pub mod photo_rendition;
 pub mod tag;
 
 pub struct AppLoader {
@@ -1,22 +1,28 @@
 use crate::{graphql::loaders::AppLoader, graphql::models::Photo as GqlPhoto};
 use async_graphql::{Result, dataloader::Loader};
 use core_victorhqc_com::{
-    models::photo::{Photo, db::Error as DbError},
+    models::photo::{Photo, db::Error as DbError, rank_similar},
     sqlx::Error as SqlxError,
     utils::hashmap::InsertOrPush,
 };
 use snafu::prelude::*;
 use std::{
     cmp::{Eq, PartialEq},
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     hash::{Hash, Hasher},
     sync::Arc,
 };
+use tracing::instrument;
 
 impl Loader<PhotoByTagId> for AppLoader {
     type Value = Vec<GqlPhoto>;
     type Error = Arc<Error>;
 
+    #[instrument(
+        name = "dataloader",
+        skip_all,
+        fields(loader = "PhotoByTagId", keys = ids.len())
+    )]
     async fn load(
         &self,
         ids: &[PhotoByTagId],
@@ -57,6 +63,125 @@ impl Hash for PhotoByTagId {
     }
 }
 
+impl Loader<PhotoByFujifilmRecipeId> for AppLoader {
+    type Value = Vec<GqlPhoto>;
+    type Error = Arc<Error>;
+
+    #[instrument(
+        name = "dataloader",
+        skip_all,
+        fields(loader = "PhotoByFujifilmRecipeId", keys = ids.len())
+    )]
+    async fn load(
+        &self,
+        ids: &[PhotoByFujifilmRecipeId],
+    ) -> Result<HashMap<PhotoByFujifilmRecipeId, Self::Value>, Self::Error> {
+        let ids: Vec<String> = ids.iter().map(|i| i.0.clone()).collect();
+
+        let mut conn = self.pool.acquire().await.context(ConnectionSnafu)?;
+
+        let values = Photo::find_by_fuji_recipe_ids(&mut conn, &ids)
+            .await
+            .context(QuerySnafu)?;
+
+        let mut grouped: HashMap<PhotoByFujifilmRecipeId, Self::Value> = HashMap::new();
+
+        for (recipe_id, photo) in values.into_iter() {
+            let id = PhotoByFujifilmRecipeId::new(&recipe_id);
+            let gql: GqlPhoto = photo.into();
+
+            grouped.insert_or_push(id, gql);
+        }
+
+        Ok(grouped)
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct PhotoByFujifilmRecipeId(String);
+
+impl PhotoByFujifilmRecipeId {
+    pub fn new(id: &str) -> Self {
+        Self(String::from(id))
+    }
+}
+
+impl Hash for PhotoByFujifilmRecipeId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl Loader<SimilarToPhoto> for AppLoader {
+    type Value = Vec<GqlPhoto>;
+    type Error = Arc<Error>;
+
+    /// The hashes are loaded once for the whole batch, then only the closest photos are loaded.
+    #[instrument(
+        name = "dataloader",
+        skip_all,
+        fields(loader = "SimilarToPhoto", keys = keys.len())
+    )]
+    async fn load(
+        &self,
+        keys: &[SimilarToPhoto],
+    ) -> Result<HashMap<SimilarToPhoto, Self::Value>, Self::Error> {
+        let mut conn = self.pool.acquire().await.context(ConnectionSnafu)?;
+
+        let hashes = Photo::find_perceptual_hashes(&mut conn)
+            .await
+            .context(QuerySnafu)?;
+
+        let ranked: Vec<(&SimilarToPhoto, Vec<String>)> = keys
+            .iter()
+            .map(|k| (k, rank_similar(&hashes, &k.id, k.hash, k.limit)))
+            .collect();
+
+        let ids: HashSet<&String> = ranked.iter().flat_map(|(_, ids)| ids).collect();
+        let ids: Vec<String> = ids.into_iter().cloned().collect();
+
+        let photos: HashMap<String, Photo> = Photo::find_by_ids(&mut conn, &ids)
+            .await
+            .context(QuerySnafu)?
+            .into_iter()
+            .map(|p| (p.id.clone(), p))
+            .collect();
+
+        let similar = ranked
+            .into_iter()
+            .map(|(key, ids)| {
+                let photos = ids
+                    .iter()
+                    .filter_map(|id| photos.get(id))
+                    .map(|p| p.clone().into())
+                    .collect();
+
+                (key.clone(), photos)
+            })
+            .collect();
+
+        Ok(similar)
+    }
+}
+
+/// The photos that look like the one with this id and perceptual hash, up to `limit`.
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct SimilarToPhoto {
+    id: String,
+    hash: u64,
+    limit: usize,
+}
+
+impl SimilarToPhoto {
+    pub fn new(id: &str, hash: u64, limit: usize) -> Self {
+        Self {
+            id: String::from(id),
+            hash,
+            limit,
+        }
+    }
+}
+
 #[derive(Debug, Snafu)]
 pub enum Error {
     #[snafu(display("Failed to query for photos in loader: {}", source))]
@@ -0,0 +1,73 @@
+use crate::graphql::{loaders::AppLoader, models::PhotoRendition as GqlPhotoRendition};
+use async_graphql::{Result, dataloader::Loader};
+use core_victorhqc_com::{
+    models::photo_rendition::{PhotoRendition, db::Error as DbError},
+    sqlx::Error as SqlxError,
+    utils::hashmap::InsertOrPush,
+};
+use snafu::prelude::*;
+use std::{
+    cmp::{Eq, PartialEq},
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+use tracing::instrument;
+
+impl Loader<RenditionByPhotoId> for AppLoader {
+    type Value = Vec<GqlPhotoRendition>;
+    type Error = Arc<Error>;
+
+    #[instrument(
+        name = "dataloader",
+        skip_all,
+        fields(loader = "RenditionByPhotoId", keys = ids.len())
+    )]
+    async fn load(
+        &self,
+        ids: &[RenditionByPhotoId],
+    ) -> Result<HashMap<RenditionByPhotoId, Self::Value>, Self::Error> {
+        let ids: Vec<String> = ids.iter().map(|i| i.0.clone()).collect();
+
+        let mut conn = self.pool.acquire().await.context(ConnectionSnafu)?;
+
+        let values = PhotoRendition::find_by_photo_ids(&mut conn, &ids)
+            .await
+            .context(QuerySnafu)?;
+
+        let mut grouped: HashMap<RenditionByPhotoId, Self::Value> = HashMap::new();
+
+        for rendition in values.into_iter() {
+            let id = RenditionByPhotoId::new(&rendition.photo_id);
+            let gql_rendition: GqlPhotoRendition = rendition.into();
+
+            grouped.insert_or_push(id, gql_rendition);
+        }
+
+        Ok(grouped)
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct RenditionByPhotoId(String);
+
+impl RenditionByPhotoId {
+    pub fn new(id: &str) -> Self {
+        Self(String::from(id))
+    }
+}
+
+impl Hash for RenditionByPhotoId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to query for renditions in loader: {}", source))]
+    Query { source: DbError },
+
+    #[snafu(display("Failed to acquire connection in rendition loader: {}", source))]
+    Connection { source: SqlxError },
+}
@@ -12,11 +12,17 @@ use std::{
     hash::{Hash, Hasher},
     sync::Arc,
 };
+use tracing::instrument;
 
 impl Loader<TagById> for AppLoader {
     type Value = GqlTag;
     type Error = Arc<Error>;
 
+    #[instrument(
+        name = "dataloader",
+        skip_all,
+        fields(loader = "TagById", keys = ids.len())
+    )]
     async fn load(&self, ids: &[TagById]) -> Result<HashMap<TagById, Self::Value>, Self::Error> {
         let ids: Vec<String> = ids.iter().map(|i| i.0.clone()).collect();
 
@@ -56,6 +62,11 @@ impl Loader<TagByPhotoId> for AppLoader {
     type Value = Vec<GqlTag>;
     type Error = Arc<Error>;
 
+    #[instrument(
+        name = "dataloader",
+        skip_all,
+        fields(loader = "TagByPhotoId", keys = ids.len())
+    )]
     async fn load(
         &self,
         ids: &[TagByPhotoId],
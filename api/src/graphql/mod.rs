@@ -1,8 +1,11 @@
 pub mod context;
 pub mod graph;
+pub mod guards;
 pub mod loaders;
 mod models;
+pub mod persisted_queries;
 pub mod queries;
+pub mod response_cache;
 pub mod routes;
 #[cfg(debug_assertions)]
 mod sdl_gen;
@@ -0,0 +1,23 @@
+use async_graphql::{ID, SimpleObject};
+use core_victorhqc_com::models::api_token::ApiToken as ApiTokenModel;
+use time::format_description::well_known::Rfc3339;
+
+#[derive(SimpleObject, Clone)]
+pub struct ApiToken {
+    pub id: ID,
+    pub name: String,
+    pub scopes: Vec<String>,
+    /// RFC 3339, `null` when the token never expires.
+    pub expires_at: Option<String>,
+}
+
+impl From<&ApiTokenModel> for ApiToken {
+    fn from(value: &ApiTokenModel) -> Self {
+        ApiToken {
+            id: value.id.clone().into(),
+            name: value.name.clone(),
+            scopes: value.scopes.iter().map(|s| s.to_string()).collect(),
+            expires_at: value.expires_at.and_then(|e| e.format(&Rfc3339).ok()),
+        }
+    }
+}
@@ -1,17 +1,46 @@
-use async_graphql::{ID, SimpleObject, Union};
+use crate::graphql::{
+    graph::list_cost,
+    loaders::{AppLoader, photo::PhotoByFujifilmRecipeId},
+    models::Photo,
+};
+use async_graphql::{
+    ComplexObject, Context, ID, Result, SimpleObject, Union, dataloader::DataLoader,
+};
 use core_victorhqc_com::models::fujifilm::{
-    FujifilmRecipe as FujifilmRecipeModel, Settings as SettingsModel,
+    FujifilmRecipe as FujifilmRecipeModel, Settings as SettingsModel, file::RecipeFile,
 };
 use fuji::recipe::Settings;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static SHIFT_VALUE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[+-]?\d+").unwrap());
 
 #[derive(SimpleObject, Clone)]
+#[graphql(complex)]
 pub struct FujifilmRecipe {
     pub id: ID,
     pub name: String,
-    pub src: String,
+    pub author: Option<String>,
+    pub src: Option<String>,
     pub film_simulation: String,
     pub sensor: String,
     pub settings: FujifilmRecipeSettings,
+    /// The same settings for every sensor generation, the ones the sensor doesn't support are
+    /// null.
+    pub settings_flat: FujifilmRecipeSettingsFlat,
+}
+
+#[ComplexObject]
+impl FujifilmRecipe {
+    #[graphql(complexity = "list_cost(None, child_complexity)")]
+    async fn photos(&self, ctx: &Context<'_>) -> Result<Vec<Photo>> {
+        let loader = ctx.data_unchecked::<DataLoader<AppLoader>>();
+        let id = PhotoByFujifilmRecipeId::new(&self.id);
+
+        let photos = (loader.load_one(id).await?).unwrap_or_default();
+
+        Ok(photos)
+    }
 }
 
 #[derive(Union, Clone)]
@@ -23,6 +52,32 @@ pub enum FujifilmRecipeSettings {
     TransV(FujifilmRecipeTransVSettings),
 }
 
+#[derive(SimpleObject, PartialEq, Clone)]
+pub struct FujifilmRecipeSettingsFlat {
+    pub film_simulation: Option<String>,
+    pub monochromatic_color: Option<String>,
+    pub white_balance: Option<String>,
+    pub white_balance_shift: Option<WhiteBalanceShift>,
+    pub dynamic_range: Option<String>,
+    pub d_range_priority: Option<String>,
+    pub highlight_tone: Option<f64>,
+    pub shadow_tone: Option<f64>,
+    pub color: Option<i64>,
+    pub sharpness: Option<i64>,
+    pub clarity: Option<i64>,
+    pub high_iso_noise_reduction: Option<i64>,
+    pub grain_strength: Option<String>,
+    pub grain_size: Option<String>,
+    pub color_chrome_effect: Option<String>,
+    pub color_chrome_fx_blue: Option<String>,
+}
+
+#[derive(SimpleObject, PartialEq, Clone)]
+pub struct WhiteBalanceShift {
+    pub red: i64,
+    pub blue: i64,
+}
+
 #[derive(SimpleObject, PartialEq, Clone)]
 pub struct FujifilmRecipeTransISettings {
     pub white_balance: String,
@@ -94,14 +149,53 @@ impl From<FujifilmRecipeModel> for FujifilmRecipe {
         FujifilmRecipe {
             id: model.id.clone().into(),
             name: model.name.clone(),
+            author: model.author.clone(),
             src: model.src.clone(),
             film_simulation: model.details().film_simulation.to_string(),
             sensor: model.details().sensor.to_string(),
             settings: settings.into(),
+            settings_flat: RecipeFile::from(&model).into(),
         }
     }
 }
 
+impl From<RecipeFile> for FujifilmRecipeSettingsFlat {
+    fn from(value: RecipeFile) -> Self {
+        FujifilmRecipeSettingsFlat {
+            film_simulation: Some(value.film_simulation),
+            monochromatic_color: value.monochromatic_color,
+            white_balance: Some(value.white_balance),
+            white_balance_shift: WhiteBalanceShift::parse(&value.white_balance_shift),
+            dynamic_range: Some(value.dynamic_range),
+            d_range_priority: value.d_range_priority,
+            highlight_tone: Some(value.highlight_tone),
+            shadow_tone: Some(value.shadow_tone),
+            color: Some(value.color),
+            sharpness: Some(value.sharpness),
+            clarity: value.clarity,
+            high_iso_noise_reduction: Some(value.high_iso_noise_reduction),
+            grain_strength: value.grain_strength,
+            grain_size: value.grain_size,
+            color_chrome_effect: value.color_chrome_effect,
+            color_chrome_fx_blue: value.color_chrome_fx_blue,
+        }
+    }
+}
+
+impl WhiteBalanceShift {
+    /// Reads the shift from the text stored in the database, where red always comes before blue.
+    fn parse(value: &str) -> Option<Self> {
+        let mut values = SHIFT_VALUE
+            .find_iter(value)
+            .filter_map(|m| m.as_str().parse::<i64>().ok());
+
+        Some(WhiteBalanceShift {
+            red: values.next()?,
+            blue: values.next()?,
+        })
+    }
+}
+
 impl From<SettingsModel> for FujifilmRecipeSettings {
     fn from(value: SettingsModel) -> Self {
         match value.0 {
@@ -0,0 +1,125 @@
+use crate::graphql::{
+    loaders::{
+        AppLoader,
+        gear_stats::{GearStatsByCameraId, GearStatsByLensId},
+    },
+    models::{CameraMaker, LensMaker},
+};
+use async_graphql::{ComplexObject, Context, ID, Result, SimpleObject, dataloader::DataLoader};
+use core_victorhqc_com::models::{
+    camera::Camera as CameraModel,
+    gear_stats::{GearStats as GearStatsModel, HistogramBucket as HistogramBucketModel},
+    lens::Lens as LensModel,
+};
+
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+pub struct Camera {
+    pub id: ID,
+    pub name: String,
+    pub display_name: String,
+    pub maker: CameraMaker,
+    pub mount: Option<String>,
+}
+
+#[ComplexObject]
+impl Camera {
+    async fn stats(&self, ctx: &Context<'_>) -> Result<GearStats> {
+        let loader = ctx.data_unchecked::<DataLoader<AppLoader>>();
+        let id = GearStatsByCameraId::new(&self.id);
+
+        let stats = (loader.load_one(id).await?).unwrap_or_default();
+
+        Ok(stats)
+    }
+}
+
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+pub struct Lens {
+    pub id: ID,
+    pub name: String,
+    pub display_name: String,
+    pub maker: LensMaker,
+    pub mount: Option<String>,
+    pub min_focal_length: Option<f64>,
+    pub max_focal_length: Option<f64>,
+    pub max_aperture: Option<f64>,
+    pub is_zoom: bool,
+}
+
+#[ComplexObject]
+impl Lens {
+    async fn stats(&self, ctx: &Context<'_>) -> Result<GearStats> {
+        let loader = ctx.data_unchecked::<DataLoader<AppLoader>>();
+        let id = GearStatsByLensId::new(&self.id);
+
+        let stats = (loader.load_one(id).await?).unwrap_or_default();
+
+        Ok(stats)
+    }
+}
+
+#[derive(SimpleObject, Clone, Default)]
+pub struct GearStats {
+    pub photo_count: i64,
+    pub most_used_focal_length: Option<f64>,
+    pub focal_lengths: Vec<HistogramBucket>,
+    pub apertures: Vec<HistogramBucket>,
+}
+
+#[derive(SimpleObject, Clone)]
+pub struct HistogramBucket {
+    pub value: f64,
+    pub count: i64,
+}
+
+impl From<CameraModel> for Camera {
+    fn from(value: CameraModel) -> Self {
+        Camera {
+            id: value.id.into(),
+            name: value.name,
+            display_name: value.display_name,
+            maker: value.maker.into(),
+            mount: value.mount,
+        }
+    }
+}
+
+impl From<LensModel> for Lens {
+    fn from(value: LensModel) -> Self {
+        let is_zoom = value.is_zoom();
+
+        Lens {
+            id: value.id.into(),
+            name: value.name,
+            display_name: value.display_name,
+            maker: value.maker.into(),
+            mount: value.mount,
+            min_focal_length: value.min_focal_length,
+            max_focal_length: value.max_focal_length,
+            max_aperture: value.max_aperture,
+            is_zoom,
+        }
+    }
+}
+
+impl From<GearStatsModel> for GearStats {
+    fn from(value: GearStatsModel) -> Self {
+        GearStats {
+            photo_count: value.photo_count,
+            most_used_focal_length: value.most_used_focal_length(),
+            focal_lengths: value.focal_lengths.into_iter().map(|b| b.into()).collect(),
+            apertures: value.apertures.into_iter().map(|b| b.into()).collect(),
+        }
+    }
+}
+
+impl From<HistogramBucketModel> for HistogramBucket {
+    fn from(value: HistogramBucketModel) -> Self {
+        HistogramBucket {
+            value: value.value,
+            count: value.count,
+        }
+    }
+}
@@ -1,9 +1,15 @@
+mod api_token;
 mod exif_meta;
 mod fujifilm_recipe;
+mod gear;
 mod photo;
+mod photo_rendition;
 mod tag;
 
+pub use api_token::*;
 pub use exif_meta::*;
 pub use fujifilm_recipe::*;
+pub use gear::*;
 pub use photo::*;
+pub use photo_rendition::*;
 pub use tag::*;
@@ -1,12 +1,24 @@
-use super::{ExifMeta, Tag};
-use crate::graphql::loaders::{AppLoader, exif_meta::ExifMetaByPhotoId, tag::TagByPhotoId};
+use super::{ExifMeta, PhotoRendition, Tag};
+use crate::graphql::{
+    graph::list_cost,
+    loaders::{
+        AppLoader, exif_meta::ExifMetaByPhotoId, photo::SimilarToPhoto,
+        photo_rendition::RenditionByPhotoId, tag::TagByPhotoId,
+    },
+};
 use async_graphql::{
     ComplexObject, Context, Enum, ID, Result, SimpleObject, dataloader::DataLoader,
 };
-use core_victorhqc_com::models::photo::{
-    FileType as CoreFileType, Orientation as CoreOrientation, Photo as CorePhoto,
+use core_victorhqc_com::{
+    models::photo::{
+        ColorSpace as CoreColorSpace, FileType as CoreFileType, Orientation as CoreOrientation,
+        Photo as CorePhoto,
+    },
+    utils::color::Palette as CorePalette,
 };
 
+const DEFAULT_SIMILAR_LIMIT: usize = 6;
+
 #[derive(Enum, Copy, Clone, Debug, Eq, PartialEq)]
 pub enum FileType {
     Jpeg,
@@ -24,6 +36,8 @@ impl From<CoreFileType> for FileType {
 pub enum Orientation {
     Landscape,
     Portrait,
+    Square,
+    Panorama,
 }
 
 impl From<CoreOrientation> for Orientation {
@@ -31,6 +45,8 @@ impl From<CoreOrientation> for Orientation {
         match value {
             CoreOrientation::Landscape => Orientation::Landscape,
             CoreOrientation::Portrait => Orientation::Portrait,
+            CoreOrientation::Square => Orientation::Square,
+            CoreOrientation::Panorama => Orientation::Panorama,
         }
     }
 }
@@ -40,6 +56,46 @@ impl From<Orientation> for CoreOrientation {
         match value {
             Orientation::Landscape => CoreOrientation::Landscape,
             Orientation::Portrait => CoreOrientation::Portrait,
+            Orientation::Square => CoreOrientation::Square,
+            Orientation::Panorama => CoreOrientation::Panorama,
+        }
+    }
+}
+
+/// Colour space of the original file, the served images are always sRGB.
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ColorSpace {
+    Srgb,
+    AdobeRgb,
+    DisplayP3,
+    Other,
+}
+
+impl From<CoreColorSpace> for ColorSpace {
+    fn from(value: CoreColorSpace) -> Self {
+        match value {
+            CoreColorSpace::Srgb => ColorSpace::Srgb,
+            CoreColorSpace::AdobeRgb => ColorSpace::AdobeRgb,
+            CoreColorSpace::DisplayP3 => ColorSpace::DisplayP3,
+            CoreColorSpace::Other => ColorSpace::Other,
+        }
+    }
+}
+
+/// Most common colours of a photo, as `#rrggbb`.
+#[derive(SimpleObject, Clone)]
+pub struct Palette {
+    /// From the most to the least common.
+    pub colors: Vec<String>,
+    /// The colour covering most of the photo, for tinted backgrounds.
+    pub dominant: String,
+}
+
+impl From<CorePalette> for Palette {
+    fn from(value: CorePalette) -> Self {
+        Palette {
+            colors: value.colors.iter().map(|c| c.to_string()).collect(),
+            dominant: value.dominant.to_string(),
         }
     }
 }
@@ -54,6 +110,12 @@ pub struct Photo {
     pub filetype: FileType,
     pub orientation: Orientation,
     pub blurhash: Option<String>,
+    pub color_space: Option<ColorSpace>,
+    /// Width over height, to lay the photo out before it loads.
+    pub aspect_ratio: Option<f64>,
+    pub palette: Option<Palette>,
+    #[graphql(skip)]
+    pub perceptual_hash: Option<u64>,
     pub created_at: String,
     pub updated_at: String,
     pub deleted: bool,
@@ -72,6 +134,7 @@ impl Photo {
         Ok(exif_meta)
     }
 
+    #[graphql(complexity = "list_cost(None, child_complexity)")]
     async fn tags(&self, ctx: &Context<'_>) -> Result<Vec<Tag>> {
         let loader = ctx.data_unchecked::<DataLoader<AppLoader>>();
         let id = TagByPhotoId::new(&self.id);
@@ -80,6 +143,34 @@ impl Photo {
 
         Ok(tags)
     }
+
+    /// Photos that look alike, the closest first. Empty when the photo has no perceptual hash
+    /// yet.
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn similar(&self, ctx: &Context<'_>, limit: Option<i32>) -> Result<Vec<Photo>> {
+        let Some(hash) = self.perceptual_hash else {
+            return Ok(Vec::new());
+        };
+
+        let loader = ctx.data_unchecked::<DataLoader<AppLoader>>();
+        let limit = limit.map_or(DEFAULT_SIMILAR_LIMIT, |l| l.max(0) as usize);
+        let key = SimilarToPhoto::new(&self.id, hash, limit);
+
+        let similar = loader.load_one(key).await?.unwrap_or_default();
+
+        Ok(similar)
+    }
+
+    /// Every file served for the photo, smallest first.
+    #[graphql(complexity = "list_cost(None, child_complexity)")]
+    async fn renditions(&self, ctx: &Context<'_>) -> Result<Vec<PhotoRendition>> {
+        let loader = ctx.data_unchecked::<DataLoader<AppLoader>>();
+        let id = RenditionByPhotoId::new(&self.id);
+
+        let renditions = loader.load_one(id).await?.unwrap_or_default();
+
+        Ok(renditions)
+    }
 }
 
 impl From<CorePhoto> for Photo {
@@ -92,6 +183,10 @@ impl From<CorePhoto> for Photo {
             filetype: photo.filetype.into(),
             orientation: photo.orientation.into(),
             blurhash: photo.blurhash,
+            color_space: photo.color_space.map(|c| c.into()),
+            aspect_ratio: photo.aspect_ratio,
+            palette: photo.palette.map(|p| p.into()),
+            perceptual_hash: photo.perceptual_hash,
             created_at: format!("{}", photo.created_at),
             updated_at: format!("{}", photo.updated_at),
             deleted: photo.deleted,
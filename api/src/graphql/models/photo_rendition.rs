@@ -0,0 +1,45 @@
+use async_graphql::{Enum, SimpleObject};
+use core_victorhqc_com::{
+    aws::image_size::ImageType as CoreImageType,
+    models::photo_rendition::PhotoRendition as CorePhotoRendition,
+};
+
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ImageType {
+    Jpeg,
+    Webp,
+}
+
+impl From<CoreImageType> for ImageType {
+    fn from(value: CoreImageType) -> Self {
+        match value {
+            CoreImageType::Jpeg => ImageType::Jpeg,
+            CoreImageType::Webp => ImageType::Webp,
+        }
+    }
+}
+
+/// One of the files served for a photo, at `/v1/images/<size>/<id>?kind=<type>`.
+#[derive(SimpleObject, Clone)]
+pub struct PhotoRendition {
+    pub size: String,
+    pub kind: ImageType,
+    /// Pixel dimensions, missing for renditions built before they were recorded.
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub bytes: i64,
+    pub md5: Option<String>,
+}
+
+impl From<CorePhotoRendition> for PhotoRendition {
+    fn from(value: CorePhotoRendition) -> Self {
+        PhotoRendition {
+            size: value.size.to_string(),
+            kind: value.kind.into(),
+            width: value.width,
+            height: value.height,
+            bytes: value.bytes,
+            md5: value.md5,
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::graphql::{
+    graph::list_cost,
     loaders::{AppLoader, photo::PhotoByTagId},
     models::Photo,
 };
@@ -17,6 +18,7 @@ pub struct Tag {
 
 #[ComplexObject]
 impl Tag {
+    #[graphql(complexity = "list_cost(None, child_complexity)")]
     async fn photos(&self, ctx: &Context<'_>) -> Result<Vec<Photo>> {
         let loader = ctx.data_unchecked::<DataLoader<AppLoader>>();
         let id = PhotoByTagId::new(&self.id);
@@ -0,0 +1,260 @@
+//! Automatic persisted queries, the Apollo protocol: a client sends the SHA-256 of a query in
+//! `extensions.persistedQuery`, and only sends the query itself when the server doesn't know the
+//! hash yet.
+use async_graphql::{
+    Request, ServerError, ServerResult,
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest},
+    from_value,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/// The queries of the `web` crate, persisted from the start so it works when only persisted
+/// queries are accepted.
+const WEB_QUERIES: [&str; 5] = [
+    include_str!("../../../web/src/gql/queries/get-gear.graphql"),
+    include_str!("../../../web/src/gql/queries/get-portfolio.graphql"),
+    include_str!("../../../web/src/gql/queries/get-recipe.graphql"),
+    include_str!("../../../web/src/gql/queries/get-recipes.graphql"),
+    include_str!("../../../web/src/gql/queries/get-similar-photos.graphql"),
+];
+
+/// Whether `name` is the operation of one of the queries of the `web` crate.
+pub fn is_web_operation(name: &str) -> bool {
+    WEB_QUERIES
+        .iter()
+        .any(|query| operation_name(query) == Some(name))
+}
+
+/// The name of a `query Name(...) { ... }` document.
+fn operation_name(query: &str) -> Option<&str> {
+    let rest = query.trim_start().strip_prefix("query")?.trim_start();
+    let end = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+
+    Some(&rest[..end]).filter(|name| !name.is_empty())
+}
+
+/// How many queries clients can persist, new ones aren't stored once it is reached.
+const MAX_QUERIES: usize = 1000;
+
+/// The error clients wait for to send the query again, along with its hash.
+pub const NOT_FOUND: &str = "PersistedQueryNotFound";
+
+pub struct PersistedQueries {
+    queries: Arc<RwLock<HashMap<String, String>>>,
+    persisted_only: bool,
+}
+
+impl PersistedQueries {
+    /// With `persisted_only` the queries of the `web` crate are the only ones accepted, clients
+    /// can't persist new ones.
+    pub fn new(persisted_only: bool) -> Self {
+        let queries = WEB_QUERIES
+            .iter()
+            .map(|query| (hash(query), query.to_string()))
+            .collect();
+
+        PersistedQueries {
+            queries: Arc::new(RwLock::new(queries)),
+            persisted_only,
+        }
+    }
+}
+
+impl ExtensionFactory for PersistedQueries {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(PersistedQueriesExtension {
+            queries: self.queries.clone(),
+            persisted_only: self.persisted_only,
+        })
+    }
+}
+
+struct PersistedQueriesExtension {
+    queries: Arc<RwLock<HashMap<String, String>>>,
+    persisted_only: bool,
+}
+
+#[derive(Deserialize)]
+struct PersistedQuery {
+    version: i32,
+    #[serde(rename = "sha256Hash")]
+    sha256_hash: String,
+}
+
+#[rocket::async_trait]
+impl Extension for PersistedQueriesExtension {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        mut request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        let persisted = match request.extensions.remove("persistedQuery") {
+            Some(value) => {
+                let persisted = from_value::<PersistedQuery>(value)
+                    .map_err(|_| ServerError::new("Invalid persistedQuery extension", None))?;
+                if persisted.version != 1 {
+                    return Err(ServerError::new(
+                        "Only version 1 of persisted queries is supported",
+                        None,
+                    ));
+                }
+
+                Some(persisted)
+            }
+            None => None,
+        };
+
+        match persisted {
+            Some(persisted) if request.query.is_empty() => {
+                let queries = self.queries.read().unwrap();
+                let query = queries
+                    .get(&persisted.sha256_hash)
+                    .ok_or_else(|| ServerError::new(NOT_FOUND, None))?;
+
+                request.query = query.clone();
+            }
+            Some(persisted) => {
+                let sha256_hash = hash(&request.query);
+                if persisted.sha256_hash != sha256_hash {
+                    return Err(ServerError::new("The hash does not match the query", None));
+                }
+
+                self.persist(sha256_hash, &request.query)?;
+            }
+            None if self.persisted_only => {
+                self.persist(hash(&request.query), &request.query)?;
+            }
+            None => {}
+        }
+
+        next.run(ctx, request).await
+    }
+}
+
+impl PersistedQueriesExtension {
+    /// Stores the query, or only checks it is already stored when new ones aren't accepted.
+    fn persist(&self, sha256_hash: String, query: &str) -> ServerResult<()> {
+        let mut queries = self.queries.write().unwrap();
+        if queries.contains_key(&sha256_hash) {
+            return Ok(());
+        }
+
+        if self.persisted_only {
+            return Err(ServerError::new(
+                "Only persisted queries are accepted",
+                None,
+            ));
+        }
+
+        if queries.len() < MAX_QUERIES {
+            queries.insert(sha256_hash, query.to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Hex encoded SHA-256 of a query, the hash clients send.
+fn hash(query: &str) -> String {
+    format!("{:x}", Sha256::digest(query.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema, Value};
+    use rocket::serde::json::json;
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn value(&self) -> i32 {
+            42
+        }
+    }
+
+    fn schema(persisted_only: bool) -> Schema<Query, EmptyMutation, EmptySubscription> {
+        Schema::build(Query, EmptyMutation, EmptySubscription)
+            .extension(PersistedQueries::new(persisted_only))
+            .finish()
+    }
+
+    fn by_hash(query: &str, sha256_hash: &str) -> Request {
+        let extension = json!({ "version": 1, "sha256Hash": sha256_hash });
+
+        let mut request = Request::new(query);
+        request.extensions.insert(
+            "persistedQuery".to_string(),
+            Value::from_json(extension).unwrap(),
+        );
+
+        request
+    }
+
+    #[rocket::async_test]
+    async fn it_asks_for_unknown_queries() {
+        let schema = schema(false);
+        let query = "{ value }";
+
+        let response = schema.execute(by_hash("", &hash(query))).await;
+        assert_eq!(response.errors[0].message, NOT_FOUND);
+
+        let response = schema.execute(by_hash(query, &hash(query))).await;
+        assert!(response.errors.is_empty());
+
+        let response = schema.execute(by_hash("", &hash(query))).await;
+        assert!(response.errors.is_empty());
+        assert_eq!(response.data.into_json().unwrap(), json!({ "value": 42 }));
+    }
+
+    #[rocket::async_test]
+    async fn it_rejects_mismatched_hashes() {
+        let response = schema(false).execute(by_hash("{ value }", "nope")).await;
+
+        assert_eq!(
+            response.errors[0].message,
+            "The hash does not match the query"
+        );
+    }
+
+    #[rocket::async_test]
+    async fn it_only_accepts_persisted_queries() {
+        let schema = schema(true);
+
+        let response = schema.execute("{ value }").await;
+        assert_eq!(
+            response.errors[0].message,
+            "Only persisted queries are accepted"
+        );
+
+        let response = schema
+            .execute(by_hash("{ value }", &hash("{ value }")))
+            .await;
+        assert_eq!(
+            response.errors[0].message,
+            "Only persisted queries are accepted"
+        );
+
+        // Not valid against this schema, but it gets past the extension.
+        let portfolio = WEB_QUERIES[1];
+        let response = schema.execute(by_hash("", &hash(portfolio))).await;
+        assert_ne!(response.errors[0].message, NOT_FOUND);
+    }
+
+    #[test]
+    fn it_knows_the_web_operations() {
+        assert!(is_web_operation("GetPortfolio"));
+        assert!(is_web_operation("GetGear"));
+        assert!(!is_web_operation("Get"));
+        assert!(!is_web_operation("AnythingElse"));
+    }
+}
@@ -0,0 +1,21 @@
+use crate::{
+    auth::Credentials,
+    graphql::{guards::ScopeGuard, models::ApiToken as GqlApiToken},
+};
+use async_graphql::{Context, Object, Result};
+use core_victorhqc_com::models::api_token::Scope;
+
+#[derive(Default)]
+pub struct ApiTokenQuery;
+
+#[Object]
+impl ApiTokenQuery {
+    /// The token sent in the `Authorization` header, to check it before using it elsewhere.
+    #[graphql(guard = "ScopeGuard::new(Scope::Read)")]
+    async fn token(&self, ctx: &Context<'_>) -> Result<GqlApiToken> {
+        let credentials = ctx.data::<Credentials>()?;
+        let token = credentials.authorize(Scope::Read)?;
+
+        Ok(token.into())
+    }
+}
@@ -1,5 +1,7 @@
-use crate::graphql::{context::get_conn, models::FujifilmRecipe as GQLFujifilmRecipe};
-use async_graphql::{Context, Object, Result};
+use crate::graphql::{
+    context::get_conn, graph::list_cost, models::FujifilmRecipe as GQLFujifilmRecipe,
+};
+use async_graphql::{Context, ID, Object, Result};
 use core_victorhqc_com::models::fujifilm::FujifilmRecipe;
 
 #[derive(Default)]
@@ -7,13 +9,32 @@ pub struct FujifilmRecipeQuery;
 
 #[Object]
 impl FujifilmRecipeQuery {
+    /// The recipe with the given id, `null` when there's none.
+    pub async fn fujifilm_recipe(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+    ) -> Result<Option<GQLFujifilmRecipe>> {
+        let mut conn = get_conn(ctx).await?;
+        let recipe = FujifilmRecipe::find_by_id(&mut conn, &id).await?;
+
+        Ok(recipe.map(|r| r.into()))
+    }
+
+    /// All the recipes, or only the ones using the given film simulation.
+    #[graphql(complexity = "list_cost(None, child_complexity)")]
     pub async fn fujifilm_recipes(
         &self,
         ctx: &Context<'_>,
-        film_simulation: String,
+        film_simulation: Option<String>,
     ) -> Result<Vec<GQLFujifilmRecipe>> {
         let mut conn = get_conn(ctx).await?;
-        let recipes = FujifilmRecipe::find_by_film_simulation(&mut conn, &film_simulation).await?;
+        let recipes = match film_simulation {
+            Some(film_simulation) => {
+                FujifilmRecipe::find_by_film_simulation(&mut conn, &film_simulation).await?
+            }
+            None => FujifilmRecipe::find_all(&mut conn).await?,
+        };
         let recipes = recipes.into_iter().map(|r| r.into()).collect();
 
         Ok(recipes)
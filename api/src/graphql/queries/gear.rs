@@ -0,0 +1,29 @@
+use crate::graphql::{
+    context::get_conn,
+    graph::list_cost,
+    models::{Camera as GqlCamera, Lens as GqlLens},
+};
+use async_graphql::{Context, Object, Result};
+use core_victorhqc_com::models::{camera::Camera, lens::Lens};
+
+#[derive(Default)]
+pub struct GearQuery;
+
+#[Object]
+impl GearQuery {
+    #[graphql(complexity = "list_cost(None, child_complexity)")]
+    async fn cameras(&self, ctx: &Context<'_>) -> Result<Vec<GqlCamera>> {
+        let mut conn = get_conn(ctx).await?;
+        let cameras = Camera::find_all(&mut conn).await?;
+
+        Ok(cameras.into_iter().map(|c| c.into()).collect())
+    }
+
+    #[graphql(complexity = "list_cost(None, child_complexity)")]
+    async fn lenses(&self, ctx: &Context<'_>) -> Result<Vec<GqlLens>> {
+        let mut conn = get_conn(ctx).await?;
+        let lenses = Lens::find_all(&mut conn).await?;
+
+        Ok(lenses.into_iter().map(|l| l.into()).collect())
+    }
+}
@@ -1,7 +1,9 @@
 use async_graphql::MergedObject;
 
+mod api_token;
 mod exif_meta;
 mod fujifilm_recipe;
+mod gear;
 mod photo;
 mod tag;
 
@@ -11,4 +13,6 @@ pub struct RootQuery(
     exif_meta::ExifMetaQuery,
     tag::TagQuery,
     fujifilm_recipe::FujifilmRecipeQuery,
+    gear::GearQuery,
+    api_token::ApiTokenQuery,
 );
@@ -1,9 +1,18 @@
 use crate::graphql::{
     context::get_conn,
+    graph::list_cost,
     models::{Orientation, Photo as GqlPhoto},
 };
 use async_graphql::{Context, ID, InputObject, Object, Result};
-use core_victorhqc_com::models::{photo::Photo, tag::Tag};
+use core_victorhqc_com::{
+    models::{photo::Photo, tag::Tag},
+    utils::color::Rgb,
+};
+use std::str::FromStr;
+
+/// ΔE within which a palette colour counts as the one searched for, close enough to tell apart
+/// the hue but not the exact shade.
+const DEFAULT_COLOR_DISTANCE: f64 = 20.0;
 
 #[derive(Default)]
 pub struct PhotoQuery;
@@ -13,6 +22,10 @@ pub struct PhotosQueryInput {
     pub tag: Option<String>,
     pub orientation: Option<Orientation>,
     pub max_results: Option<i32>,
+    /// Only photos with a palette colour close to this one, as `#rrggbb`.
+    pub color: Option<String>,
+    /// How close the palette colour has to be, as a CIE76 ΔE. 20 by default.
+    pub color_distance: Option<f64>,
 }
 
 #[Object]
@@ -24,6 +37,7 @@ impl PhotoQuery {
         Ok(photo.into())
     }
 
+    #[graphql(complexity = "list_cost(input.max_results, child_complexity)")]
     pub async fn photos(
         &self,
         ctx: &Context<'_>,
@@ -31,14 +45,22 @@ impl PhotoQuery {
     ) -> Result<Vec<GqlPhoto>> {
         let mut conn = get_conn(ctx).await?;
 
-        let photos = if let Some(tag) = input.tag {
+        let color = input.color.as_deref().map(Rgb::from_str).transpose()?;
+        // The colour is filtered after querying, the limit has to wait until then.
+        let max_results = if color.is_some() {
+            None
+        } else {
+            input.max_results
+        };
+
+        let photos: Vec<Photo> = if let Some(tag) = input.tag {
             let tag = Tag::find_by_name(&mut conn, &tag).await?;
             let ids = vec![tag.id];
 
             Photo::find_by_tag_ids(
                 &mut conn,
                 &ids,
-                input.max_results,
+                max_results,
                 input.orientation.map(|o| o.into()),
             )
             .await?
@@ -49,7 +71,23 @@ impl PhotoQuery {
             Photo::find_all(&mut conn, input.orientation.map(|o| o.into())).await?
         };
 
-        let photos = photos.into_iter().map(|p| p.into()).collect();
+        let photos = match color {
+            Some(color) => {
+                let distance = input.color_distance.unwrap_or(DEFAULT_COLOR_DISTANCE);
+
+                photos
+                    .into_iter()
+                    .filter(|p| {
+                        p.palette
+                            .as_ref()
+                            .is_some_and(|p| p.matches(&color, distance))
+                    })
+                    .take(input.max_results.map_or(usize::MAX, |m| m.max(0) as usize))
+                    .map(|p| p.into())
+                    .collect()
+            }
+            None => photos.into_iter().map(|p| p.into()).collect(),
+        };
 
         Ok(photos)
     }
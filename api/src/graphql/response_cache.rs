@@ -0,0 +1,470 @@
+//! Responses of GraphQL queries kept in memory. The data only changes when a photo is published,
+//! so the same queries are answered without reaching the database until then.
+use super::graph::RootSchema;
+use async_graphql::{
+    Name, Positioned, Request, ServerResult, Value,
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextResolve, ResolveInfo},
+    parser::{
+        parse_query,
+        types::{Directive, ExecutableDocument, Selection, SelectionSet},
+    },
+};
+use rocket::{serde::json::to_string, tokio::time::interval};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Display, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant, SystemTime},
+};
+use tracing::info;
+
+/// How many responses are kept, the oldest one makes room for a new one once it is reached.
+const MAX_ENTRIES: usize = 1000;
+
+/// How often the database file is checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Types whose responses depend on the credentials of the request, they are never cached.
+const PRIVATE_KINDS: [&str; 1] = ["ApiToken"];
+
+/// `Cache-Control` of the cached responses. Clients revalidate them with their `ETag` after a
+/// minute.
+pub const CACHE_CONTROL: &str = "public, max-age=60";
+
+/// Something that changed, by its GraphQL type. Without an id, every entity of the type changed,
+/// e.g. a new one was added to its lists.
+pub struct Entity {
+    kind: &'static str,
+    id: Option<String>,
+}
+
+impl Entity {
+    pub fn new(kind: &'static str, id: impl Into<String>) -> Self {
+        Entity {
+            kind,
+            id: Some(id.into()),
+        }
+    }
+
+    pub fn kind(kind: &'static str) -> Self {
+        Entity { kind, id: None }
+    }
+}
+
+/// The types a response has fields of, and the ids it shows of each.
+#[derive(Default)]
+struct Shown {
+    kinds: HashSet<String>,
+    ids: HashSet<(String, String)>,
+}
+
+impl Shown {
+    fn shows(&self, entity: &Entity) -> bool {
+        if !self.kinds.contains(entity.kind) {
+            return false;
+        }
+
+        match &entity.id {
+            None => true,
+            // A response without any id of the type may still show the entity.
+            Some(id) => {
+                self.ids.contains(&(entity.kind.to_string(), id.clone()))
+                    || !self.ids.iter().any(|(kind, _)| kind == entity.kind)
+            }
+        }
+    }
+}
+
+/// What a response shows, filled while it resolves. Each request gets its own as data.
+#[derive(Clone, Default)]
+struct Collector(Arc<Mutex<Shown>>);
+
+struct Entry {
+    body: String,
+    etag: String,
+    shown: Shown,
+    stored: Instant,
+}
+
+/// A serialized response, with an `etag` when it is cached.
+pub struct Cached {
+    pub body: String,
+    pub etag: Option<String>,
+}
+
+#[derive(Clone, Default)]
+pub struct ResponseCache {
+    entries: Arc<RwLock<HashMap<String, Entry>>>,
+}
+
+impl ResponseCache {
+    /// The cached response of the request, or the response of running it. Responses without
+    /// errors are stored, unless they depend on the credentials.
+    pub async fn execute(&self, schema: &RootSchema, request: Request) -> Cached {
+        let key = key(&request);
+        if let Some(cached) = self.get(&key) {
+            return cached;
+        }
+
+        let collector = Collector::default();
+        let response = schema.execute(request.data(collector.clone())).await;
+        let body = to_string(&response).unwrap_or_default();
+
+        let shown = std::mem::take(&mut *collector.0.lock().unwrap());
+        let private = PRIVATE_KINDS.iter().any(|k| shown.kinds.contains(*k));
+        if response.is_err() || private {
+            return Cached { body, etag: None };
+        }
+
+        let etag = format!("{:x}", Sha256::digest(body.as_bytes()));
+        let entry = Entry {
+            body: body.clone(),
+            etag: etag.clone(),
+            shown,
+            stored: Instant::now(),
+        };
+        self.store(key, entry);
+
+        Cached {
+            body,
+            etag: Some(etag),
+        }
+    }
+
+    /// Drops the responses showing any of the entities.
+    pub fn invalidate(&self, entities: &[Entity]) {
+        let mut entries = self.entries.write().unwrap();
+        entries.retain(|_, entry| !entities.iter().any(|e| entry.shown.shows(e)));
+    }
+
+    pub fn clear(&self) {
+        self.entries.write().unwrap().clear();
+    }
+
+    fn store(&self, key: String, entry: Entry) {
+        let mut entries = self.entries.write().unwrap();
+        if entries.len() >= MAX_ENTRIES && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+
+        entries.insert(key, entry);
+    }
+
+    fn get(&self, key: &str) -> Option<Cached> {
+        let entries = self.entries.read().unwrap();
+
+        entries.get(key).map(|entry| Cached {
+            body: entry.body.clone(),
+            etag: Some(entry.etag.clone()),
+        })
+    }
+}
+
+/// Clears the cache whenever the database file changes after startup, e.g. when the CLI
+/// publishes a photo or a deploy replaces the database.
+pub async fn watch_database(path: PathBuf, cache: ResponseCache) {
+    let mut last = fingerprint(&path);
+    let mut interval = interval(WATCH_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let current = fingerprint(&path);
+        if current != last {
+            info!("{} changed, clearing the GraphQL cache", path.display());
+            cache.clear();
+            last = current;
+        }
+    }
+}
+
+/// The file of a `sqlite:` database url, `None` for in-memory databases.
+pub fn database_path(database_url: &str) -> Option<PathBuf> {
+    let path = database_url
+        .trim_start_matches("sqlite://")
+        .trim_start_matches("sqlite:");
+    let path = path.split('?').next().unwrap_or_default();
+
+    if path.is_empty() || path == ":memory:" {
+        return None;
+    }
+
+    Some(PathBuf::from(path))
+}
+
+fn fingerprint(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// The query, the operation, the variables and the persisted query hash, which together decide the
+/// response. The query is normalized, so the same query formatted differently shares its entry. One
+/// that doesn't parse is kept as sent, its errors are never cached anyway.
+fn key(request: &Request) -> String {
+    let query = match parse_query(&request.query) {
+        Ok(document) => normalize(&document),
+        Err(_) => request.query.clone(),
+    };
+    let variables = to_string(&request.variables).unwrap_or_default();
+    let persisted = match request.extensions.get("persistedQuery") {
+        Some(Value::Object(extension)) => match extension.get("sha256Hash") {
+            Some(Value::String(hash)) => hash.as_str(),
+            _ => "",
+        },
+        _ => "",
+    };
+
+    let mut hasher = Sha256::new();
+    for part in [
+        query.as_str(),
+        request.operation_name.as_deref().unwrap_or_default(),
+        variables.as_str(),
+        persisted,
+    ] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+
+    format!("{:x}", hasher.finalize())
+}
+
+/// The document printed back without its ignored tokens, i.e. whitespace, commas and comments.
+/// Operations and fragments are sorted by name, the parser keeps them unordered.
+fn normalize(document: &ExecutableDocument) -> String {
+    let mut out = String::new();
+
+    let mut operations: Vec<_> = document.operations.iter().collect();
+    operations.sort_by_key(|(name, _)| *name);
+    for (name, operation) in operations {
+        let operation = &operation.node;
+        let _ = write!(out, "{}", operation.ty);
+        if let Some(name) = name {
+            let _ = write!(out, " {}", name);
+        }
+        if !operation.variable_definitions.is_empty() {
+            out.push('(');
+            for variable in &operation.variable_definitions {
+                let variable = &variable.node;
+                let _ = write!(out, "${}:{}", variable.name.node, variable.var_type.node);
+                if let Some(default) = &variable.default_value {
+                    let _ = write!(out, "={}", default.node);
+                }
+                write_directives(&mut out, &variable.directives);
+                out.push(' ');
+            }
+            out.push(')');
+        }
+        write_directives(&mut out, &operation.directives);
+        write_selection_set(&mut out, &operation.selection_set.node);
+    }
+
+    let mut fragments: Vec<_> = document.fragments.iter().collect();
+    fragments.sort_by_key(|(name, _)| *name);
+    for (name, fragment) in fragments {
+        let fragment = &fragment.node;
+        let _ = write!(
+            out,
+            "fragment {} on {}",
+            name, fragment.type_condition.node.on.node
+        );
+        write_directives(&mut out, &fragment.directives);
+        write_selection_set(&mut out, &fragment.selection_set.node);
+    }
+
+    out
+}
+
+fn write_selection_set(out: &mut String, selection_set: &SelectionSet) {
+    if selection_set.items.is_empty() {
+        return;
+    }
+
+    out.push('{');
+    for selection in &selection_set.items {
+        match &selection.node {
+            Selection::Field(field) => {
+                let field = &field.node;
+                if let Some(alias) = &field.alias {
+                    let _ = write!(out, "{}:", alias.node);
+                }
+                out.push_str(&field.name.node);
+                write_arguments(out, &field.arguments);
+                write_directives(out, &field.directives);
+                write_selection_set(out, &field.selection_set.node);
+            }
+            Selection::FragmentSpread(spread) => {
+                let _ = write!(out, "...{}", spread.node.fragment_name.node);
+                write_directives(out, &spread.node.directives);
+            }
+            Selection::InlineFragment(fragment) => {
+                out.push_str("...");
+                if let Some(condition) = &fragment.node.type_condition {
+                    let _ = write!(out, "on {}", condition.node.on.node);
+                }
+                write_directives(out, &fragment.node.directives);
+                write_selection_set(out, &fragment.node.selection_set.node);
+            }
+        }
+        out.push(' ');
+    }
+    out.push('}');
+}
+
+fn write_directives(out: &mut String, directives: &[Positioned<Directive>]) {
+    for directive in directives {
+        let _ = write!(out, "@{}", directive.node.name.node);
+        write_arguments(out, &directive.node.arguments);
+    }
+}
+
+/// Arguments are parser values, which `async_graphql` only exports as constants.
+fn write_arguments(out: &mut String, arguments: &[(Positioned<Name>, Positioned<impl Display>)]) {
+    if arguments.is_empty() {
+        return;
+    }
+
+    out.push('(');
+    for (name, value) in arguments {
+        let _ = write!(out, "{}:{} ", name.node, value.node);
+    }
+    out.push(')');
+}
+
+/// Records the types and ids every response shows, so they can be invalidated.
+pub struct CollectShown;
+
+impl ExtensionFactory for CollectShown {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(CollectShown)
+    }
+}
+
+#[rocket::async_trait]
+impl Extension for CollectShown {
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        let kind = info.parent_type.to_string();
+        let is_id = info.name == "id";
+        let introspection = info.is_for_introspection;
+
+        let value = next.run(ctx, info).await?;
+
+        if !introspection && let Some(collector) = ctx.data_opt::<Collector>() {
+            let mut shown = collector.0.lock().unwrap();
+            if is_id && let Some(Value::String(id)) = &value {
+                shown.ids.insert((kind.clone(), id.clone()));
+            }
+            shown.kinds.insert(kind);
+        }
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shown(kinds: &[&str], ids: &[(&str, &str)]) -> Shown {
+        Shown {
+            kinds: kinds.iter().map(|k| k.to_string()).collect(),
+            ids: ids
+                .iter()
+                .map(|(k, id)| (k.to_string(), id.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn it_matches_entities_by_id() {
+        let shown = shown(&["Photo", "Tag"], &[("Photo", "1")]);
+
+        assert!(shown.shows(&Entity::new("Photo", "1")));
+        assert!(!shown.shows(&Entity::new("Photo", "2")));
+        assert!(!shown.shows(&Entity::new("Lens", "1")));
+        // No tag ids were shown, it may be any tag.
+        assert!(shown.shows(&Entity::new("Tag", "1")));
+    }
+
+    #[test]
+    fn it_matches_entities_by_kind() {
+        let shown = shown(&["Photo"], &[("Photo", "1")]);
+
+        assert!(shown.shows(&Entity::kind("Photo")));
+        assert!(!shown.shows(&Entity::kind("Tag")));
+    }
+
+    #[test]
+    fn it_keys_queries_by_their_normalized_text() {
+        let a = Request::new(r#"{ tag(name: "a b") { id photos { id } } }"#);
+        let b = Request::new("{tag(name:\"a b\"),{id,photos{id}}} # all of it");
+        let c = Request::new(r#"{ tag(name: "a  b") { id photos { id } } }"#);
+        let d = Request::new(r#"{ tag(name: "a b") { id photos { title } } }"#);
+
+        assert_eq!(key(&a), key(&b));
+        assert_ne!(key(&a), key(&c));
+        assert_ne!(key(&a), key(&d));
+    }
+
+    #[test]
+    fn it_normalizes_operations_and_fragments() {
+        let query = r#"
+            query Tags($name: String = "a") @cached { tag(name: $name) { ...T ... on Tag { id } } }
+            fragment T on Tag { name }
+        "#;
+        let document = parse_query(query).unwrap();
+
+        assert_eq!(
+            normalize(&document),
+            r#"query Tags($name:String="a" )@cached{tag(name:$name ){...T ...on Tag{id } } }fragment T on Tag{name }"#
+        );
+    }
+
+    #[test]
+    fn it_evicts_the_oldest_response() {
+        let cache = ResponseCache::default();
+        let start = Instant::now();
+        let entry = |i| Entry {
+            body: String::new(),
+            etag: String::new(),
+            shown: Shown::default(),
+            stored: start + Duration::from_millis(i as u64),
+        };
+
+        for i in 0..MAX_ENTRIES {
+            cache.store(i.to_string(), entry(i));
+        }
+        cache.store("new".to_string(), entry(MAX_ENTRIES));
+
+        assert!(cache.get("0").is_none());
+        assert!(cache.get("1").is_some());
+        assert!(cache.get("new").is_some());
+        assert_eq!(cache.entries.read().unwrap().len(), MAX_ENTRIES);
+    }
+
+    #[test]
+    fn it_reads_sqlite_urls() {
+        assert_eq!(
+            database_path("sqlite://data/db.sqlite?mode=rwc"),
+            Some(PathBuf::from("data/db.sqlite"))
+        );
+        assert_eq!(
+            database_path("sqlite:db.sqlite"),
+            Some(PathBuf::from("db.sqlite"))
+        );
+        assert_eq!(database_path("sqlite::memory:"), None);
+    }
+}
@@ -1,10 +1,21 @@
-use super::graph::RootSchema;
+use super::{
+    graph::RootSchema,
+    response_cache::{CACHE_CONTROL, Cached},
+};
+use crate::{AppState, auth::Credentials, routes::conditional::none_match};
 #[cfg(debug_assertions)]
 use async_graphql::http::{GraphQLPlaygroundConfig, playground_source};
-use async_graphql_rocket::{GraphQLQuery, GraphQLRequest, GraphQLResponse};
-use rocket::State;
+use async_graphql_rocket::{GraphQLQuery, GraphQLRequest};
 #[cfg(debug_assertions)]
 use rocket::response::content;
+use rocket::{
+    Request, Response, State,
+    http::{ContentType, Header, Status},
+    request::{FromRequest, Outcome},
+    response::Responder,
+};
+use std::io::Cursor;
+use tracing::{Instrument, info_span};
 
 #[cfg(debug_assertions)]
 #[get("/graphql")]
@@ -12,15 +23,97 @@ pub fn graphql_playground() -> content::RawHtml<String> {
     content::RawHtml(playground_source(GraphQLPlaygroundConfig::new("/graphql")))
 }
 
+// The credentials go with every request, the fields that need a token check them.
 #[get("/graphql?<query..>")]
-pub async fn graphql_query(schema: &State<RootSchema>, query: GraphQLQuery) -> GraphQLResponse {
-    query.execute(schema.inner()).await
+pub async fn graphql_query(
+    schema: &State<RootSchema>,
+    state: &State<AppState>,
+    query: GraphQLQuery,
+    credentials: Credentials,
+    if_none_match: IfNoneMatch,
+) -> GraphQLCachedResponse {
+    let request = GraphQLRequest::from(query).0.data(credentials);
+    let cached = state
+        .response_cache
+        .execute(schema.inner(), request)
+        .instrument(info_span!("graphql"))
+        .await;
+
+    let not_modified = match (&if_none_match.0, &cached.etag) {
+        (Some(header), Some(etag)) => none_match(header, etag),
+        _ => false,
+    };
+
+    GraphQLCachedResponse {
+        cached,
+        not_modified,
+        headers: true,
+    }
 }
 
 #[rocket::post("/graphql", data = "<request>", format = "application/json")]
 pub async fn graphql_request(
     schema: &State<RootSchema>,
+    state: &State<AppState>,
     request: GraphQLRequest,
-) -> GraphQLResponse {
-    request.execute(schema.inner()).await
+    credentials: Credentials,
+) -> GraphQLCachedResponse {
+    let request = request.0.data(credentials);
+    let cached = state
+        .response_cache
+        .execute(schema.inner(), request)
+        .instrument(info_span!("graphql"))
+        .await;
+
+    GraphQLCachedResponse {
+        cached,
+        not_modified: false,
+        headers: false,
+    }
+}
+
+/// A JSON response of the schema, a 304 when the client has it. Only GET responses can be cached
+/// by clients, they get the `ETag` and `Cache-Control` headers.
+pub struct GraphQLCachedResponse {
+    cached: Cached,
+    not_modified: bool,
+    headers: bool,
+}
+
+impl<'r> Responder<'r, 'static> for GraphQLCachedResponse {
+    fn respond_to(self, _: &'r Request<'_>) -> Result<Response<'static>, Status> {
+        let mut response = Response::build();
+
+        if self.headers
+            && let Some(etag) = &self.cached.etag
+        {
+            response
+                .header(Header::new("ETag", format!("\"{}\"", etag)))
+                .header(Header::new("Cache-Control", CACHE_CONTROL));
+        }
+
+        if self.not_modified {
+            return response.status(Status::NotModified).ok();
+        }
+
+        let body = self.cached.body;
+        response
+            .header(ContentType::JSON)
+            .sized_body(body.len(), Cursor::new(body))
+            .ok()
+    }
+}
+
+/// The `If-None-Match` header of the request.
+pub struct IfNoneMatch(Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IfNoneMatch {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let header = req.headers().get_one("If-None-Match").map(String::from);
+
+        Outcome::Success(IfNoneMatch(header))
+    }
 }
@@ -4,28 +4,39 @@ extern crate rocket;
 use crate::cache::image_cache::ImageCache;
 use crate::graphql::{
     context::Context,
-    graph::RootSchema,
+    graph::{self, RootSchema},
     loaders::AppLoader,
-    queries::RootQuery,
+    response_cache::{ResponseCache, database_path, watch_database},
     routes::{graphql_query, graphql_request},
 };
 #[cfg(debug_assertions)]
 use crate::graphql::{routes::graphql_playground, sdl_gen};
-use async_graphql::{EmptyMutation, EmptySubscription, Schema, dataloader::DataLoader};
+use crate::metrics::RequestMetrics;
+use crate::request_id::{RequestIds, traced};
+use async_graphql::dataloader::DataLoader;
 #[cfg(debug_assertions)]
 use core_victorhqc_com::db::migrate;
 use core_victorhqc_com::{
     aws::S3,
     db::{Error as DBError, get_pool},
     sqlx::sqlite::SqlitePool,
+    telemetry,
 };
-use log::info;
 use rocket::tokio::spawn;
 use snafu::prelude::*;
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+use tracing::{Instrument, debug, info, warn};
 
+mod auth;
 mod bootstrap;
 mod cache;
 mod graphql;
+mod metrics;
+mod recipe_card;
+mod request_id;
 mod routes;
 
 #[get("/")]
@@ -37,7 +48,7 @@ fn index() -> &'static str {
 async fn main() -> Result<(), Box<Error>> {
     dotenvy::dotenv().ok();
 
-    pretty_env_logger::init();
+    let _telemetry = telemetry::init("api");
 
     let rocket = rocket::build();
     let figment = rocket.figment();
@@ -56,6 +67,12 @@ async fn main() -> Result<(), Box<Error>> {
 
     debug!("Cached Tags: {:?}", cached_tags);
 
+    let collections: Vec<String> = cached_tags
+        .iter()
+        .filter(|t| !t.is_empty())
+        .cloned()
+        .collect();
+
     let s3 = S3::new(&bucket_name).await;
 
     info!("DATABASE_URL: {}", database_url);
@@ -69,25 +86,49 @@ async fn main() -> Result<(), Box<Error>> {
     let context = Context::default(db_pool.clone());
     let loader = AppLoader::default(db_pool.clone());
     let img_cache = ImageCache::default(s3);
+    let response_cache = ResponseCache::default();
+
+    if let Some(path) = database_path(&database_url) {
+        spawn(watch_database(path, response_cache.clone()));
+    }
 
-    let mut state = AppState { db_pool, img_cache };
+    let state = AppState {
+        db_pool,
+        img_cache,
+        collections,
+        response_cache,
+        bootstrapped: Arc::new(AtomicBool::new(cached_tags.is_empty())),
+    };
 
+    // The images are cached while the API already serves, `/readyz` tells when it is done.
     if !cached_tags.is_empty() {
-        match bootstrap::prepare_images(state.clone(), cached_tags).await {
-            Ok(new_state) => state = new_state,
-            Err(e) => {
-                log::warn!(
+        let caching = bootstrap::prepare_images(state.clone(), cached_tags);
+        let bootstrapped = state.bootstrapped.clone();
+
+        spawn(async move {
+            if let Err(e) = caching.await {
+                warn!(
                     "Bootstrap image caching failed, continuing without cache: {}",
                     e
                 );
             }
-        }
+
+            bootstrapped.store(true, Ordering::Release);
+        });
     }
 
-    let schema: RootSchema = Schema::build(RootQuery::default(), EmptyMutation, EmptySubscription)
+    // Debug builds take any query, the playground needs them.
+    let persisted_only = cfg!(not(debug_assertions))
+        && figment
+            .extract_inner("graphql_persisted_only")
+            .unwrap_or(false);
+
+    let schema: RootSchema = graph::build(persisted_only)
         .data(context)
-        .data(DataLoader::new(loader, spawn))
-        // .limit_depth(4)
+        // The batches run in their own tasks, they keep the span of the request that queued them.
+        .data(DataLoader::new(loader, |batch| {
+            spawn(batch.in_current_span())
+        }))
         .finish();
 
     #[cfg(debug_assertions)]
@@ -104,15 +145,12 @@ async fn main() -> Result<(), Box<Error>> {
     let app = rocket
         .manage(schema)
         .manage(state)
-        .mount("/", gql_routes)
-        .mount(
-            "/v1/",
-            routes![
-                routes::photos::get_all_photos,
-                routes::photos::get_all_photos_by_tag,
-                routes::images::get_image
-            ],
-        );
+        .attach(RequestIds)
+        .attach(RequestMetrics)
+        .mount("/", traced(gql_routes))
+        .mount("/", traced(routes::health()))
+        .mount("/v1/", traced(routes::v1()))
+        .register("/v1/", routes::v1_catchers());
 
     app.launch().await.context(RocketSnafu)?;
 
@@ -123,6 +161,12 @@ async fn main() -> Result<(), Box<Error>> {
 struct AppState {
     db_pool: SqlitePool,
     img_cache: ImageCache,
+    /// The tags shown as galleries, from `cached_photo_tags`.
+    collections: Vec<String>,
+    /// Responses of the GraphQL queries.
+    response_cache: ResponseCache,
+    /// Whether the images of `cached_photo_tags` were cached, successfully or not.
+    bootstrapped: Arc<AtomicBool>,
 }
 
 #[derive(Debug, Snafu)]
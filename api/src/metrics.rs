@@ -0,0 +1,365 @@
+//! Counters and histograms of the API, served at `/metrics` in the Prometheus text format. They
+//! live in a global so the image cache, the S3 calls and the schema can record without being
+//! handed a registry.
+use crate::graphql::persisted_queries::is_web_operation;
+use async_graphql::{
+    Response as GraphQLResponse,
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute},
+};
+use once_cell::sync::Lazy;
+use rocket::{
+    Data, Request, Response,
+    fairing::{Fairing, Info, Kind},
+};
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{Instrument, info_span};
+
+pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::default);
+
+/// Upper bounds of the latency buckets, in seconds.
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+type Label = (&'static str, String);
+type Labels = Vec<Label>;
+
+#[derive(Default)]
+struct Counters(Mutex<BTreeMap<Labels, u64>>);
+
+impl Counters {
+    fn inc(&self, labels: Labels) {
+        *self.0.lock().unwrap().entry(labels).or_default() += 1;
+    }
+
+    fn get(&self, labels: &[Label]) -> u64 {
+        let counters = self.0.lock().unwrap();
+
+        counters.get(labels).copied().unwrap_or_default()
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        header(out, name, help, "counter");
+
+        for (labels, value) in self.0.lock().unwrap().iter() {
+            let _ = writeln!(out, "{}{} {}", name, format_labels(labels, None), value);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Histogram {
+    /// Observations per bucket, not cumulative, they are added up when rendered.
+    buckets: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Default)]
+struct Histograms(Mutex<BTreeMap<Labels, Histogram>>);
+
+impl Histograms {
+    fn observe(&self, labels: Labels, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        let mut histograms = self.0.lock().unwrap();
+        let histogram = histograms.entry(labels).or_default();
+
+        if let Some(i) = BUCKETS.iter().position(|bound| seconds <= *bound) {
+            histogram.buckets[i] += 1;
+        }
+        histogram.sum += seconds;
+        histogram.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        header(out, name, help, "histogram");
+
+        for (labels, histogram) in self.0.lock().unwrap().iter() {
+            let mut cumulative = 0;
+            for (bound, count) in BUCKETS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                let le = format_labels(labels, Some(&bound.to_string()));
+                let _ = writeln!(out, "{}_bucket{} {}", name, le, cumulative);
+            }
+
+            let le = format_labels(labels, Some("+Inf"));
+            let labels = format_labels(labels, None);
+            let _ = writeln!(out, "{}_bucket{} {}", name, le, histogram.count);
+            let _ = writeln!(out, "{}_sum{} {}", name, labels, histogram.sum);
+            let _ = writeln!(out, "{}_count{} {}", name, labels, histogram.count);
+        }
+    }
+}
+
+/// A value read when the metrics are scraped, e.g. the connections of the pool.
+pub struct Gauge {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub value: f64,
+}
+
+#[derive(Default)]
+pub struct Metrics {
+    http_requests: Counters,
+    http_durations: Histograms,
+    image_cache: Counters,
+    s3_durations: Histograms,
+    s3_errors: Counters,
+    graphql_durations: Histograms,
+}
+
+impl Metrics {
+    pub fn request(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        let labels = vec![("method", method.to_string()), ("route", route.to_string())];
+
+        let mut with_status = labels.clone();
+        with_status.push(("status", status.to_string()));
+
+        self.http_requests.inc(with_status);
+        self.http_durations.observe(labels, elapsed);
+    }
+
+    /// Whether the image cache had the image or it was downloaded.
+    pub fn image_cache(&self, hit: bool) {
+        let result = if hit { "hit" } else { "miss" };
+
+        self.image_cache.inc(vec![("result", result.to_string())]);
+    }
+
+    /// Times an S3 call in its own span, counting it as an error when it fails.
+    pub async fn s3<T, E>(
+        &self,
+        operation: &'static str,
+        call: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        let start = Instant::now();
+        let result = call.instrument(info_span!("s3", operation)).await;
+        let labels = vec![("operation", operation.to_string())];
+
+        self.s3_durations.observe(labels.clone(), start.elapsed());
+        if result.is_err() {
+            self.s3_errors.inc(labels);
+        }
+
+        result
+    }
+
+    pub fn graphql(&self, operation: &str, elapsed: Duration) {
+        let labels = vec![("operation", operation.to_string())];
+
+        self.graphql_durations.observe(labels, elapsed);
+    }
+
+    /// Every metric in the Prometheus text format, along with the gauges read for this scrape.
+    pub fn render(&self, gauges: &[Gauge]) -> String {
+        let mut out = String::new();
+
+        self.http_requests.render(
+            &mut out,
+            "http_requests_total",
+            "Requests by method, route and status.",
+        );
+        self.http_durations.render(
+            &mut out,
+            "http_request_duration_seconds",
+            "Time to answer a request, by method and route.",
+        );
+        self.image_cache.render(
+            &mut out,
+            "image_cache_requests_total",
+            "Images asked to the cache, by whether it had them.",
+        );
+
+        let hits = self.image_cache.get(&[("result", "hit".to_string())]);
+        let misses = self.image_cache.get(&[("result", "miss".to_string())]);
+        let ratio = if hits + misses == 0 {
+            0.0
+        } else {
+            hits as f64 / (hits + misses) as f64
+        };
+        render_gauge(
+            &mut out,
+            &Gauge {
+                name: "image_cache_hit_ratio",
+                help: "Share of the images served from the cache.",
+                value: ratio,
+            },
+        );
+
+        self.s3_durations.render(
+            &mut out,
+            "s3_request_duration_seconds",
+            "Time of the S3 calls, by operation.",
+        );
+        self.s3_errors.render(
+            &mut out,
+            "s3_errors_total",
+            "Failed S3 calls, by operation.",
+        );
+        self.graphql_durations.render(
+            &mut out,
+            "graphql_operation_duration_seconds",
+            "Time to execute a GraphQL operation, by operation name.",
+        );
+
+        for gauge in gauges {
+            render_gauge(&mut out, gauge);
+        }
+
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn render_gauge(out: &mut String, gauge: &Gauge) {
+    header(out, gauge.name, gauge.help, "gauge");
+    let _ = writeln!(out, "{} {}", gauge.name, gauge.value);
+}
+
+/// `{name="value",...}`, with `le` last for histogram buckets. Empty without labels.
+fn format_labels(labels: &[Label], le: Option<&str>) -> String {
+    let mut pairs: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape(value)))
+        .collect();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{}\"", le));
+    }
+
+    if pairs.is_empty() {
+        return String::new();
+    }
+
+    format!("{{{}}}", pairs.join(","))
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// When the request arrived, kept in the request's local cache.
+struct RequestStart(Instant);
+
+/// Counts and times every request by the route that answered it.
+pub struct RequestMetrics;
+
+#[rocket::async_trait]
+impl Fairing for RequestMetrics {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request metrics",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        req.local_cache(|| RequestStart(Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let start = req.local_cache(|| RequestStart(Instant::now()));
+        // The pattern of the route, not the path, so ids don't make new series.
+        let route = req
+            .route()
+            .map(|r| r.uri.to_string())
+            .unwrap_or_else(|| "unmatched".to_string());
+
+        METRICS.request(
+            req.method().as_str(),
+            &route,
+            res.status().code,
+            start.0.elapsed(),
+        );
+    }
+}
+
+/// Times every GraphQL operation. Responses served from the cache aren't executed, so they
+/// aren't timed.
+pub struct GraphQLMetrics;
+
+impl ExtensionFactory for GraphQLMetrics {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(GraphQLMetrics)
+    }
+}
+
+#[rocket::async_trait]
+impl Extension for GraphQLMetrics {
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> GraphQLResponse {
+        let start = Instant::now();
+        let response = next.run(ctx, operation_name).await;
+
+        // Clients name their operations, only the ones of the website get their own series.
+        let operation = operation_name
+            .filter(|name| is_web_operation(name))
+            .unwrap_or("other");
+        METRICS.graphql(operation, start.elapsed());
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_renders_counters() {
+        let metrics = Metrics::default();
+        metrics.image_cache(true);
+        metrics.image_cache(true);
+        metrics.image_cache(false);
+
+        let out = metrics.render(&[]);
+
+        assert!(out.contains("# TYPE image_cache_requests_total counter"));
+        assert!(out.contains("image_cache_requests_total{result=\"hit\"} 2"));
+        assert!(out.contains("image_cache_requests_total{result=\"miss\"} 1"));
+        assert!(out.contains("image_cache_hit_ratio 0.6666666666666666"));
+    }
+
+    #[test]
+    fn it_renders_cumulative_buckets() {
+        let metrics = Metrics::default();
+        metrics.graphql("GetPortfolio", Duration::from_millis(20));
+        metrics.graphql("GetPortfolio", Duration::from_millis(200));
+
+        let out = metrics.render(&[]);
+
+        let series = "graphql_operation_duration_seconds_bucket{operation=\"GetPortfolio\"";
+        assert!(out.contains(&format!("{},le=\"0.01\"}} 0", series)));
+        assert!(out.contains(&format!("{},le=\"0.025\"}} 1", series)));
+        assert!(out.contains(&format!("{},le=\"0.25\"}} 2", series)));
+        assert!(out.contains(&format!("{},le=\"+Inf\"}} 2", series)));
+        assert!(
+            out.contains("graphql_operation_duration_seconds_count{operation=\"GetPortfolio\"} 2")
+        );
+    }
+
+    #[test]
+    fn it_escapes_label_values() {
+        assert_eq!(
+            format_labels(&[("route", "a\"b".to_string())], None),
+            "{route=\"a\\\"b\"}"
+        );
+        assert_eq!(format_labels(&[], None), "");
+    }
+}
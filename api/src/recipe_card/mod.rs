@@ -0,0 +1,265 @@
+//! Printable card with everything needed to dial a recipe in the camera. The card carries a QR code
+//! with the recipe in the same JSON format `recipe import` reads, so it can be shared offline.
+mod swatches;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use core_victorhqc_com::models::fujifilm::{FujifilmRecipe, file::RecipeFile};
+use once_cell::sync::Lazy;
+use qrcode::{Color, EcLevel, QrCode, types::QrError};
+use resvg::{
+    tiny_skia::{Pixmap, Transform},
+    usvg::{self, fontdb},
+};
+use rocket::serde::json::serde_json;
+use snafu::prelude::*;
+use std::fmt::Write;
+use std::sync::Arc;
+use swatches::get_swatch;
+
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 420;
+const QR_SIZE: u32 = 220;
+const PADDING: u32 = 24;
+const ROW_HEIGHT: u32 = 18;
+
+/// PNGs are rendered at twice the size of the SVG so they stay sharp when printed.
+const PNG_SCALE: f32 = 2.0;
+
+/// The fonts are embedded, the server doesn't need any installed and the cards look the same
+/// everywhere. The SVG keeps the generic families, so browsers use their own.
+static FONTS: Lazy<Arc<fontdb::Database>> = Lazy::new(|| {
+    let mut db = fontdb::Database::new();
+    db.load_font_data(include_bytes!("fonts/DejaVuSerif-Italic.ttf").to_vec());
+    db.load_font_data(include_bytes!("fonts/DejaVuSansMono.ttf").to_vec());
+    db.set_serif_family("DejaVu Serif");
+    db.set_monospace_family("DejaVu Sans Mono");
+
+    Arc::new(db)
+});
+
+pub struct RecipeCard {
+    svg: String,
+}
+
+impl RecipeCard {
+    pub fn new(recipe: &FujifilmRecipe) -> Result<Self, Error> {
+        let file = RecipeFile::from(recipe);
+        let json = serde_json::to_string(&file).context(JsonSnafu)?;
+        let qr =
+            QrCode::with_error_correction_level(json.as_bytes(), EcLevel::L).context(QrSnafu)?;
+
+        let swatch = get_swatch(&recipe.details().film_simulation);
+
+        Ok(RecipeCard {
+            svg: build_svg(&file, &qr, swatch),
+        })
+    }
+
+    pub fn svg(&self) -> &str {
+        &self.svg
+    }
+
+    /// Rasterizes the card, it takes long enough to be run outside of the async workers.
+    pub fn png(&self) -> Result<Vec<u8>, Error> {
+        let options = usvg::Options {
+            fontdb: FONTS.clone(),
+            ..Default::default()
+        };
+        let tree = usvg::Tree::from_str(&self.svg, &options).context(SvgSnafu)?;
+
+        let width = (WIDTH as f32 * PNG_SCALE) as u32;
+        let height = (HEIGHT as f32 * PNG_SCALE) as u32;
+        let mut pixmap = Pixmap::new(width, height).context(PixmapSnafu)?;
+
+        resvg::render(
+            &tree,
+            Transform::from_scale(PNG_SCALE, PNG_SCALE),
+            &mut pixmap.as_mut(),
+        );
+
+        pixmap.encode_png().map_err(|e| Error::Png {
+            message: e.to_string(),
+        })
+    }
+}
+
+fn build_svg(file: &RecipeFile, qr: &QrCode, swatch: Option<&[u8]>) -> String {
+    let mut svg = String::new();
+
+    write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = WIDTH,
+        h = HEIGHT
+    )
+    .unwrap();
+    write!(
+        svg,
+        r#"<rect x="0.5" y="0.5" width="{}" height="{}" fill="white" stroke="black"/>"#,
+        WIDTH - 1,
+        HEIGHT - 1
+    )
+    .unwrap();
+
+    let mut title_x = PADDING;
+    if let Some(swatch) = swatch {
+        write!(
+            svg,
+            r#"<image x="{}" y="{}" width="48" height="48" href="data:image/png;base64,{}"/>"#,
+            PADDING,
+            PADDING,
+            STANDARD.encode(swatch)
+        )
+        .unwrap();
+        title_x += 60;
+    }
+
+    write!(
+        svg,
+        r#"<text x="{}" y="{}" font-family="serif" font-size="22" font-style="italic">{}</text>"#,
+        title_x,
+        PADDING + 20,
+        escape(&file.name)
+    )
+    .unwrap();
+
+    if let Some(author) = &file.author {
+        write!(
+            svg,
+            r##"<text x="{}" y="{}" font-family="monospace" font-size="12" fill="#555">by {}</text>"##,
+            title_x,
+            PADDING + 42,
+            escape(author)
+        )
+        .unwrap();
+    }
+
+    let top = PADDING + 80;
+    for (i, (label, value)) in rows(file).iter().enumerate() {
+        let y = top + i as u32 * ROW_HEIGHT;
+
+        write!(
+            svg,
+            r##"<text x="{}" y="{}" font-family="monospace" font-size="12" fill="#555">{}</text>"##,
+            PADDING, y, label
+        )
+        .unwrap();
+        write!(
+            svg,
+            r#"<text x="{}" y="{}" font-family="monospace" font-size="12">{}</text>"#,
+            PADDING + 160,
+            y,
+            escape(value)
+        )
+        .unwrap();
+    }
+
+    write_qr(&mut svg, qr);
+
+    svg.push_str("</svg>");
+
+    svg
+}
+
+/// Draws the QR code as a single path, one square per dark module, at the bottom right corner.
+fn write_qr(svg: &mut String, qr: &QrCode) {
+    // Four modules of quiet zone around the code, as the spec asks.
+    let modules = qr.width() + 8;
+    let scale = QR_SIZE as f32 / modules as f32;
+    let x = WIDTH - PADDING - QR_SIZE;
+    let y = HEIGHT - PADDING - QR_SIZE;
+
+    write!(
+        svg,
+        r#"<g transform="translate({} {}) scale({})"><path fill="black" d=""#,
+        x, y, scale
+    )
+    .unwrap();
+
+    for (i, color) in qr.to_colors().iter().enumerate() {
+        if *color == Color::Dark {
+            let mx = i % qr.width() + 4;
+            let my = i / qr.width() + 4;
+
+            write!(svg, "M{} {}h1v1h-1z", mx, my).unwrap();
+        }
+    }
+
+    svg.push_str(r#""/></g>"#);
+}
+
+fn rows(file: &RecipeFile) -> Vec<(&'static str, String)> {
+    let film_simulation = match &file.monochromatic_color {
+        Some(color) => format!("{} ({})", file.film_simulation, color),
+        None => file.film_simulation.clone(),
+    };
+    let grain = match (&file.grain_strength, &file.grain_size) {
+        (Some(strength), Some(size)) => Some(format!("{}, {}", strength, size)),
+        (Some(strength), None) => Some(strength.clone()),
+        _ => None,
+    };
+
+    let mut rows = vec![
+        ("Film Simulation", film_simulation),
+        ("Sensor", file.sensor.clone()),
+        ("White Balance", file.white_balance.clone()),
+        ("WB Shift", file.white_balance_shift.clone()),
+        ("Dynamic Range", file.dynamic_range.clone()),
+    ];
+
+    if let Some(d_range_priority) = &file.d_range_priority {
+        rows.push(("D Range Priority", d_range_priority.clone()));
+    }
+
+    rows.push(("Highlight", format!("{:+}", file.highlight_tone)));
+    rows.push(("Shadow", format!("{:+}", file.shadow_tone)));
+    rows.push(("Color", format!("{:+}", file.color)));
+    rows.push(("Sharpness", format!("{:+}", file.sharpness)));
+
+    if let Some(clarity) = file.clarity {
+        rows.push(("Clarity", format!("{:+}", clarity)));
+    }
+
+    rows.push((
+        "High ISO NR",
+        format!("{:+}", file.high_iso_noise_reduction),
+    ));
+
+    if let Some(grain) = grain {
+        rows.push(("Grain Effect", grain));
+    }
+    if let Some(cce) = &file.color_chrome_effect {
+        rows.push(("Color Chrome", cce.clone()));
+    }
+    if let Some(fx_blue) = &file.color_chrome_fx_blue {
+        rows.push(("Color Chrome FX Blue", fx_blue.clone()));
+    }
+
+    rows
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to serialize recipe: {}", source))]
+    Json { source: serde_json::Error },
+
+    #[snafu(display("Failed to build QR code: {}", source))]
+    Qr { source: QrError },
+
+    #[snafu(display("Failed to parse card SVG: {}", source))]
+    Svg { source: usvg::Error },
+
+    #[snafu(display("Failed to allocate card image"))]
+    Pixmap,
+
+    #[snafu(display("Failed to encode card PNG: {}", message))]
+    Png { message: String },
+}
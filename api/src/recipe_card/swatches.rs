@@ -0,0 +1,49 @@
+use core_victorhqc_com::models::fujifilm::swatch_filename;
+use fuji::recipe::FilmSimulation;
+
+macro_rules! swatch {
+    ($name:literal) => {
+        (
+            $name,
+            include_bytes!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../web/public/film-simulations/",
+                $name
+            ))
+            .as_slice(),
+        )
+    };
+}
+
+/// The same images the website uses, embedded so the cards can be rendered without it.
+static SWATCHES: [(&str, &[u8]); 20] = [
+    swatch!("provia_1.png"),
+    swatch!("realaace_1.png"),
+    swatch!("velvia_1.png"),
+    swatch!("astia_1.png"),
+    swatch!("classicchrome_1.png"),
+    swatch!("classicneg_1.png"),
+    swatch!("proneghi_1.png"),
+    swatch!("pronegstd_1.png"),
+    swatch!("nostalgicneg_1.png"),
+    swatch!("etrna_1.png"),
+    swatch!("eternabb_1.png"),
+    swatch!("sepia_1.png"),
+    swatch!("monostd_1.png"),
+    swatch!("monor_1.png"),
+    swatch!("monog_1.png"),
+    swatch!("monoye_1.png"),
+    swatch!("acrosstd_1.png"),
+    swatch!("acrosr_1.png"),
+    swatch!("acrosg_1.png"),
+    swatch!("acrosye_1.png"),
+];
+
+pub fn get_swatch(film_simulation: &FilmSimulation) -> Option<&'static [u8]> {
+    let filename = swatch_filename(film_simulation);
+
+    SWATCHES
+        .iter()
+        .find(|(name, _)| *name == filename)
+        .map(|(_, bytes)| *bytes)
+}
@@ -0,0 +1,90 @@
+//! Every request gets an id, the one the website sends in `X-Request-Id` or a new one, so the logs
+//! of both services can be matched. It's sent back in the same header.
+use core_victorhqc_com::telemetry::{REQUEST_ID_HEADER, request_id};
+use rocket::{
+    Data, Request, Response, Route,
+    fairing::{Fairing, Info, Kind},
+    http::Header,
+    request::{FromRequest, Outcome},
+    route::{self, Handler},
+};
+use std::convert::Infallible;
+use tracing::{Instrument, info, info_span};
+
+/// The id of the request, kept in its local cache.
+pub struct RequestId(pub String);
+
+impl RequestId {
+    fn of<'r>(req: &'r Request<'_>) -> &'r RequestId {
+        req.local_cache(|| RequestId(request_id(req.headers().get_one(REQUEST_ID_HEADER))))
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for &'r RequestId {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(RequestId::of(req))
+    }
+}
+
+/// Gives the id to every request and logs it along with the response.
+pub struct RequestIds;
+
+#[rocket::async_trait]
+impl Fairing for RequestIds {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request ids",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        RequestId::of(req);
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let id = &RequestId::of(req).0;
+
+        info!(
+            request_id = %id,
+            method = %req.method(),
+            uri = %req.uri(),
+            status = res.status().code,
+            "request"
+        );
+
+        res.set_header(Header::new(REQUEST_ID_HEADER, id.clone()));
+    }
+}
+
+/// Runs the routes in a span with the id of the request, so everything logged while answering it
+/// carries the id, down to the S3 calls and the DataLoader batches.
+pub fn traced(routes: Vec<Route>) -> Vec<Route> {
+    routes
+        .into_iter()
+        .map(|mut route| {
+            route.handler = Box::new(Traced(route.handler));
+            route
+        })
+        .collect()
+}
+
+#[derive(Clone)]
+struct Traced(Box<dyn Handler>);
+
+#[rocket::async_trait]
+impl Handler for Traced {
+    async fn handle<'r>(&self, req: &'r Request<'_>, data: Data<'r>) -> route::Outcome<'r> {
+        let span = info_span!(
+            "request",
+            request_id = %RequestId::of(req).0,
+            method = %req.method(),
+            path = %req.uri().path()
+        );
+
+        self.0.handle(req, data).instrument(span).await
+    }
+}
@@ -0,0 +1,211 @@
+use once_cell::sync::Lazy;
+use time::{
+    OffsetDateTime, PrimitiveDateTime, UtcOffset,
+    format_description::{self, BorrowedFormatItem},
+};
+
+/// The `IMF-fixdate` format HTTP uses for dates, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+static HTTP_DATE: Lazy<Vec<BorrowedFormatItem<'static>>> = Lazy::new(|| {
+    format_description::parse(
+        "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT",
+    )
+    .unwrap()
+});
+
+pub fn format_http_date(date: OffsetDateTime) -> String {
+    date.to_offset(UtcOffset::UTC)
+        .format(&HTTP_DATE)
+        .unwrap_or_default()
+}
+
+pub fn parse_http_date(value: &str) -> Option<OffsetDateTime> {
+    PrimitiveDateTime::parse(value.trim(), &HTTP_DATE)
+        .ok()
+        .map(|date| date.assume_utc())
+}
+
+/// Whether an `If-None-Match` value lists the etag. Weak tags match too, as the RFC asks for in
+/// this header.
+pub fn none_match(header: &str, etag: &str) -> bool {
+    header
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || opaque(tag) == etag)
+}
+
+/// Whether the representation is unchanged since the date of an `If-Modified-Since` value. An
+/// invalid date is ignored, as if the header wasn't sent.
+pub fn not_modified_since(header: &str, last_modified: OffsetDateTime) -> bool {
+    parse_http_date(header).is_some_and(|since| last_modified <= since)
+}
+
+/// Whether the `Range` can be honoured for an `If-Range` value, either an etag compared strongly
+/// or the exact last modification date.
+pub fn if_range(header: &str, etag: &str, last_modified: OffsetDateTime) -> bool {
+    let header = header.trim();
+
+    if header.starts_with("W/") {
+        return false;
+    }
+
+    if header.starts_with('"') {
+        return opaque(header) == etag;
+    }
+
+    parse_http_date(header).is_some_and(|date| date == last_modified)
+}
+
+fn opaque(tag: &str) -> &str {
+    tag.trim_start_matches("W/").trim_matches('"')
+}
+
+/// Inclusive byte positions, as written in `Content-Range`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Range {
+    /// No range was asked for, or it can't be served as a single part.
+    Full,
+    Partial(ByteRange),
+    /// Every position asked for is past the end of the representation.
+    Unsatisfiable,
+}
+
+impl Range {
+    /// Reads a `Range` value for a representation of `len` bytes. Only single byte ranges are
+    /// served, anything else gets the full body, which the RFC allows for.
+    pub fn parse(header: &str, len: u64) -> Range {
+        let Some(spec) = header.trim().strip_prefix("bytes=") else {
+            return Range::Full;
+        };
+
+        if spec.contains(',') {
+            return Range::Full;
+        }
+
+        let Some((start, end)) = spec.trim().split_once('-') else {
+            return Range::Full;
+        };
+
+        match (start.parse::<u64>(), end.parse::<u64>()) {
+            // `bytes=-500`, the last 500 bytes.
+            (Err(_), Ok(suffix)) if start.is_empty() => {
+                if suffix == 0 || len == 0 {
+                    return Range::Unsatisfiable;
+                }
+
+                Range::Partial(ByteRange {
+                    start: len.saturating_sub(suffix),
+                    end: len - 1,
+                })
+            }
+            // `bytes=500-`, from the 500th byte to the end.
+            (Ok(start), Err(_)) if end.is_empty() => {
+                if start >= len {
+                    return Range::Unsatisfiable;
+                }
+
+                Range::Partial(ByteRange {
+                    start,
+                    end: len - 1,
+                })
+            }
+            (Ok(start), Ok(end)) if start <= end => {
+                if start >= len {
+                    return Range::Unsatisfiable;
+                }
+
+                Range::Partial(ByteRange {
+                    start,
+                    end: end.min(len - 1),
+                })
+            }
+            _ => Range::Full,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_byte_ranges() {
+        assert_eq!(
+            Range::parse("bytes=0-99", 1000),
+            Range::Partial(ByteRange { start: 0, end: 99 })
+        );
+        assert_eq!(
+            Range::parse("bytes=900-", 1000),
+            Range::Partial(ByteRange {
+                start: 900,
+                end: 999
+            })
+        );
+        assert_eq!(
+            Range::parse("bytes=-100", 1000),
+            Range::Partial(ByteRange {
+                start: 900,
+                end: 999
+            })
+        );
+        assert_eq!(
+            Range::parse("bytes=500-5000", 1000),
+            Range::Partial(ByteRange {
+                start: 500,
+                end: 999
+            })
+        );
+    }
+
+    #[test]
+    fn it_serves_the_full_body_for_other_ranges() {
+        assert_eq!(Range::parse("bytes=0-10, 20-30", 1000), Range::Full);
+        assert_eq!(Range::parse("items=0-10", 1000), Range::Full);
+        assert_eq!(Range::parse("bytes=20-10", 1000), Range::Full);
+        assert_eq!(Range::parse("bytes=abc", 1000), Range::Full);
+    }
+
+    #[test]
+    fn it_rejects_ranges_past_the_end() {
+        assert_eq!(Range::parse("bytes=1000-", 1000), Range::Unsatisfiable);
+        assert_eq!(Range::parse("bytes=-0", 1000), Range::Unsatisfiable);
+    }
+
+    #[test]
+    fn it_round_trips_http_dates() {
+        let date = parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+
+        assert_eq!(date.unix_timestamp(), 784111777);
+        assert_eq!(format_http_date(date), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert!(parse_http_date("yesterday").is_none());
+    }
+
+    #[test]
+    fn it_matches_etags() {
+        assert!(none_match("\"abc\"", "abc"));
+        assert!(none_match("W/\"abc\", \"def\"", "def"));
+        assert!(none_match("*", "abc"));
+        assert!(!none_match("\"abc\"", "def"));
+    }
+
+    #[test]
+    fn it_compares_if_range_strongly() {
+        let date = parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+
+        assert!(if_range("\"abc\"", "abc", date));
+        assert!(!if_range("W/\"abc\"", "abc", date));
+        assert!(if_range("Sun, 06 Nov 1994 08:49:37 GMT", "abc", date));
+        assert!(!if_range("Mon, 07 Nov 1994 08:49:37 GMT", "abc", date));
+    }
+}
@@ -0,0 +1,115 @@
+use super::{ApiSchema, nullable};
+use core_victorhqc_com::models::exif_meta::ExifMeta;
+use rocket::serde::json::{Value, json};
+use serde::Serialize;
+
+/// How the photo was taken.
+#[derive(Serialize)]
+pub struct Exif {
+    /// From 0 to 5 stars.
+    pub rating: i8,
+    pub city: Option<String>,
+    /// `YYYY-MM-DD`.
+    pub date_taken: Option<String>,
+    pub iso: i64,
+    pub aperture: f64,
+    /// As shown by the camera, e.g. `1/250`.
+    pub shutter_speed: String,
+    pub focal_length: FocalLength,
+    pub exposure_compensation: f64,
+    pub camera: Gear,
+    pub lens: Gear,
+    /// The Fujifilm recipe the photo was shot with, see `/v1/recipes/{id}`.
+    pub recipe_id: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct FocalLength {
+    /// In millimetres.
+    pub value: f64,
+    /// The same field of view on a full frame sensor.
+    pub eq_35mm: f64,
+    pub crop_factor: f64,
+}
+
+#[derive(Serialize)]
+pub struct Gear {
+    pub maker: String,
+    pub name: Option<String>,
+}
+
+impl From<ExifMeta> for Exif {
+    fn from(value: ExifMeta) -> Self {
+        let details = value.details;
+
+        Exif {
+            rating: details.rating.0,
+            city: details.city.map(|c| c.0),
+            date_taken: details.date_taken.map(|d| d.0.to_string()),
+            iso: details.iso.0,
+            aperture: details.aperture.0,
+            shutter_speed: details.shutter_speed.0,
+            focal_length: FocalLength {
+                value: details.focal_length.value,
+                eq_35mm: details.focal_length.eq_35mm,
+                crop_factor: details.focal_length.crop_factor,
+            },
+            exposure_compensation: details.exposure_compensation.0,
+            camera: Gear {
+                maker: details.camera_maker.to_string(),
+                name: Some(details.camera_name),
+            },
+            lens: Gear {
+                maker: details.lens_maker.to_string(),
+                name: details.lens_name,
+            },
+            recipe_id: value.fuji_recipe_id,
+        }
+    }
+}
+
+impl ApiSchema for Exif {
+    fn name() -> String {
+        "Exif".to_string()
+    }
+
+    fn schema() -> Value {
+        let gear = json!({
+            "type": "object",
+            "required": ["maker", "name"],
+            "properties": {
+                "maker": { "type": "string" },
+                "name": nullable(json!({ "type": "string" })),
+            },
+        });
+
+        json!({
+            "type": "object",
+            "required": [
+                "rating", "city", "date_taken", "iso", "aperture", "shutter_speed",
+                "focal_length", "exposure_compensation", "camera", "lens", "recipe_id",
+            ],
+            "properties": {
+                "rating": { "type": "integer", "minimum": 0, "maximum": 5 },
+                "city": nullable(json!({ "type": "string" })),
+                "date_taken": nullable(json!({ "type": "string", "format": "date" })),
+                "iso": { "type": "integer" },
+                "aperture": { "type": "number" },
+                "shutter_speed": { "type": "string", "examples": ["1/250"] },
+                "focal_length": {
+                    "type": "object",
+                    "required": ["value", "eq_35mm", "crop_factor"],
+                    "properties": {
+                        "value": { "type": "number" },
+                        "eq_35mm": { "type": "number" },
+                        "crop_factor": { "type": "number" },
+                    },
+                },
+                "exposure_compensation": { "type": "number" },
+                "camera": gear,
+                "lens": gear,
+                "recipe_id": nullable(json!({ "type": "string" })),
+            },
+        })
+    }
+}
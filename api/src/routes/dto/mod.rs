@@ -0,0 +1,35 @@
+//! The shapes `/v1` answers with. They are kept apart from the core models so the database can
+//! change without breaking the widgets built on top of the API, every field here is part of the
+//! public contract and described in the OpenAPI document.
+pub mod exif;
+pub mod page;
+pub mod photo;
+pub mod recipe;
+pub mod tag;
+pub mod token;
+
+pub use exif::Exif;
+pub use page::Page;
+pub use photo::Photo;
+pub use recipe::Recipe;
+pub use tag::{Collection, Tag};
+pub use token::Token;
+
+use rocket::serde::json::{Value, json};
+
+/// A DTO described in the OpenAPI document, under `#/components/schemas/<name>`.
+pub trait ApiSchema {
+    fn name() -> String;
+
+    /// JSON Schema of the DTO, other DTOs are referenced with [`schema_ref`].
+    fn schema() -> Value;
+}
+
+pub fn schema_ref<T: ApiSchema>() -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", T::name()) })
+}
+
+/// A property that may be `null`, written so it works for references too.
+fn nullable(schema: Value) -> Value {
+    json!({ "anyOf": [schema, { "type": "null" }] })
+}
@@ -0,0 +1,143 @@
+use super::{ApiSchema, nullable, schema_ref};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use core_victorhqc_com::models::photo::Photo as CorePhoto;
+use rocket::serde::json::{Value, json};
+use serde::Serialize;
+
+pub const DEFAULT_LIMIT: usize = 20;
+pub const MAX_LIMIT: usize = 100;
+
+/// A slice of a list. `next_cursor` is passed as `cursor` to get the following one, it's `null`
+/// on the last page.
+#[derive(Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+impl<T: ApiSchema> ApiSchema for Page<T> {
+    fn name() -> String {
+        format!("{}Page", T::name())
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["items", "next_cursor"],
+            "properties": {
+                "items": { "type": "array", "items": schema_ref::<T>() },
+                "next_cursor": nullable(json!({ "type": "string" })),
+            },
+        })
+    }
+}
+
+/// Where a page ends. Photos are listed oldest first, the creation date and id of the last one
+/// are enough to continue after it, even when photos were added in between.
+#[derive(Debug, PartialEq)]
+pub struct Cursor {
+    created_at: i64,
+    id: String,
+}
+
+impl Cursor {
+    fn of(photo: &CorePhoto) -> Self {
+        Cursor {
+            created_at: photo.created_at.unix_timestamp(),
+            id: photo.id.clone(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}:{}", self.created_at, self.id))
+    }
+
+    /// `None` when the value wasn't given by this API.
+    pub fn decode(value: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(value).ok()?;
+        let text = String::from_utf8(bytes).ok()?;
+        let (created_at, id) = text.split_once(':')?;
+
+        Some(Cursor {
+            created_at: created_at.parse().ok()?,
+            id: id.to_string(),
+        })
+    }
+
+    /// The creation date and id of the last photo of the previous page.
+    pub fn key(&self) -> (i64, &str) {
+        (self.created_at, &self.id)
+    }
+}
+
+/// The limit asked for, within the bounds of a page.
+pub fn page_limit(limit: Option<usize>) -> usize {
+    limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+}
+
+/// Cuts the photos found after the cursor, up to one more than `limit`, to the page and the
+/// cursor of the next one. The extra photo only tells whether there's a next page.
+pub fn paginate(mut photos: Vec<CorePhoto>, limit: usize) -> (Vec<CorePhoto>, Option<String>) {
+    let next_cursor = if photos.len() > limit {
+        photos.truncate(limit);
+        photos.last().map(|p| Cursor::of(p).encode())
+    } else {
+        None
+    };
+
+    (photos, next_cursor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_victorhqc_com::models::photo::Orientation;
+    use std::path::Path;
+    use time::OffsetDateTime;
+
+    fn photo(created_at: i64) -> CorePhoto {
+        let mut photo = CorePhoto::new(
+            format!("Photo {}", created_at),
+            Path::new("photo.jpg"),
+            Orientation::Landscape,
+        )
+        .unwrap();
+        photo.created_at = OffsetDateTime::from_unix_timestamp(created_at).unwrap();
+
+        photo
+    }
+
+    #[test]
+    fn it_continues_after_the_last_photo() {
+        let photos: Vec<CorePhoto> = (0..3).map(photo).collect();
+
+        let (page, next) = paginate(photos.clone(), 2);
+        assert_eq!(page.len(), 2);
+
+        let cursor = Cursor::decode(&next.unwrap()).unwrap();
+        assert_eq!(cursor.key(), (1, page[1].id.as_str()));
+
+        let (page, next) = paginate(photos, 3);
+        assert_eq!(page.len(), 3);
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn it_clamps_the_limit() {
+        assert_eq!(page_limit(Some(0)), 1);
+        assert_eq!(page_limit(Some(1000)), MAX_LIMIT);
+        assert_eq!(page_limit(None), DEFAULT_LIMIT);
+    }
+
+    #[test]
+    fn it_rejects_foreign_cursors() {
+        assert_eq!(Cursor::decode("not a cursor"), None);
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode("abc")), None);
+
+        let cursor = Cursor {
+            created_at: 10,
+            id: "a:b".to_string(),
+        };
+        assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor));
+    }
+}
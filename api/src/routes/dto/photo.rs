@@ -0,0 +1,218 @@
+use super::{ApiSchema, Exif, nullable, schema_ref};
+use core_victorhqc_com::{
+    aws::image_size::{ImageSize, ImageType},
+    models::{
+        exif_meta::{ExifMeta, db::Error as ExifMetaDbError},
+        photo::Photo as CorePhoto,
+        photo_rendition::{PhotoRendition, db::Error as PhotoRenditionDbError},
+        tag::{Tag, db::Error as TagDbError},
+    },
+    sqlx::SqliteConnection,
+};
+use rocket::serde::json::{Value, json};
+use serde::Serialize;
+use snafu::prelude::*;
+use std::collections::HashMap;
+use time::format_description::well_known::Rfc3339;
+
+#[derive(Serialize)]
+pub struct Photo {
+    pub id: String,
+    pub title: String,
+    pub orientation: String,
+    /// Width over height, to lay the photo out before it loads.
+    pub aspect_ratio: Option<f64>,
+    pub blurhash: Option<String>,
+    pub palette: Option<Palette>,
+    pub tags: Vec<String>,
+    pub exif: Option<Exif>,
+    /// Every file served for the photo.
+    pub images: Vec<Image>,
+    /// RFC 3339.
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Serialize)]
+pub struct Palette {
+    /// `#rrggbb`, from the most to the least common.
+    pub colors: Vec<String>,
+    pub dominant: String,
+}
+
+#[derive(Serialize)]
+pub struct Image {
+    pub size: String,
+    /// `jpeg` or `webp`.
+    pub kind: String,
+    /// `null` for photos built before the dimensions were recorded.
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub url: String,
+}
+
+impl Image {
+    fn new(photo_id: &str, size: &ImageSize, kind: &ImageType) -> Self {
+        let kind = kind.to_string().to_lowercase();
+
+        Image {
+            url: format!("/v1/images/{}/{}?kind={}", size, photo_id, kind),
+            size: size.to_string(),
+            kind,
+            width: None,
+            height: None,
+        }
+    }
+}
+
+impl From<PhotoRendition> for Image {
+    fn from(value: PhotoRendition) -> Self {
+        Image {
+            width: value.width,
+            height: value.height,
+            ..Image::new(&value.photo_id, &value.size, &value.kind)
+        }
+    }
+}
+
+impl Photo {
+    /// Builds the DTOs of the photos with their tags, EXIF and images, in as many queries as
+    /// there are relations regardless of how many photos there are.
+    pub async fn load(
+        conn: &mut SqliteConnection,
+        photos: Vec<CorePhoto>,
+    ) -> Result<Vec<Photo>, Error> {
+        if photos.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids: Vec<String> = photos.iter().map(|p| p.id.clone()).collect();
+
+        let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+        for (photo_id, tag) in Tag::find_by_photo_ids(conn, &ids)
+            .await
+            .context(TagsSnafu)?
+        {
+            tags.entry(photo_id).or_default().push(tag.name);
+        }
+
+        let mut exifs: HashMap<String, ExifMeta> = ExifMeta::find_by_photo_ids(conn, &ids)
+            .await
+            .context(ExifSnafu)?
+            .into_iter()
+            .map(|e| (e.photo_id.clone(), e))
+            .collect();
+
+        let mut images: HashMap<String, Vec<Image>> = HashMap::new();
+        for rendition in PhotoRendition::find_by_photo_ids(conn, &ids)
+            .await
+            .context(RenditionsSnafu)?
+        {
+            images
+                .entry(rendition.photo_id.clone())
+                .or_default()
+                .push(rendition.into());
+        }
+
+        let photos = photos
+            .into_iter()
+            .map(|photo| {
+                // Photos built before the renditions were recorded still have every file.
+                let images = images.remove(&photo.id).unwrap_or_else(|| {
+                    ImageSize::renditions()
+                        .flat_map(|size| {
+                            [ImageType::Jpeg, ImageType::Webp]
+                                .map(|kind| Image::new(&photo.id, &size, &kind))
+                        })
+                        .collect()
+                });
+
+                Photo {
+                    tags: tags.remove(&photo.id).unwrap_or_default(),
+                    exif: exifs.remove(&photo.id).map(Exif::from),
+                    images,
+                    id: photo.id,
+                    title: photo.title,
+                    orientation: photo.orientation.to_string(),
+                    aspect_ratio: photo.aspect_ratio,
+                    blurhash: photo.blurhash,
+                    palette: photo.palette.map(|p| Palette {
+                        colors: p.colors.iter().map(|c| c.to_string()).collect(),
+                        dominant: p.dominant.to_string(),
+                    }),
+                    created_at: photo.created_at.format(&Rfc3339).unwrap_or_default(),
+                    updated_at: photo.updated_at.format(&Rfc3339).unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        Ok(photos)
+    }
+}
+
+impl ApiSchema for Photo {
+    fn name() -> String {
+        "Photo".to_string()
+    }
+
+    fn schema() -> Value {
+        let string = json!({ "type": "string" });
+        let dimension = nullable(json!({ "type": "integer" }));
+
+        json!({
+            "type": "object",
+            "required": [
+                "id", "title", "orientation", "aspect_ratio", "blurhash", "palette", "tags",
+                "exif", "images", "created_at", "updated_at",
+            ],
+            "properties": {
+                "id": string,
+                "title": string,
+                "orientation": {
+                    "type": "string",
+                    "enum": ["landscape", "portrait", "square", "panorama"],
+                },
+                "aspect_ratio": nullable(json!({ "type": "number" })),
+                "blurhash": nullable(string.clone()),
+                "palette": nullable(json!({
+                    "type": "object",
+                    "required": ["colors", "dominant"],
+                    "properties": {
+                        "colors": { "type": "array", "items": { "type": "string" } },
+                        "dominant": { "type": "string", "examples": ["#1f2a3c"] },
+                    },
+                })),
+                "tags": { "type": "array", "items": string },
+                "exif": nullable(schema_ref::<Exif>()),
+                "images": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["size", "kind", "width", "height", "url"],
+                        "properties": {
+                            "size": string,
+                            "kind": { "type": "string", "enum": ["jpeg", "webp"] },
+                            "width": dimension,
+                            "height": dimension,
+                            "url": string,
+                        },
+                    },
+                },
+                "created_at": { "type": "string", "format": "date-time" },
+                "updated_at": { "type": "string", "format": "date-time" },
+            },
+        })
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to get the tags: {}", source))]
+    Tags { source: TagDbError },
+
+    #[snafu(display("Failed to get the EXIF: {}", source))]
+    Exif { source: ExifMetaDbError },
+
+    #[snafu(display("Failed to get the renditions: {}", source))]
+    Renditions { source: PhotoRenditionDbError },
+}
@@ -0,0 +1,70 @@
+use super::ApiSchema;
+use core_victorhqc_com::models::fujifilm::{FujifilmRecipe, file::RecipeFile};
+use rocket::serde::json::{Value, json};
+use serde::Serialize;
+
+/// A Fujifilm recipe. Besides the id and card, it has the same fields as the files `recipe
+/// export` writes, so a recipe can be imported straight from the API.
+#[derive(Serialize)]
+pub struct Recipe {
+    pub id: String,
+    #[serde(flatten)]
+    pub settings: RecipeFile,
+    /// The recipe drawn as a card, see `/v1/recipes/{id}/card`.
+    pub card: String,
+}
+
+impl From<&FujifilmRecipe> for Recipe {
+    fn from(value: &FujifilmRecipe) -> Self {
+        Recipe {
+            id: value.id.clone(),
+            settings: value.into(),
+            card: format!("/v1/recipes/{}/card", value.id),
+        }
+    }
+}
+
+impl ApiSchema for Recipe {
+    fn name() -> String {
+        "Recipe".to_string()
+    }
+
+    fn schema() -> Value {
+        let string = json!({ "type": "string" });
+
+        // Settings the sensor doesn't support are left out instead of being `null`, the author
+        // and source too when they aren't known.
+        json!({
+            "type": "object",
+            "required": [
+                "id", "name", "sensor", "film_simulation", "white_balance",
+                "white_balance_shift", "dynamic_range", "highlight_tone", "shadow_tone", "color",
+                "sharpness", "high_iso_noise_reduction", "card",
+            ],
+            "properties": {
+                "id": string,
+                "name": string,
+                "author": string,
+                "src": string,
+                "sensor": string,
+                "film_simulation": string,
+                "white_balance": string,
+                "white_balance_shift": string,
+                "dynamic_range": string,
+                "d_range_priority": string,
+                "highlight_tone": { "type": "number" },
+                "shadow_tone": { "type": "number" },
+                "color": { "type": "integer" },
+                "sharpness": { "type": "integer" },
+                "clarity": { "type": "integer" },
+                "high_iso_noise_reduction": { "type": "integer" },
+                "grain_strength": string,
+                "grain_size": string,
+                "color_chrome_effect": string,
+                "color_chrome_fx_blue": string,
+                "monochromatic_color": string,
+                "card": { "type": "string", "format": "uri-reference" },
+            },
+        })
+    }
+}
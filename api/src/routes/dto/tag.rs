@@ -0,0 +1,74 @@
+use super::ApiSchema;
+use core_victorhqc_com::models::tag::Tag as CoreTag;
+use rocket::serde::json::{Value, json};
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct Tag {
+    pub name: String,
+    /// Photos with the tag, see `/v1/tags/{name}/photos`.
+    pub photos: String,
+}
+
+impl From<CoreTag> for Tag {
+    fn from(value: CoreTag) -> Self {
+        Tag {
+            photos: format!("/v1/tags/{}/photos", value.name),
+            name: value.name,
+        }
+    }
+}
+
+impl ApiSchema for Tag {
+    fn name() -> String {
+        "Tag".to_string()
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["name", "photos"],
+            "properties": {
+                "name": { "type": "string" },
+                "photos": { "type": "string", "format": "uri-reference" },
+            },
+        })
+    }
+}
+
+/// One of the tags the site shows as a gallery.
+#[derive(Serialize)]
+pub struct Collection {
+    pub name: String,
+    pub photo_count: usize,
+    /// Photos in the collection, see `/v1/collections/{name}/photos`.
+    pub photos: String,
+}
+
+impl Collection {
+    pub fn new(name: String, photo_count: usize) -> Self {
+        Collection {
+            photos: format!("/v1/collections/{}/photos", name),
+            name,
+            photo_count,
+        }
+    }
+}
+
+impl ApiSchema for Collection {
+    fn name() -> String {
+        "Collection".to_string()
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["name", "photo_count", "photos"],
+            "properties": {
+                "name": { "type": "string" },
+                "photo_count": { "type": "integer", "minimum": 0 },
+                "photos": { "type": "string", "format": "uri-reference" },
+            },
+        })
+    }
+}
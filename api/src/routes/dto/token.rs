@@ -0,0 +1,51 @@
+use super::{ApiSchema, nullable};
+use core_victorhqc_com::models::api_token::ApiToken;
+use rocket::serde::json::{Value, json};
+use serde::Serialize;
+use time::format_description::well_known::Rfc3339;
+
+/// The token a request was made with, never its secret.
+#[derive(Serialize)]
+pub struct Token {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    /// RFC 3339, `null` when it never expires.
+    pub expires_at: Option<String>,
+}
+
+impl From<&ApiToken> for Token {
+    fn from(value: &ApiToken) -> Self {
+        Token {
+            id: value.id.clone(),
+            name: value.name.clone(),
+            scopes: value.scopes.iter().map(|s| s.to_string()).collect(),
+            expires_at: value.expires_at.and_then(|e| e.format(&Rfc3339).ok()),
+        }
+    }
+}
+
+impl ApiSchema for Token {
+    fn name() -> String {
+        "Token".to_string()
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["id", "name", "scopes", "expires_at"],
+            "properties": {
+                "id": { "type": "string" },
+                "name": { "type": "string" },
+                "scopes": {
+                    "type": "array",
+                    "items": {
+                        "type": "string",
+                        "enum": ["read", "write:photos", "write:tags", "admin"],
+                    },
+                },
+                "expires_at": nullable(json!({ "type": "string", "format": "date-time" })),
+            },
+        })
+    }
+}
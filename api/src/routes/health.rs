@@ -0,0 +1,92 @@
+//! Probes for the process manager and metrics for Prometheus. They aren't part of the API, so
+//! they live outside of `/v1`.
+use crate::{
+    AppState,
+    auth::{Admin, Authorized},
+    metrics::{Gauge, METRICS},
+};
+use core_victorhqc_com::sqlx;
+use rocket::{
+    State,
+    http::{ContentType, Status},
+    serde::json::{Json, Value, json},
+    tokio::time::timeout,
+};
+use std::{sync::atomic::Ordering, time::Duration};
+
+/// How long a dependency has to answer the readiness probe.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The process is up, nothing else is checked.
+#[get("/healthz")]
+pub fn healthz() -> &'static str {
+    "ok"
+}
+
+/// Whether the API can serve: the database and the bucket answer, and the images of the cached
+/// tags were downloaded. Every check is listed, a 503 means one of them failed.
+#[get("/readyz")]
+pub async fn readyz(state: &State<AppState>) -> (Status, Json<Value>) {
+    let query = sqlx::query("SELECT 1").execute(&state.db_pool);
+    let database = matches!(timeout(CHECK_TIMEOUT, query).await, Ok(Ok(_)));
+    let storage = timeout(CHECK_TIMEOUT, state.img_cache.s3.is_reachable())
+        .await
+        .unwrap_or(false);
+    let bootstrap = state.bootstrapped.load(Ordering::Acquire);
+
+    let ready = database && storage && bootstrap;
+    let status = if ready {
+        Status::Ok
+    } else {
+        Status::ServiceUnavailable
+    };
+
+    let body = json!({
+        "ready": ready,
+        "checks": {
+            "database": database,
+            "storage": storage,
+            "bootstrap": bootstrap,
+        },
+    });
+
+    (status, Json(body))
+}
+
+/// Every metric in the Prometheus text format. The state of the pool and the size of the image
+/// cache are read on each scrape. Scrapers need an `admin` token.
+#[get("/metrics")]
+pub async fn metrics(_admin: Authorized<Admin>, state: &State<AppState>) -> (ContentType, String) {
+    let (images, bytes) = state.img_cache.stats().await;
+    let pool = &state.db_pool;
+
+    let gauges = [
+        Gauge {
+            name: "image_cache_images",
+            help: "Images in the cache.",
+            value: images as f64,
+        },
+        Gauge {
+            name: "image_cache_bytes",
+            help: "Size of the images in the cache.",
+            value: bytes as f64,
+        },
+        Gauge {
+            name: "db_pool_connections",
+            help: "Open connections of the database pool.",
+            value: pool.size() as f64,
+        },
+        Gauge {
+            name: "db_pool_idle_connections",
+            help: "Open connections of the database pool that aren't in use.",
+            value: pool.num_idle() as f64,
+        },
+        Gauge {
+            name: "db_pool_max_connections",
+            help: "Connections the database pool can open.",
+            value: pool.options().get_max_connections() as f64,
+        },
+    ];
+
+    (ContentType::Plain, METRICS.render(&gauges))
+}
@@ -1,23 +1,32 @@
+use super::{
+    conditional::{ByteRange, Range, format_http_date, if_range, none_match, not_modified_since},
+    problem::Problem,
+};
 use crate::AppState;
-use crate::cache::image_cache::Error as CacheError;
+use crate::cache::image_cache::{Error as CacheError, ImageCache};
+use crate::metrics::METRICS;
 use core_victorhqc_com::{
-    aws::image_size::{Error as ParseError, ImageSize, ImageType},
-    models::photo::{Photo, db::Error as PhotoDbError},
-    sqlx::Error as SqlxError,
+    aws::{
+        image_size::{Error as ParseError, ImageSize, ImageType},
+        photo::Error as AWSError,
+    },
+    models::{
+        photo::{Photo, db::Error as PhotoDbError},
+        photo_rendition::{PhotoRendition, db::Error as PhotoRenditionDbError},
+    },
+    sqlx::{Error as SqlxError, SqliteConnection},
 };
-use log::{debug, error};
 use rocket::{
     Request, Response, State,
-    http::{ContentType, Header, Status},
+    http::{Header, Status},
     request::{FromRequest, Outcome},
     response::Responder,
-    serde::json::serde_json,
 };
-use serde::ser::SerializeStruct;
-use serde::{Serialize, Serializer};
 use snafu::prelude::*;
 use std::io::Cursor;
 use std::str::FromStr;
+use time::OffsetDateTime;
+use tracing::debug;
 
 #[get("/images/<size>/<id>?<kind>")]
 pub async fn get_image(
@@ -25,7 +34,30 @@ pub async fn get_image(
     id: &str,
     kind: Option<&str>,
     state: &State<AppState>,
-    if_none_match: IfNoneMatch,
+    conditions: Conditions,
+) -> Result<ImageResponse, Error> {
+    serve(size, id, kind, state, conditions, true).await
+}
+
+/// Same headers as `get_image`, without downloading the image when it isn't cached.
+#[head("/images/<size>/<id>?<kind>")]
+pub async fn head_image(
+    size: &str,
+    id: &str,
+    kind: Option<&str>,
+    state: &State<AppState>,
+    conditions: Conditions,
+) -> Result<ImageResponse, Error> {
+    serve(size, id, kind, state, conditions, false).await
+}
+
+async fn serve(
+    size: &str,
+    id: &str,
+    kind: Option<&str>,
+    state: &State<AppState>,
+    conditions: Conditions,
+    with_body: bool,
 ) -> Result<ImageResponse, Error> {
     let pool = &state.db_pool;
     let cache = &state.img_cache;
@@ -35,6 +67,9 @@ pub async fn get_image(
         size: size.to_string(),
     })?;
 
+    // Originals are archived for rebuilding the renditions, not for visitors.
+    ensure!(img_size != ImageSize::Original, OriginalSnafu);
+
     let kind = kind.map_or("webp", |k| k);
     let img_kind: ImageType = ImageType::from_str(kind).context(KindSnafu {
         kind: kind.to_string(),
@@ -42,49 +77,113 @@ pub async fn get_image(
 
     debug!("id: {}", id);
 
-    if let Some(etag) = if_none_match.0
-        && cache.md5_exists(&etag).await
-    {
-        debug!("Cache Hit");
+    let photo = Photo::find_by_id(&mut conn, id).await.context(PhotoSnafu)?;
+    // Rebuilding a photo touches it, so it changes whenever its images do.
+    let last_modified = photo.updated_at;
 
-        return Ok(ImageResponse::not_modified(etag, img_kind));
-    };
+    let (etag, len) = validators(&mut conn, cache, &photo, &img_kind, &img_size).await?;
+    drop(conn);
 
-    debug!("Cache Miss");
+    if conditions.not_modified(&etag, last_modified) {
+        return Ok(ImageResponse::new(
+            etag,
+            last_modified,
+            img_kind,
+            Body::NotModified,
+        ));
+    }
 
-    let photo = Photo::find_by_id(&mut conn, id).await.context(PhotoSnafu)?;
+    if !with_body {
+        return Ok(ImageResponse::new(
+            etag,
+            last_modified,
+            img_kind,
+            Body::Headers(len),
+        ));
+    }
 
     let (etag, data) = cache
         .get(photo, &img_kind, &img_size)
         .await
         .context(CacheSnafu)?;
 
-    Ok(ImageResponse::ok(data, etag, img_kind))
+    let body = match conditions.range(&etag, last_modified, data.len() as u64) {
+        Range::Full => Body::Full(data),
+        Range::Partial(range) => Body::Partial(data, range),
+        Range::Unsatisfiable => Body::Unsatisfiable(data.len() as u64),
+    };
+
+    Ok(ImageResponse::new(etag, last_modified, img_kind, body))
+}
+
+/// ETag and length of the image, found without downloading it: from the cache when it's there,
+/// otherwise from what was recorded when it was built.
+async fn validators(
+    conn: &mut SqliteConnection,
+    cache: &ImageCache,
+    photo: &Photo,
+    kind: &ImageType,
+    size: &ImageSize,
+) -> Result<(String, u64), Error> {
+    if let Some(cached) = cache.md5_of(&photo.id, kind, size).await {
+        debug!("Cache Hit");
+
+        return Ok(cached);
+    }
+
+    debug!("Cache Miss");
+
+    let rendition = PhotoRendition::find_one(conn, &photo.id, size, kind)
+        .await
+        .context(RenditionSnafu)?;
+
+    if let Some(PhotoRendition {
+        md5: Some(md5),
+        bytes,
+        ..
+    }) = rendition
+    {
+        return Ok((md5, bytes as u64));
+    }
+
+    // Renditions built before their MD5 was recorded. S3 uses the MD5 as ETag for objects that
+    // weren't uploaded in parts, which is how every image is uploaded.
+    let head = cache.s3.head_from_aws_s3((photo, size, kind));
+    let head = METRICS
+        .s3("head_object", head)
+        .await
+        .map_err(Box::new)
+        .context(HeadSnafu)?;
+
+    let etag = head.e_tag().map(|tag| tag.trim_matches('"').to_string());
+    let len = head.content_length().map(|len| len as u64);
+
+    etag.zip(len).context(MetadataSnafu)
+}
+
+enum Body {
+    NotModified,
+    /// Only the headers, for `HEAD`, with the length the body would have.
+    Headers(u64),
+    Full(Vec<u8>),
+    Partial(Vec<u8>, ByteRange),
+    Unsatisfiable(u64),
 }
 
 pub struct ImageResponse {
-    data: Option<Vec<u8>>,
     kind: ImageType,
     etag: String,
-    not_modified: bool,
+    last_modified: OffsetDateTime,
+    body: Body,
 }
 
 impl ImageResponse {
-    pub fn ok(data: Vec<u8>, etag: String, kind: ImageType) -> Self {
-        Self {
-            data: Some(data),
-            kind,
-            etag,
-            not_modified: false,
-        }
-    }
-
-    pub fn not_modified(etag: String, kind: ImageType) -> Self {
+    fn new(etag: String, last_modified: OffsetDateTime, kind: ImageType, body: Body) -> Self {
         Self {
-            data: None,
             kind,
             etag,
-            not_modified: true,
+            last_modified,
+            body,
         }
     }
 }
@@ -99,35 +198,101 @@ impl<'r> Responder<'r, 'static> for ImageResponse {
         let mut response = Response::build();
         response
             .header(Header::new("Content-Type", content_type))
-            .header(Header::new("ETag", self.etag))
+            .header(Header::new("ETag", format!("\"{}\"", self.etag)))
+            .header(Header::new(
+                "Last-Modified",
+                format_http_date(self.last_modified),
+            ))
+            .header(Header::new("Accept-Ranges", "bytes"))
             .header(Header::new(
                 "Cache-Control",
                 "public, max-age=31536000, immutable",
             ));
 
-        if self.not_modified {
-            // 304 Not Modified - no body
-            response.status(Status::NotModified);
-        } else if let Some(data) = self.data {
-            // 200 OK - with body
-            response.sized_body(data.len(), Cursor::new(data));
-        } else {
-            return Err(Status::InternalServerError);
+        match self.body {
+            Body::NotModified => {
+                response.status(Status::NotModified);
+            }
+            Body::Headers(len) => {
+                // Rocket strips the body of HEAD responses but keeps the size it was given, which
+                // becomes the Content-Length.
+                response.sized_body(len as usize, Cursor::new(Vec::new()));
+            }
+            Body::Full(data) => {
+                response.sized_body(data.len(), Cursor::new(data));
+            }
+            Body::Partial(data, range) => {
+                let total = data.len();
+                let part = data[range.start as usize..=range.end as usize].to_vec();
+
+                response
+                    .status(Status::PartialContent)
+                    .header(Header::new(
+                        "Content-Range",
+                        format!("bytes {}-{}/{}", range.start, range.end, total),
+                    ))
+                    .sized_body(range.len() as usize, Cursor::new(part));
+            }
+            Body::Unsatisfiable(len) => {
+                response
+                    .status(Status::RangeNotSatisfiable)
+                    .header(Header::new("Content-Range", format!("bytes */{}", len)));
+            }
         }
 
         response.ok()
     }
 }
 
-pub struct IfNoneMatch(Option<String>);
+/// The conditional and range headers of the request.
+pub struct Conditions {
+    if_none_match: Option<String>,
+    if_modified_since: Option<String>,
+    range: Option<String>,
+    if_range: Option<String>,
+}
+
+impl Conditions {
+    /// Whether a 304 is enough. `If-Modified-Since` is only looked at when there's no
+    /// `If-None-Match`, the etag being the more precise of both.
+    fn not_modified(&self, etag: &str, last_modified: OffsetDateTime) -> bool {
+        match (&self.if_none_match, &self.if_modified_since) {
+            (Some(header), _) => none_match(header, etag),
+            (None, Some(header)) => not_modified_since(header, last_modified),
+            (None, None) => false,
+        }
+    }
+
+    /// The part of the body to send. A `Range` is ignored when its `If-Range` no longer matches,
+    /// so a client resuming a download of an older image gets the new one whole.
+    fn range(&self, etag: &str, last_modified: OffsetDateTime, len: u64) -> Range {
+        let Some(range) = &self.range else {
+            return Range::Full;
+        };
+
+        if let Some(header) = &self.if_range
+            && !if_range(header, etag, last_modified)
+        {
+            return Range::Full;
+        }
+
+        Range::parse(range, len)
+    }
+}
 
 #[rocket::async_trait]
-impl<'r> FromRequest<'r> for IfNoneMatch {
+impl<'r> FromRequest<'r> for Conditions {
     type Error = ();
 
     async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        let header_value = req.headers().get_one("If-None-Match");
-        Outcome::Success(IfNoneMatch(header_value.map(String::from)))
+        let header = |name: &str| req.headers().get_one(name).map(String::from);
+
+        Outcome::Success(Conditions {
+            if_none_match: header("If-None-Match"),
+            if_modified_since: header("If-Modified-Since"),
+            range: header("Range"),
+            if_range: header("If-Range"),
+        })
     }
 }
 
@@ -139,71 +304,50 @@ pub enum Error {
     #[snafu(display("Invalid type '{}': {}", kind, source))]
     Kind { kind: String, source: ParseError },
 
+    #[snafu(display("Original images are not available"))]
+    Original,
+
     #[snafu(display("Failed to get connection: {}", source))]
     Connection { source: SqlxError },
 
     #[snafu(display("Failed to get photo by id: {}", source))]
     Photo { source: PhotoDbError },
 
-    #[snafu(display("Failed to get image from cache: {}", source))]
-    Cache { source: CacheError },
-}
+    #[snafu(display("Failed to get the rendition: {}", source))]
+    Rendition { source: PhotoRenditionDbError },
 
-impl<'r> Responder<'r, 'static> for Error {
-    fn respond_to(self, _: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
-        let status: Status = match &self {
-            Error::Size { .. } => Status::InternalServerError,
-            Error::Kind { .. } => Status::BadRequest,
-            Error::Connection { .. } => Status::InternalServerError,
-            Error::Photo { .. } => Status::InternalServerError,
-            Error::Cache { source } => match source {
-                CacheError::GetAWSObject { .. } => Status::NotFound,
-                CacheError::Stream { .. } => Status::InternalServerError,
-            },
-        };
+    #[snafu(display("Failed to get the image metadata: {}", source))]
+    Head { source: Box<AWSError> },
 
-        let serialized = serde_json::to_string(&self).unwrap();
+    #[snafu(display("The stored image has no ETag or length"))]
+    Metadata,
 
-        Response::build()
-            .status(status)
-            .sized_body(serialized.len(), Cursor::new(serialized))
-            .header(ContentType::JSON)
-            .ok()
-    }
+    #[snafu(display("Failed to get image from cache: {}", source))]
+    Cache { source: CacheError },
 }
 
-impl Serialize for Error {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let mut state = serializer.serialize_struct("Error", 2)?;
-
-        match self {
-            Error::Size { size, .. } => {
-                state.serialize_field("kind", &format!("Invalid Size: {}", size))?;
-                state.serialize_field("message", &self.to_string())?;
-            }
-            Error::Kind { kind, .. } => {
-                state.serialize_field("kind", &format!("Invalid type: {}", kind))?;
-                state.serialize_field("message", &self.to_string())?;
-            }
-            Error::Connection { .. } => {
-                state.serialize_field("kind", "Connection Error")?;
-                state.serialize_field("message", &self.to_string())?;
-            }
-            Error::Photo { .. } => {
-                state.serialize_field("kind", "Photo Error")?;
-                state.serialize_field("message", &self.to_string())?;
-            }
-            Error::Cache { source } => {
-                state.serialize_field("kind", "Invalid Photo")?;
-                state.serialize_field("message", "")?;
-
-                error!("Failed to get AWS Object: {}", source);
+impl From<&Error> for Problem {
+    fn from(error: &Error) -> Self {
+        match error {
+            Error::Size { .. } | Error::Kind { .. } => Problem::BadRequest(error.to_string()),
+            Error::Original => Problem::NotFound(error.to_string()),
+            Error::Connection { source } => Problem::from_connection(source),
+            Error::Photo {
+                source: PhotoDbError::Sqlx { source },
+            } => Problem::from_query(source, "The photo"),
+            Error::Head { source } if source.is_not_found() => {
+                Problem::NotFound("The image does not exist".to_string())
             }
-        };
+            Error::Cache {
+                source: CacheError::GetAWSObject { source },
+            } if source.is_not_found() => Problem::NotFound("The image does not exist".to_string()),
+            _ => Problem::Internal(error.to_string()),
+        }
+    }
+}
 
-        state.end()
+impl<'r> Responder<'r, 'static> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> rocket::response::Result<'static> {
+        Problem::from(&self).respond_to(req)
     }
 }
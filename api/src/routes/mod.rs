@@ -1,2 +1,52 @@
+pub mod conditional;
+pub mod dto;
+pub mod health;
 pub mod images;
+mod openapi;
 pub mod photos;
+pub mod problem;
+pub mod recipes;
+pub mod tags;
+#[cfg(test)]
+mod tests;
+pub mod token;
+
+use rocket::{Catcher, Route};
+
+/// Every route of the REST surface, mounted under `/v1/`.
+pub fn v1() -> Vec<Route> {
+    routes![
+        photos::get_all_photos,
+        photos::get_photo,
+        photos::create_photo,
+        tags::get_tags,
+        tags::get_tag_photos,
+        tags::get_collections,
+        tags::get_collection_photos,
+        images::get_image,
+        images::head_image,
+        recipes::get_recipes,
+        recipes::get_recipe,
+        recipes::get_recipe_card,
+        token::get_token,
+        openapi::get_openapi
+    ]
+}
+
+/// The probes and metrics, mounted under `/`.
+pub fn health() -> Vec<Route> {
+    routes![health::healthz, health::readyz, health::metrics]
+}
+
+/// Catchers of `/v1`, so the errors of the request guards are problem details too.
+pub fn v1_catchers() -> Vec<Catcher> {
+    catchers![
+        problem::unauthorized,
+        problem::forbidden,
+        problem::not_found,
+        problem::unprocessable,
+        problem::internal,
+        problem::unavailable,
+        problem::default
+    ]
+}
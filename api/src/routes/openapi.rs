@@ -0,0 +1,371 @@
+//! OpenAPI 3.1 document of `/v1`, built from the mounted routes so a new route shows up in it
+//! without listing it twice. Each route only needs an entry in [`operation`], the paths and their
+//! parameters are read from the route itself.
+use super::dto::{ApiSchema, Collection, Exif, Page, Photo, Recipe, Tag, Token, schema_ref};
+use core_victorhqc_com::models::api_token::Scope;
+use rocket::{
+    Orbit, Request, Rocket, Route,
+    http::Method,
+    request::{FromRequest, Outcome},
+    serde::json::{Json, Value, json},
+};
+use std::convert::Infallible;
+
+#[get("/openapi.json")]
+pub fn get_openapi(mounted: Mounted<'_>) -> Json<Value> {
+    Json(document(mounted.0.routes()))
+}
+
+/// The running instance, to read the routes that are mounted.
+pub struct Mounted<'r>(&'r Rocket<Orbit>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Mounted<'r> {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(Mounted(req.rocket()))
+    }
+}
+
+pub fn document<'a>(routes: impl Iterator<Item = &'a Route>) -> Value {
+    let mut paths = json!({});
+
+    for route in routes.filter(|r| r.uri.path().starts_with("/v1/")) {
+        let Some(operation) = route.name.as_deref().and_then(operation) else {
+            continue;
+        };
+
+        let path = route
+            .uri
+            .path()
+            .split('/')
+            .map(|segment| match param(segment) {
+                Some(name) => format!("{{{}}}", name),
+                None => segment.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let mut parameters: Vec<Value> = route
+            .uri
+            .path()
+            .split('/')
+            .filter_map(param)
+            .map(|name| {
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" },
+                })
+            })
+            .collect();
+
+        for name in route.uri.query().iter().flat_map(|q| q.split('&')) {
+            let Some(name) = param(name) else {
+                continue;
+            };
+
+            let schema = operation
+                .query
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map_or_else(|| json!({ "type": "string" }), |(_, schema)| schema.clone());
+
+            parameters.push(json!({ "name": name, "in": "query", "schema": schema }));
+        }
+
+        // Every POST creates something.
+        let status = if route.method == Method::Post {
+            "201"
+        } else {
+            "200"
+        };
+        let mut responses = json!({});
+        responses[status] = operation.response;
+        for status in operation.errors {
+            let reference = format!("#/components/responses/{}", status);
+            responses[status.to_string()] = json!({ "$ref": reference });
+        }
+
+        let method = route.method.as_str().to_lowercase();
+        paths[&path][&method] = json!({
+            "operationId": route.name.as_deref(),
+            "summary": operation.summary,
+            "parameters": parameters,
+            "responses": responses,
+        });
+
+        if let Some(schema) = operation.request {
+            paths[&path][&method]["requestBody"] = json!({
+                "required": true,
+                "content": { "multipart/form-data": { "schema": schema } },
+            });
+        }
+
+        if let Some(scope) = operation.scope {
+            paths[&path][&method]["security"] = json!([{ "bearer": [scope.to_string()] }]);
+        }
+    }
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "victorhqc.com",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Photos, tags, collections and Fujifilm recipes of victorhqc.com.",
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas(),
+            "securitySchemes": {
+                "bearer": { "type": "http", "scheme": "bearer" },
+            },
+            "responses": {
+                "400": problem("The request is invalid"),
+                "401": problem("No valid bearer token was sent"),
+                "403": problem("The token lacks the scope"),
+                "409": problem("The photo already exists or looks like one that does"),
+                "422": problem("Something is missing, e.g. the name of a new recipe"),
+                "404": problem("The resource does not exist"),
+                "503": problem("The database is busy, try again later"),
+            },
+        },
+    })
+}
+
+struct Operation {
+    summary: &'static str,
+    /// The `200` response.
+    response: Value,
+    /// Schemas of the query parameters, the ones missing are plain strings.
+    query: Vec<(&'static str, Value)>,
+    errors: &'static [u16],
+    /// Scope of the bearer token the route needs, `None` for the public ones.
+    scope: Option<Scope>,
+    /// Schema of the `multipart/form-data` body.
+    request: Option<Value>,
+}
+
+fn operation(name: &str) -> Option<Operation> {
+    let page = || {
+        vec![
+            (
+                "cursor",
+                json!({ "type": "string", "description": "next_cursor of the previous page" }),
+            ),
+            (
+                "limit",
+                json!({ "type": "integer", "minimum": 1, "maximum": 100, "default": 20 }),
+            ),
+        ]
+    };
+    let image_kind = json!({ "type": "string", "enum": ["jpeg", "webp"], "default": "webp" });
+
+    let operation = match name {
+        "get_all_photos" => Operation {
+            summary: "Every photo, oldest first",
+            response: json_response(schema_ref::<Page<Photo>>()),
+            query: page(),
+            errors: &[400, 503],
+            scope: None,
+            request: None,
+        },
+        "get_photo" => Operation {
+            summary: "A photo with its tags, EXIF and images",
+            response: json_response(schema_ref::<Photo>()),
+            query: vec![],
+            errors: &[404, 503],
+            scope: None,
+            request: None,
+        },
+        "create_photo" => Operation {
+            summary: "Publish a photo from its original, through the same pipeline as the CLI",
+            response: json_response(schema_ref::<Photo>()),
+            query: vec![],
+            errors: &[400, 401, 403, 409, 422, 503],
+            scope: Some(Scope::WritePhotos),
+            request: Some(json!({
+                "type": "object",
+                "required": ["photo", "title"],
+                "properties": {
+                    "photo": { "type": "string", "format": "binary" },
+                    "title": { "type": "string" },
+                    "tags": { "type": "string", "description": "Comma separated" },
+                    "recipe": {
+                        "type": "string",
+                        "description": "Name of the recipe when it is seen for the first time",
+                    },
+                    "watermark": { "type": "boolean", "default": true },
+                    "force": {
+                        "type": "boolean",
+                        "default": false,
+                        "description": "Publish even when it looks like a photo already uploaded",
+                    },
+                },
+            })),
+        },
+        "get_tags" => Operation {
+            summary: "Every tag, sorted by name",
+            response: json_response(array_of::<Tag>()),
+            query: vec![],
+            errors: &[503],
+            scope: None,
+            request: None,
+        },
+        "get_tag_photos" => Operation {
+            summary: "Photos with the tag, oldest first",
+            response: json_response(schema_ref::<Page<Photo>>()),
+            query: page(),
+            errors: &[400, 404, 503],
+            scope: None,
+            request: None,
+        },
+        "get_collections" => Operation {
+            summary: "The galleries of the site",
+            response: json_response(array_of::<Collection>()),
+            query: vec![],
+            errors: &[503],
+            scope: None,
+            request: None,
+        },
+        "get_collection_photos" => Operation {
+            summary: "Photos in the collection, oldest first",
+            response: json_response(schema_ref::<Page<Photo>>()),
+            query: page(),
+            errors: &[400, 404, 503],
+            scope: None,
+            request: None,
+        },
+        "get_recipes" => Operation {
+            summary: "Every Fujifilm recipe",
+            response: json_response(array_of::<Recipe>()),
+            query: vec![],
+            errors: &[503],
+            scope: None,
+            request: None,
+        },
+        "get_recipe" => Operation {
+            summary: "A Fujifilm recipe",
+            response: json_response(schema_ref::<Recipe>()),
+            query: vec![],
+            errors: &[404, 503],
+            scope: None,
+            request: None,
+        },
+        "get_recipe_card" => Operation {
+            summary: "The recipe drawn as a card",
+            response: binary_response(&["image/png", "image/svg+xml"]),
+            query: vec![(
+                "kind",
+                json!({ "type": "string", "enum": ["png", "svg"], "default": "png" }),
+            )],
+            errors: &[400, 404, 503],
+            scope: None,
+            request: None,
+        },
+        "get_image" => Operation {
+            summary: "A photo file, supports Range and conditional requests",
+            response: binary_response(&["image/jpeg", "image/webp"]),
+            query: vec![("kind", image_kind)],
+            errors: &[400, 404, 503],
+            scope: None,
+            request: None,
+        },
+        "head_image" => Operation {
+            summary: "The headers of a photo file",
+            response: json!({ "description": "OK" }),
+            query: vec![("kind", image_kind)],
+            errors: &[400, 404, 503],
+            scope: None,
+            request: None,
+        },
+        "get_token" => Operation {
+            summary: "The token the request was made with",
+            response: json_response(schema_ref::<Token>()),
+            query: vec![],
+            errors: &[401, 503],
+            scope: Some(Scope::Read),
+            request: None,
+        },
+        "get_openapi" => Operation {
+            summary: "This document",
+            response: json_response(json!({ "type": "object" })),
+            query: vec![],
+            errors: &[],
+            scope: None,
+            request: None,
+        },
+        _ => return None,
+    };
+
+    Some(operation)
+}
+
+/// Name of a dynamic segment, `<id>` gives `id`.
+fn param(segment: &str) -> Option<&str> {
+    segment
+        .strip_prefix('<')
+        .and_then(|s| s.strip_suffix('>'))
+        .map(|s| s.trim_end_matches(".."))
+}
+
+fn schemas() -> Value {
+    let mut schemas = json!({});
+
+    for (name, schema) in [
+        (Photo::name(), Photo::schema()),
+        (Page::<Photo>::name(), Page::<Photo>::schema()),
+        (Exif::name(), Exif::schema()),
+        (Tag::name(), Tag::schema()),
+        (Collection::name(), Collection::schema()),
+        (Recipe::name(), Recipe::schema()),
+        (Token::name(), Token::schema()),
+    ] {
+        schemas[name] = schema;
+    }
+
+    schemas["Problem"] = json!({
+        "type": "object",
+        "required": ["type", "title", "status", "instance"],
+        "properties": {
+            "type": { "type": "string" },
+            "title": { "type": "string" },
+            "status": { "type": "integer" },
+            "detail": { "type": "string" },
+            "instance": { "type": "string" },
+        },
+    });
+
+    schemas
+}
+
+fn array_of<T: ApiSchema>() -> Value {
+    json!({ "type": "array", "items": schema_ref::<T>() })
+}
+
+fn json_response(schema: Value) -> Value {
+    json!({
+        "description": "OK",
+        "content": { "application/json": { "schema": schema } },
+    })
+}
+
+fn binary_response(types: &[&str]) -> Value {
+    let mut content = json!({});
+    for kind in types {
+        content[*kind] = json!({ "schema": { "type": "string", "format": "binary" } });
+    }
+
+    json!({ "description": "OK", "content": content })
+}
+
+fn problem(description: &str) -> Value {
+    json!({
+        "description": description,
+        "content": {
+            "application/problem+json": { "schema": { "$ref": "#/components/schemas/Problem" } },
+        },
+    })
+}
@@ -1,34 +1,267 @@
-use crate::AppState;
-use core_victorhqc_com::models::{photo::Photo, tag::Tag};
-use rocket::State;
-use rocket::http::Status;
-use rocket::serde::json::Json;
-
-#[get("/photos", format = "json")]
-pub async fn get_all_photos(state: &State<AppState>) -> (Status, Json<Vec<Photo>>) {
+use super::{
+    dto::{
+        Page, Photo,
+        page::{Cursor, page_limit, paginate},
+        photo::Error as LoadError,
+    },
+    problem::Problem,
+};
+use crate::{
+    AppState,
+    auth::{Authorized, WritePhotos},
+    graphql::response_cache::Entity,
+};
+use core_victorhqc_com::{
+    models::{
+        photo::{Photo as CorePhoto, db::Error as PhotoDbError},
+        tag::{Tag, db::Error as TagDbError},
+    },
+    sqlx::{Error as SqlxError, SqliteConnection},
+};
+use pipeline_victorhqc_com::{
+    exiftool::spawn::Error as ExiftoolError,
+    publish::{Answers, Error as PublishError, parse_tags, publish},
+    utils::{GetFujifilmError, is_valid_extension},
+};
+use rocket::{
+    Request, State,
+    form::Form,
+    fs::TempFile,
+    response::{Responder, status::Created},
+    serde::json::Json,
+    tokio::fs,
+};
+use snafu::prelude::*;
+use std::path::Path;
+use time::OffsetDateTime;
+use tracing::warn;
+
+/// Every photo, oldest first.
+#[get("/photos?<cursor>&<limit>")]
+pub async fn get_all_photos(
+    cursor: Option<&str>,
+    limit: Option<usize>,
+    state: &State<AppState>,
+) -> Result<Json<Page<Photo>>, Error> {
     let pool = &state.db_pool;
-    let mut conn = pool.try_acquire().unwrap();
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    let page = photo_page(&mut conn, None, cursor, limit).await?;
+
+    Ok(Json(page))
+}
+
+#[get("/photos/<id>")]
+pub async fn get_photo(id: &str, state: &State<AppState>) -> Result<Json<Photo>, Error> {
+    let pool = &state.db_pool;
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    let photo = CorePhoto::find_by_id(&mut conn, id)
+        .await
+        .context(PhotosSnafu)?;
+    let photo = Photo::load(&mut conn, vec![photo])
+        .await
+        .context(LoadSnafu)?
+        .pop()
+        .context(PhotoNotFoundSnafu)?;
+
+    Ok(Json(photo))
+}
+
+/// A new photo, sent as `multipart/form-data`.
+#[derive(FromForm)]
+pub struct Upload<'r> {
+    /// The original JPEG, its filename is kept.
+    photo: TempFile<'r>,
+    title: String,
+    /// Comma separated.
+    #[field(default = "")]
+    tags: String,
+    /// Name of the recipe when it is seen for the first time.
+    recipe: Option<String>,
+    #[field(default = true)]
+    watermark: bool,
+    /// Publish even when the photo looks like some already uploaded.
+    #[field(default = false)]
+    force: bool,
+}
 
-    let photos = Photo::find_all(&mut conn, None).await.unwrap();
+impl Answers for Upload<'_> {
+    fn title(&mut self) -> String {
+        self.title.clone()
+    }
 
-    (Status::Ok, Json(photos))
+    fn tags(&mut self) -> Vec<String> {
+        parse_tags(&self.tags)
+    }
+
+    fn recipe_name(&mut self) -> Option<String> {
+        self.recipe.clone()
+    }
+
+    fn upload_near_duplicate(&mut self, _: &[(u32, CorePhoto)]) -> bool {
+        self.force
+    }
 }
 
-#[get("/photos/<name>", format = "json")]
-pub async fn get_all_photos_by_tag(
-    name: &str,
+/// Runs the same pipeline as `cli create` on the uploaded original. Questions the CLI would ask
+/// are problems here: a 409 for a photo that looks like another one, to send again with `force`,
+/// and a 422 for an unknown recipe, to send again with its name.
+#[post("/photos", data = "<upload>")]
+pub async fn create_photo(
+    _auth: Authorized<WritePhotos>,
+    mut upload: Form<Upload<'_>>,
     state: &State<AppState>,
-) -> (Status, Json<Vec<Photo>>) {
+) -> Result<Created<Json<Photo>>, Error> {
+    let filename = upload
+        .photo
+        .raw_name()
+        .map(|name| name.dangerous_unsafe_unsanitized_raw().as_str())
+        // Only the last component, a name like `../photo.jpg` stays in the upload directory.
+        .and_then(|name| Path::new(name).file_name())
+        .and_then(|name| name.to_str())
+        .map(String::from)
+        .filter(|name| is_valid_extension(Path::new(name)))
+        .context(FilenameSnafu)?;
+
+    // The pipeline stores the name of the file it reads, so it has to be the original one.
+    let dir = std::env::temp_dir().join(format!(
+        "victorhqc-upload-{}",
+        OffsetDateTime::now_utc().unix_timestamp_nanos()
+    ));
+    fs::create_dir_all(&dir).await.context(SaveUploadSnafu)?;
+
+    let src = dir.join(&filename);
+    let result = match upload.photo.persist_to(&src).await {
+        Ok(_) => {
+            let s3 = &state.img_cache.s3;
+            let watermark = upload.watermark;
+
+            publish(&state.db_pool, &src, s3, watermark, &mut *upload)
+                .await
+                .context(PublishSnafu)
+        }
+        Err(e) => Err(e).context(SaveUploadSnafu),
+    };
+
+    if let Err(e) = fs::remove_dir_all(&dir).await {
+        warn!("Failed to remove {}: {}", dir.display(), e);
+    }
+
+    let photo = result?;
+
     let pool = &state.db_pool;
-    let mut conn = pool.try_acquire().unwrap();
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
 
-    let tag = Tag::find_by_name(&mut conn, name).await.unwrap();
-    let photos = Photo::find_by_tag_ids(&mut conn, &vec![tag.id], None, None)
+    // A new photo is in the lists of photos, of its tags and of its recipe, and in the gear stats. Its
+    // camera or lens may be new too.
+    let tags = Tag::find_by_photo_ids(&mut conn, &vec![photo.id.clone()])
         .await
-        .unwrap()
+        .context(TagsSnafu)?;
+    let mut changed: Vec<Entity> = tags
         .into_iter()
-        .map(|(_, photo)| photo)
+        .map(|(_, tag)| Entity::new("Tag", tag.id))
         .collect();
+    changed.extend([
+        Entity::kind("Photo"),
+        Entity::kind("FujifilmRecipe"),
+        Entity::kind("GearStats"),
+        Entity::kind("Camera"),
+        Entity::kind("Lens"),
+    ]);
+    state.response_cache.invalidate(&changed);
+
+    let photo = Photo::load(&mut conn, vec![photo])
+        .await
+        .context(LoadSnafu)?
+        .pop()
+        .context(PhotoNotFoundSnafu)?;
+
+    Ok(Created::new(format!("/v1/photos/{}", photo.id)).body(Json(photo)))
+}
+
+/// The page of photos after the cursor, of the tag when there's one, with their relations loaded.
+pub async fn photo_page(
+    conn: &mut SqliteConnection,
+    tag_id: Option<&str>,
+    cursor: Option<&str>,
+    limit: Option<usize>,
+) -> Result<Page<Photo>, Error> {
+    let after = match cursor {
+        Some(cursor) => Some(Cursor::decode(cursor).context(CursorSnafu)?),
+        None => None,
+    };
+    let limit = page_limit(limit);
+
+    let photos = CorePhoto::find_page(conn, tag_id, after.as_ref().map(Cursor::key), limit + 1)
+        .await
+        .context(PhotosSnafu)?;
+    let (photos, next_cursor) = paginate(photos, limit);
+    let items = Photo::load(conn, photos).await.context(LoadSnafu)?;
+
+    Ok(Page { items, next_cursor })
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to get connection: {}", source))]
+    Connection { source: SqlxError },
+
+    #[snafu(display("Failed to get the photos: {}", source))]
+    Photos { source: PhotoDbError },
+
+    #[snafu(display("The photo does not exist"))]
+    PhotoNotFound,
+
+    #[snafu(display("Invalid cursor, use the next_cursor of a previous page"))]
+    Cursor,
+
+    #[snafu(display("Failed to load the photos: {}", source))]
+    Load { source: LoadError },
+
+    #[snafu(display("Failed to get the tags: {}", source))]
+    Tags { source: TagDbError },
+
+    #[snafu(display("The photo must be a JPEG file with a .jpg or .jpeg name"))]
+    Filename,
+
+    #[snafu(display("Failed to save the upload: {}", source))]
+    SaveUpload { source: std::io::Error },
+
+    #[snafu(display("{}", source))]
+    Publish { source: PublishError },
+}
+
+impl From<&Error> for Problem {
+    fn from(error: &Error) -> Self {
+        match error {
+            Error::Connection { source } => Problem::from_connection(source),
+            Error::Photos {
+                source: PhotoDbError::Sqlx { source },
+            } => Problem::from_query(source, "The photo"),
+            Error::PhotoNotFound => Problem::NotFound(error.to_string()),
+            Error::Cursor | Error::Filename => Problem::BadRequest(error.to_string()),
+            Error::Publish { source } => match source {
+                PublishError::PhotoExists { .. } | PublishError::NearDuplicate { .. } => {
+                    Problem::Conflict(error.to_string())
+                }
+                PublishError::FujifilmRecipe {
+                    source: GetFujifilmError::RecipeName,
+                } => {
+                    Problem::Unprocessable("The recipe is new, send its name as recipe".to_string())
+                }
+                PublishError::Exiftool {
+                    source: ExiftoolError::Path { .. },
+                } => Problem::BadRequest(error.to_string()),
+                _ => Problem::Internal(error.to_string()),
+            },
+            _ => Problem::Internal(error.to_string()),
+        }
+    }
+}
 
-    (Status::Ok, Json(photos))
+impl<'r> Responder<'r, 'static> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> rocket::response::Result<'static> {
+        Problem::from(&self).respond_to(req)
+    }
 }
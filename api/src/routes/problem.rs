@@ -0,0 +1,164 @@
+use core_victorhqc_com::sqlx::Error as SqlxError;
+use rocket::{
+    Request, Response,
+    http::{ContentType, Header, Status},
+    response::Responder,
+    serde::json::serde_json,
+};
+use serde::Serialize;
+use std::io::Cursor;
+use tracing::error;
+
+/// What went wrong in a `/v1` request, sent as a problem details body (RFC 7807). Every route
+/// error turns into one of these, so clients only need to tell them apart by status.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    BadRequest(String),
+    /// No valid bearer token, sent with a `WWW-Authenticate` challenge.
+    Unauthorized(String),
+    /// The token lacks the scope the route needs.
+    Forbidden(String),
+    NotFound(String),
+    /// The resource already exists, or looks like one that does.
+    Conflict(String),
+    /// The request is well formed but something is missing to fulfil it.
+    Unprocessable(String),
+    /// The database can't take more connections right now, trying again later may work.
+    Unavailable(String),
+    /// The cause is logged, it isn't sent to the client.
+    Internal(String),
+    /// Any other status Rocket answered with before reaching a route, e.g. a body over the limits.
+    Other(Status),
+}
+
+impl Problem {
+    /// Unavailable when the pool is exhausted or closed, internal otherwise.
+    pub fn from_connection(source: &SqlxError) -> Problem {
+        match source {
+            SqlxError::PoolTimedOut | SqlxError::PoolClosed => {
+                Problem::Unavailable("The database is busy, try again later".to_string())
+            }
+            _ => Problem::Internal(source.to_string()),
+        }
+    }
+
+    /// Not found when the query returned nothing, internal otherwise.
+    pub fn from_query(source: &SqlxError, what: &str) -> Problem {
+        match source {
+            SqlxError::RowNotFound => Problem::NotFound(format!("{} does not exist", what)),
+            _ => Problem::Internal(source.to_string()),
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        match self {
+            Problem::BadRequest(_) => Status::BadRequest,
+            Problem::Unauthorized(_) => Status::Unauthorized,
+            Problem::Forbidden(_) => Status::Forbidden,
+            Problem::NotFound(_) => Status::NotFound,
+            Problem::Conflict(_) => Status::Conflict,
+            Problem::Unprocessable(_) => Status::UnprocessableEntity,
+            Problem::Unavailable(_) => Status::ServiceUnavailable,
+            Problem::Internal(_) => Status::InternalServerError,
+            Problem::Other(status) => *status,
+        }
+    }
+
+    fn detail(&self) -> Option<&str> {
+        match self {
+            Problem::BadRequest(detail)
+            | Problem::Unauthorized(detail)
+            | Problem::Forbidden(detail)
+            | Problem::NotFound(detail)
+            | Problem::Conflict(detail)
+            | Problem::Unprocessable(detail)
+            | Problem::Unavailable(detail) => Some(detail),
+            Problem::Internal(_) | Problem::Other(_) => None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Details<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    title: &'static str,
+    status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<&'a str>,
+    instance: String,
+}
+
+impl<'r> Responder<'r, 'static> for Problem {
+    fn respond_to(self, req: &'r Request<'_>) -> rocket::response::Result<'static> {
+        if let Problem::Internal(cause) = &self {
+            error!("{} failed: {}", req.uri(), cause);
+        }
+
+        let status = self.status();
+        let details = Details {
+            // No page documents the problems, the status says it all.
+            kind: "about:blank",
+            title: status.reason_lossy(),
+            status: status.code,
+            detail: self.detail(),
+            instance: req.uri().to_string(),
+        };
+        let serialized = serde_json::to_string(&details).unwrap();
+
+        let mut response = Response::build();
+        if let Problem::Unauthorized(_) = &self {
+            response.header(Header::new("WWW-Authenticate", "Bearer"));
+        }
+
+        response
+            .status(status)
+            .header(ContentType::new("application", "problem+json"))
+            .sized_body(serialized.len(), Cursor::new(serialized))
+            .ok()
+    }
+}
+
+/// The problem a request guard left for its failure, see [`crate::auth::Authorized`].
+fn cached(req: &Request<'_>) -> Option<Problem> {
+    req.local_cache(|| None::<Problem>).clone()
+}
+
+#[catch(401)]
+pub fn unauthorized(req: &Request<'_>) -> Problem {
+    cached(req).unwrap_or_else(|| Problem::Unauthorized("A bearer token is required".to_string()))
+}
+
+#[catch(403)]
+pub fn forbidden(req: &Request<'_>) -> Problem {
+    cached(req).unwrap_or_else(|| Problem::Forbidden("The token lacks the scope".to_string()))
+}
+
+#[catch(404)]
+pub fn not_found(req: &Request<'_>) -> Problem {
+    cached(req).unwrap_or_else(|| Problem::NotFound(format!("No route matches {}", req.uri())))
+}
+
+/// Rocket answers with a 422 when a body can't be parsed, e.g. invalid JSON.
+#[catch(422)]
+pub fn unprocessable(req: &Request<'_>) -> Problem {
+    cached(req)
+        .unwrap_or_else(|| Problem::Unprocessable("The request body is not valid".to_string()))
+}
+
+#[catch(500)]
+pub fn internal(req: &Request<'_>) -> Problem {
+    cached(req).unwrap_or_else(|| Problem::Internal("The request failed".to_string()))
+}
+
+#[catch(503)]
+pub fn unavailable(req: &Request<'_>) -> Problem {
+    cached(req).unwrap_or_else(|| {
+        Problem::Unavailable("The database is busy, try again later".to_string())
+    })
+}
+
+#[catch(default)]
+pub fn default(status: Status, req: &Request<'_>) -> Problem {
+    cached(req).unwrap_or(Problem::Other(status))
+}
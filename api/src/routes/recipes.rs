@@ -0,0 +1,152 @@
+use super::{dto::Recipe, problem::Problem};
+use crate::AppState;
+use crate::recipe_card::{Error as CardError, RecipeCard};
+use core_victorhqc_com::{
+    models::fujifilm::{FujifilmRecipe, db::Error as FujifilmDbError},
+    sqlx::Error as SqlxError,
+};
+use rocket::{
+    Request, Response, State,
+    http::{Header, Status},
+    response::Responder,
+    serde::json::Json,
+    tokio::task::{JoinError, spawn_blocking},
+};
+use snafu::prelude::*;
+use std::io::Cursor;
+use std::str::FromStr;
+use strum_macros::EnumString;
+
+#[get("/recipes")]
+pub async fn get_recipes(state: &State<AppState>) -> Result<Json<Vec<Recipe>>, Error> {
+    let pool = &state.db_pool;
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    let recipes = FujifilmRecipe::find_all(&mut conn)
+        .await
+        .context(RecipeSnafu)?;
+
+    Ok(Json(recipes.iter().map(Recipe::from).collect()))
+}
+
+#[get("/recipes/<id>")]
+pub async fn get_recipe(id: &str, state: &State<AppState>) -> Result<Json<Recipe>, Error> {
+    let pool = &state.db_pool;
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    let recipe = FujifilmRecipe::find_by_id(&mut conn, id)
+        .await
+        .context(RecipeSnafu)?
+        .context(NotFoundSnafu { id })?;
+
+    Ok(Json(Recipe::from(&recipe)))
+}
+
+#[get("/recipes/<id>/card?<kind>")]
+pub async fn get_recipe_card(
+    id: &str,
+    kind: Option<&str>,
+    state: &State<AppState>,
+) -> Result<CardResponse, Error> {
+    let pool = &state.db_pool;
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    let kind = kind.map_or("png", |k| k);
+    let card_kind = CardKind::from_str(kind).context(KindSnafu {
+        kind: kind.to_string(),
+    })?;
+
+    let recipe = FujifilmRecipe::find_by_id(&mut conn, id)
+        .await
+        .context(RecipeSnafu)?
+        .context(NotFoundSnafu { id })?;
+
+    let card = RecipeCard::new(&recipe).context(CardSnafu)?;
+
+    let data = match card_kind {
+        CardKind::Png => spawn_blocking(move || card.png())
+            .await
+            .context(RenderSnafu)?
+            .context(CardSnafu)?,
+        CardKind::Svg => card.svg().as_bytes().to_vec(),
+    };
+
+    Ok(CardResponse {
+        data,
+        kind: card_kind,
+    })
+}
+
+#[derive(Debug, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum CardKind {
+    #[strum(serialize = "png")]
+    Png,
+    #[strum(serialize = "svg")]
+    Svg,
+}
+
+pub struct CardResponse {
+    data: Vec<u8>,
+    kind: CardKind,
+}
+
+impl<'r> Responder<'r, 'static> for CardResponse {
+    fn respond_to(self, _: &'r Request<'_>) -> Result<Response<'static>, Status> {
+        let content_type = match self.kind {
+            CardKind::Png => "image/png",
+            CardKind::Svg => "image/svg+xml",
+        };
+
+        // Recipes can be renamed, so the card is not cached forever like the photos.
+        Response::build()
+            .header(Header::new("Content-Type", content_type))
+            .header(Header::new("Cache-Control", "public, max-age=86400"))
+            .sized_body(self.data.len(), Cursor::new(self.data))
+            .ok()
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Invalid type '{}': {}", kind, source))]
+    Kind {
+        kind: String,
+        source: strum::ParseError,
+    },
+
+    #[snafu(display("Failed to get connection: {}", source))]
+    Connection { source: SqlxError },
+
+    #[snafu(display("Failed to get the recipes: {}", source))]
+    Recipe { source: FujifilmDbError },
+
+    #[snafu(display("Recipe {} does not exist", id))]
+    NotFound { id: String },
+
+    #[snafu(display("Failed to render recipe card: {}", source))]
+    Card { source: CardError },
+
+    #[snafu(display("The recipe card render stopped: {}", source))]
+    Render { source: JoinError },
+}
+
+impl From<&Error> for Problem {
+    fn from(error: &Error) -> Self {
+        match error {
+            Error::Kind { .. } => Problem::BadRequest(error.to_string()),
+            Error::NotFound { .. } => Problem::NotFound(error.to_string()),
+            Error::Connection { source } => Problem::from_connection(source),
+            Error::Recipe {
+                source: FujifilmDbError::Sqlx { source },
+            } => Problem::from_query(source, "The recipe"),
+            _ => Problem::Internal(error.to_string()),
+        }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> rocket::response::Result<'static> {
+        Problem::from(&self).respond_to(req)
+    }
+}
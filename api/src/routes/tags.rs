@@ -0,0 +1,134 @@
+use super::{
+    dto::{Collection, Page, Photo, Tag},
+    photos::{Error as PhotosError, photo_page},
+    problem::Problem,
+};
+use crate::AppState;
+use core_victorhqc_com::{
+    models::{
+        photo::{Photo as CorePhoto, db::Error as PhotoDbError},
+        tag::{Tag as CoreTag, db::Error as TagDbError},
+    },
+    sqlx::{Error as SqlxError, SqliteConnection},
+};
+use rocket::{Request, State, response::Responder, serde::json::Json};
+use snafu::prelude::*;
+
+#[get("/tags")]
+pub async fn get_tags(state: &State<AppState>) -> Result<Json<Vec<Tag>>, Error> {
+    let pool = &state.db_pool;
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    let tags = CoreTag::find_all(&mut conn).await.context(TagSnafu)?;
+
+    Ok(Json(tags.into_iter().map(Tag::from).collect()))
+}
+
+/// Photos with the tag, oldest first.
+#[get("/tags/<name>/photos?<cursor>&<limit>")]
+pub async fn get_tag_photos(
+    name: &str,
+    cursor: Option<&str>,
+    limit: Option<usize>,
+    state: &State<AppState>,
+) -> Result<Json<Page<Photo>>, Error> {
+    let pool = &state.db_pool;
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    let tag = CoreTag::find_by_name(&mut conn, name)
+        .await
+        .context(TagSnafu)?;
+    let page = photo_page(&mut conn, Some(&tag.id), cursor, limit)
+        .await
+        .context(PhotosSnafu)?;
+
+    Ok(Json(page))
+}
+
+/// The galleries of the site, in the order they are configured.
+#[get("/collections")]
+pub async fn get_collections(state: &State<AppState>) -> Result<Json<Vec<Collection>>, Error> {
+    let pool = &state.db_pool;
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    let mut collections = Vec::new();
+    for name in &state.collections {
+        // A collection without photos yet has no tag either.
+        let count = match tagged_photos(&mut conn, name).await {
+            Ok(photos) => photos.len(),
+            Err(Error::Tag {
+                source: TagDbError::TagsNotFound { .. },
+            }) => 0,
+            Err(e) => return Err(e),
+        };
+
+        collections.push(Collection::new(name.clone(), count));
+    }
+
+    Ok(Json(collections))
+}
+
+#[get("/collections/<name>/photos?<cursor>&<limit>")]
+pub async fn get_collection_photos(
+    name: &str,
+    cursor: Option<&str>,
+    limit: Option<usize>,
+    state: &State<AppState>,
+) -> Result<Json<Page<Photo>>, Error> {
+    ensure!(
+        state.collections.iter().any(|c| c == name),
+        CollectionNotFoundSnafu { name }
+    );
+
+    get_tag_photos(name, cursor, limit, state).await
+}
+
+async fn tagged_photos(conn: &mut SqliteConnection, name: &str) -> Result<Vec<CorePhoto>, Error> {
+    let tag = CoreTag::find_by_name(conn, name).await.context(TagSnafu)?;
+    let photos = CorePhoto::find_by_tag_ids(conn, &vec![tag.id], None, None)
+        .await
+        .context(TaggedPhotosSnafu)?
+        .into_iter()
+        .map(|(_, photo)| photo)
+        .collect();
+
+    Ok(photos)
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to get connection: {}", source))]
+    Connection { source: SqlxError },
+
+    #[snafu(display("Failed to get the tag: {}", source))]
+    Tag { source: TagDbError },
+
+    #[snafu(display("Failed to get the photos of the tag: {}", source))]
+    TaggedPhotos { source: PhotoDbError },
+
+    #[snafu(display("Collection {} does not exist", name))]
+    CollectionNotFound { name: String },
+
+    #[snafu(display("{}", source))]
+    Photos { source: PhotosError },
+}
+
+impl From<&Error> for Problem {
+    fn from(error: &Error) -> Self {
+        match error {
+            Error::Connection { source } => Problem::from_connection(source),
+            Error::Tag {
+                source: TagDbError::TagsNotFound { name },
+            } => Problem::NotFound(format!("Tag {} does not exist", name)),
+            Error::CollectionNotFound { .. } => Problem::NotFound(error.to_string()),
+            Error::Photos { source } => Problem::from(source),
+            _ => Problem::Internal(error.to_string()),
+        }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> rocket::response::Result<'static> {
+        Problem::from(&self).respond_to(req)
+    }
+}
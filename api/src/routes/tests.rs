@@ -0,0 +1,601 @@
+use crate::{
+    AppState,
+    cache::image_cache::ImageCache,
+    graphql::{
+        context::Context,
+        graph,
+        loaders::AppLoader,
+        response_cache::ResponseCache,
+        routes::{graphql_query, graphql_request},
+    },
+    metrics::RequestMetrics,
+    request_id::{RequestIds, traced},
+};
+use async_graphql::dataloader::DataLoader;
+use core_victorhqc_com::{
+    aws::{
+        S3,
+        image_size::{ImageSize, ImageType},
+    },
+    db::migrate,
+    models::{
+        api_token::{ApiToken, Scope},
+        photo::{Orientation, Photo},
+        photo_rendition::PhotoRendition,
+    },
+    sqlx::sqlite::{SqlitePool, SqlitePoolOptions},
+};
+use rocket::{
+    http::{Accept, ContentType, Header, Status},
+    local::asynchronous::{Client, LocalResponse},
+    serde::json::{Value, json},
+    tokio::spawn,
+};
+use std::{
+    path::Path,
+    str::FromStr,
+    sync::{Arc, atomic::AtomicBool},
+};
+use time::{Duration, OffsetDateTime};
+
+/// A client over an empty, migrated, in-memory database, with the `/v1` and GraphQL routes. S3 is
+/// never reached, every request here fails before downloading anything.
+async fn client() -> (Client, SqlitePool) {
+    // A single connection that never expires, every new connection would get its own database.
+    let db_pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    migrate(&db_pool).await.unwrap();
+
+    let state = AppState {
+        db_pool: db_pool.clone(),
+        img_cache: ImageCache::default(S3::new("test").await),
+        collections: vec!["portfolio".to_string()],
+        response_cache: ResponseCache::default(),
+        bootstrapped: Arc::new(AtomicBool::new(true)),
+    };
+    let schema = graph::build(false)
+        .data(Context::default(db_pool.clone()))
+        .data(DataLoader::new(AppLoader::default(db_pool.clone()), spawn))
+        .finish();
+    let rocket = rocket::build()
+        .manage(schema)
+        .manage(state)
+        .attach(RequestIds)
+        .attach(RequestMetrics)
+        .mount("/", traced(routes![graphql_query, graphql_request]))
+        .mount("/", traced(super::health()))
+        .mount("/v1/", traced(super::v1()))
+        .register("/v1/", super::v1_catchers());
+
+    (Client::tracked(rocket).await.unwrap(), db_pool)
+}
+
+async fn problem(response: LocalResponse<'_>, status: Status) -> Value {
+    assert_eq!(response.status(), status);
+    assert_eq!(
+        response.content_type(),
+        Some(ContentType::new("application", "problem+json"))
+    );
+
+    let body: Value = response.into_json().await.unwrap();
+    assert_eq!(body["status"], status.code);
+    assert_eq!(body["type"], "about:blank");
+
+    body
+}
+
+#[rocket::async_test]
+async fn it_lists_photos() {
+    let (client, _) = client().await;
+
+    let response = client
+        .get("/v1/photos")
+        .header(Accept::JSON)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.into_json::<Value>().await,
+        Some(json!({ "items": [], "next_cursor": null }))
+    );
+}
+
+#[rocket::async_test]
+async fn it_walks_every_page_of_photos() {
+    let (client, db_pool) = client().await;
+    let mut conn = db_pool.acquire().await.unwrap();
+    for i in 0..3 {
+        let path = format!("photo-{}.jpg", i);
+        let photo = Photo::new(
+            format!("Photo {}", i),
+            Path::new(&path),
+            Orientation::Landscape,
+        );
+        photo.unwrap().save(&mut conn).await.unwrap();
+    }
+    drop(conn);
+
+    let response = client.get("/v1/photos?limit=2").dispatch().await;
+    let first: Value = response.into_json().await.unwrap();
+    assert_eq!(first["items"].as_array().unwrap().len(), 2);
+    let year = OffsetDateTime::now_utc().year().to_string();
+    assert!(
+        first["items"][0]["created_at"]
+            .as_str()
+            .unwrap()
+            .starts_with(&year)
+    );
+
+    let cursor = first["next_cursor"].as_str().unwrap();
+    let response = client
+        .get(format!("/v1/photos?limit=2&cursor={}", cursor))
+        .dispatch()
+        .await;
+    let last: Value = response.into_json().await.unwrap();
+    assert_eq!(last["items"].as_array().unwrap().len(), 1);
+    assert_eq!(last["next_cursor"], Value::Null);
+    assert_ne!(last["items"][0]["id"], first["items"][1]["id"]);
+}
+
+#[rocket::async_test]
+async fn it_is_a_bad_request_for_invalid_cursors() {
+    let (client, _) = client().await;
+
+    let response = client.get("/v1/photos?cursor=nope").dispatch().await;
+    problem(response, Status::BadRequest).await;
+}
+
+#[rocket::async_test]
+async fn it_is_not_found_for_unknown_photo_ids() {
+    let (client, _) = client().await;
+
+    let response = client.get("/v1/photos/unknown").dispatch().await;
+    problem(response, Status::NotFound).await;
+}
+
+#[rocket::async_test]
+async fn it_is_not_found_for_unknown_routes() {
+    let (client, _) = client().await;
+
+    let response = client.get("/v1/nope").dispatch().await;
+    let body = problem(response, Status::NotFound).await;
+
+    assert_eq!(body["detail"], "No route matches /v1/nope");
+}
+
+#[rocket::async_test]
+async fn it_is_not_found_for_unknown_tags() {
+    let (client, _) = client().await;
+
+    let response = client.get("/v1/tags/unknown/photos").dispatch().await;
+    let body = problem(response, Status::NotFound).await;
+
+    assert_eq!(body["detail"], "Tag unknown does not exist");
+    assert_eq!(body["instance"], "/v1/tags/unknown/photos");
+}
+
+#[rocket::async_test]
+async fn it_lists_tags_and_collections() {
+    let (client, _) = client().await;
+
+    let response = client.get("/v1/tags").dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_json::<Value>().await, Some(json!([])));
+
+    // The configured collections are listed even before they have photos.
+    let response = client.get("/v1/collections").dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let body: Value = response.into_json().await.unwrap();
+    assert_eq!(body[0]["name"], "portfolio");
+    assert_eq!(body[0]["photo_count"], 0);
+
+    let response = client
+        .get("/v1/collections/unknown/photos")
+        .dispatch()
+        .await;
+    let body = problem(response, Status::NotFound).await;
+    assert_eq!(body["detail"], "Collection unknown does not exist");
+}
+
+#[rocket::async_test]
+async fn it_documents_every_route() {
+    let (client, _) = client().await;
+
+    let response = client.get("/v1/openapi.json").dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let body: Value = response.into_json().await.unwrap();
+    assert_eq!(body["openapi"], "3.1.0");
+
+    let paths = &body["paths"];
+    for route in super::v1() {
+        let path = route.uri.path().replace('<', "{").replace('>', "}");
+        let method = route.method.as_str().to_lowercase();
+
+        assert!(
+            paths["/v1".to_string() + &path][&method].is_object(),
+            "{} {} is not documented",
+            method,
+            path
+        );
+    }
+
+    let schemas = &body["components"]["schemas"];
+    for schema in [
+        "Photo",
+        "PhotoPage",
+        "Exif",
+        "Tag",
+        "Collection",
+        "Recipe",
+        "Problem",
+    ] {
+        assert!(schemas[schema].is_object(), "{} is missing", schema);
+    }
+}
+
+#[rocket::async_test]
+async fn it_is_unavailable_without_connections() {
+    let (client, db_pool) = client().await;
+    db_pool.close().await;
+
+    let response = client
+        .get("/v1/photos")
+        .header(Accept::JSON)
+        .dispatch()
+        .await;
+    problem(response, Status::ServiceUnavailable).await;
+
+    let response = client.get("/v1/images/Md/unknown").dispatch().await;
+    problem(response, Status::ServiceUnavailable).await;
+}
+
+#[rocket::async_test]
+async fn it_is_not_found_for_unknown_photos() {
+    let (client, _) = client().await;
+
+    let response = client.get("/v1/images/Md/unknown").dispatch().await;
+    problem(response, Status::NotFound).await;
+
+    let response = client.head("/v1/images/Md/unknown").dispatch().await;
+    assert_eq!(response.status(), Status::NotFound);
+}
+
+#[rocket::async_test]
+async fn it_sends_when_photos_were_last_modified() {
+    let (client, db_pool) = client().await;
+    let mut conn = db_pool.acquire().await.unwrap();
+    let photo = Photo::new(
+        "Photo".to_string(),
+        Path::new("photo.jpg"),
+        Orientation::Landscape,
+    )
+    .unwrap();
+    photo.save(&mut conn).await.unwrap();
+    let rendition = PhotoRendition {
+        photo_id: photo.id.clone(),
+        size: ImageSize::from_str("Md").unwrap(),
+        kind: ImageType::Webp,
+        quality: 80,
+        bytes: 100,
+        ssim: None,
+        width: None,
+        height: None,
+        md5: Some("abc".to_string()),
+    };
+    rendition.save(&mut conn).await.unwrap();
+    drop(conn);
+
+    let response = client
+        .head(format!("/v1/images/Md/{}", photo.id))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    let last_modified = response.headers().get_one("Last-Modified").unwrap();
+    let year = OffsetDateTime::now_utc().year().to_string();
+    assert!(last_modified.contains(&year), "{}", last_modified);
+}
+
+#[rocket::async_test]
+async fn it_is_a_bad_request_for_unknown_sizes_and_types() {
+    let (client, _) = client().await;
+
+    let response = client.get("/v1/images/xl/unknown").dispatch().await;
+    problem(response, Status::BadRequest).await;
+
+    let response = client
+        .get("/v1/images/Md/unknown?kind=gif")
+        .dispatch()
+        .await;
+    problem(response, Status::BadRequest).await;
+}
+
+#[rocket::async_test]
+async fn it_never_serves_originals() {
+    let (client, _) = client().await;
+
+    let response = client.get("/v1/images/Original/unknown").dispatch().await;
+    problem(response, Status::NotFound).await;
+}
+
+#[rocket::async_test]
+async fn it_is_not_found_for_unknown_recipes() {
+    let (client, _) = client().await;
+
+    let response = client.get("/v1/recipes/unknown").dispatch().await;
+    let body = problem(response, Status::NotFound).await;
+
+    assert_eq!(body["detail"], "Recipe unknown does not exist");
+}
+
+#[rocket::async_test]
+async fn it_handles_recipe_card_errors() {
+    let (client, _) = client().await;
+
+    let response = client.get("/v1/recipes/unknown/card").dispatch().await;
+    problem(response, Status::NotFound).await;
+
+    let response = client
+        .get("/v1/recipes/unknown/card?kind=gif")
+        .dispatch()
+        .await;
+    problem(response, Status::BadRequest).await;
+}
+
+#[rocket::async_test]
+async fn it_asks_for_a_token() {
+    let (client, _) = client().await;
+
+    let response = client.get("/v1/token").dispatch().await;
+    assert_eq!(
+        response.headers().get_one("WWW-Authenticate"),
+        Some("Bearer")
+    );
+    let body = problem(response, Status::Unauthorized).await;
+    assert_eq!(body["detail"], "A bearer token is required");
+
+    let response = client
+        .get("/v1/token")
+        .header(Header::new("Authorization", "Basic abc"))
+        .dispatch()
+        .await;
+    problem(response, Status::Unauthorized).await;
+
+    let response = client
+        .get("/v1/token")
+        .header(Header::new("Authorization", "Bearer vhqc_unknown"))
+        .dispatch()
+        .await;
+    problem(response, Status::Unauthorized).await;
+}
+
+#[rocket::async_test]
+async fn it_accepts_valid_tokens_only() {
+    let (client, db_pool) = client().await;
+    let mut conn = db_pool.acquire().await.unwrap();
+
+    let (token, secret) = ApiToken::generate("ci".to_string(), vec![Scope::WritePhotos], None);
+    token.save(&mut conn).await.unwrap();
+
+    let yesterday = OffsetDateTime::now_utc() - Duration::days(1);
+    let (expired, expired_secret) =
+        ApiToken::generate("old".to_string(), vec![Scope::Admin], Some(yesterday));
+    expired.save(&mut conn).await.unwrap();
+    drop(conn);
+
+    let response = client
+        .get("/v1/token")
+        .header(Header::new("Authorization", format!("Bearer {}", secret)))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let body: Value = response.into_json().await.unwrap();
+    assert_eq!(body["name"], "ci");
+    assert_eq!(body["scopes"], json!(["write:photos"]));
+    assert_eq!(body["expires_at"], Value::Null);
+
+    let response = client
+        .get("/v1/token")
+        .header(Header::new(
+            "Authorization",
+            format!("Bearer {}", expired_secret),
+        ))
+        .dispatch()
+        .await;
+    let body = problem(response, Status::Unauthorized).await;
+    assert_eq!(body["detail"], "The token expired");
+}
+
+/// A `multipart/form-data` upload of a file named `filename` with a title.
+fn upload(filename: &str) -> (ContentType, String) {
+    let body = [
+        "--BOUNDARY",
+        &format!(
+            "Content-Disposition: form-data; name=\"photo\"; filename=\"{}\"",
+            filename
+        ),
+        "Content-Type: application/octet-stream",
+        "",
+        "not a photo",
+        "--BOUNDARY",
+        "Content-Disposition: form-data; name=\"title\"",
+        "",
+        "Sunset",
+        "--BOUNDARY--",
+        "",
+    ]
+    .join("\r\n");
+
+    let content_type =
+        ContentType::new("multipart", "form-data").with_params(("boundary", "BOUNDARY"));
+
+    (content_type, body)
+}
+
+#[rocket::async_test]
+async fn it_needs_a_write_token_to_upload_photos() {
+    let (client, db_pool) = client().await;
+    let mut conn = db_pool.acquire().await.unwrap();
+
+    let (reader, reader_secret) = ApiToken::generate("reader".to_string(), vec![Scope::Read], None);
+    reader.save(&mut conn).await.unwrap();
+
+    let (writer, writer_secret) =
+        ApiToken::generate("writer".to_string(), vec![Scope::WritePhotos], None);
+    writer.save(&mut conn).await.unwrap();
+    drop(conn);
+
+    let (content_type, body) = upload("photo.jpg");
+    let response = client
+        .post("/v1/photos")
+        .header(content_type.clone())
+        .body(&body)
+        .dispatch()
+        .await;
+    problem(response, Status::Unauthorized).await;
+
+    let response = client
+        .post("/v1/photos")
+        .header(content_type)
+        .header(Header::new(
+            "Authorization",
+            format!("Bearer {}", reader_secret),
+        ))
+        .body(&body)
+        .dispatch()
+        .await;
+    let body = problem(response, Status::Forbidden).await;
+    assert_eq!(body["detail"], "The token lacks the write:photos scope");
+
+    // Only JPEGs go through the pipeline.
+    let (content_type, body) = upload("notes.txt");
+    let response = client
+        .post("/v1/photos")
+        .header(content_type)
+        .header(Header::new(
+            "Authorization",
+            format!("Bearer {}", writer_secret),
+        ))
+        .body(&body)
+        .dispatch()
+        .await;
+    problem(response, Status::BadRequest).await;
+}
+
+#[rocket::async_test]
+async fn it_caches_graphql_queries() {
+    let (client, _) = client().await;
+    let uri = "/graphql?query=%7B%20cameras%20%7B%20id%20%7D%20%7D";
+
+    let response = client.get(uri).dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.headers().get_one("Cache-Control"),
+        Some("public, max-age=60")
+    );
+    let etag = response.headers().get_one("ETag").unwrap().to_string();
+    assert_eq!(
+        response.into_json::<Value>().await,
+        Some(json!({ "data": { "cameras": [] } }))
+    );
+
+    let response = client
+        .get(uri)
+        .header(Header::new("If-None-Match", etag))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::NotModified);
+}
+
+#[rocket::async_test]
+async fn it_never_caches_graphql_errors() {
+    let (client, _) = client().await;
+
+    let response = client
+        .get("/graphql?query=%7B%20token%20%7B%20id%20%7D%20%7D")
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response.headers().get_one("ETag").is_none());
+    assert!(response.headers().get_one("Cache-Control").is_none());
+}
+
+#[rocket::async_test]
+async fn it_is_healthy() {
+    let (client, _) = client().await;
+
+    let response = client.get("/healthz").dispatch().await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_string().await.unwrap(), "ok");
+}
+
+#[rocket::async_test]
+async fn it_is_not_ready_without_storage() {
+    let (client, _) = client().await;
+
+    let response = client.get("/readyz").dispatch().await;
+    assert_eq!(response.status(), Status::ServiceUnavailable);
+
+    let body: Value = response.into_json().await.unwrap();
+    assert_eq!(body["ready"], false);
+    assert_eq!(body["checks"]["database"], true);
+    assert_eq!(body["checks"]["storage"], false);
+    assert_eq!(body["checks"]["bootstrap"], true);
+}
+
+#[rocket::async_test]
+async fn it_serves_prometheus_metrics() {
+    let (client, db_pool) = client().await;
+    let mut conn = db_pool.acquire().await.unwrap();
+    let (token, secret) = ApiToken::generate("prometheus".to_string(), vec![Scope::Admin], None);
+    token.save(&mut conn).await.unwrap();
+    drop(conn);
+
+    client.get("/healthz").dispatch().await;
+    let response = client.get("/metrics").dispatch().await;
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let response = client
+        .get("/metrics")
+        .header(Header::new("Authorization", format!("Bearer {}", secret)))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let body = response.into_string().await.unwrap();
+    assert!(body.contains("# TYPE http_requests_total counter"));
+    assert!(body.contains("http_requests_total{method=\"GET\",route=\"/healthz\",status=\"200\"}"));
+    assert!(body.contains("db_pool_max_connections 1"));
+}
+
+#[rocket::async_test]
+async fn it_sends_back_request_ids() {
+    let (client, _) = client().await;
+
+    let response = client
+        .get("/healthz")
+        .header(Header::new("X-Request-Id", "from-web"))
+        .dispatch()
+        .await;
+    assert_eq!(response.headers().get_one("X-Request-Id"), Some("from-web"));
+
+    let response = client
+        .get("/healthz")
+        .header(Header::new("X-Request-Id", "not valid"))
+        .dispatch()
+        .await;
+    let id = response.headers().get_one("X-Request-Id").unwrap();
+    assert_ne!(id, "not valid");
+    assert!(!id.is_empty());
+}
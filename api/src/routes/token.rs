@@ -0,0 +1,9 @@
+use super::dto::Token;
+use crate::auth::{Authorized, Read};
+use rocket::serde::json::Json;
+
+/// The token the request was made with, to check it before using it elsewhere.
+#[get("/token")]
+pub fn get_token(auth: Authorized<Read>) -> Json<Token> {
+    Json(Token::from(&auth.token))
+}
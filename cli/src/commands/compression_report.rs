@@ -0,0 +1,130 @@
+use core_victorhqc_com::{
+    aws::image_size::{ImageSize, ImageType},
+    models::{
+        photo::{Photo, db::Error as PhotoDbError},
+        photo_rendition::{PhotoRendition, db::Error as PhotoRenditionDbError},
+    },
+    sqlx::{SqlitePool, error::Error as SqlxError},
+};
+use snafu::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Summarises how every size and type of the library was encoded: how many files there are, how
+/// much they weigh, and the quality and SSIM they were encoded with. The files with the lowest
+/// SSIM are listed afterwards, as those are the first ones to check by eye. Only the files built
+/// with a target SSIM or by `rebuild --measure` have one.
+pub async fn compression_report(pool: &SqlitePool, worst: usize) -> Result<(), Error> {
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    let photos = Photo::find_all(&mut conn, None)
+        .await
+        .context(PhotosSnafu)?;
+    let renditions = PhotoRendition::find_all(&mut conn)
+        .await
+        .context(RenditionsSnafu)?;
+
+    let photos: HashMap<&str, &Photo> = photos.iter().map(|p| (p.id.as_str(), p)).collect();
+
+    println!(
+        "{:<8} {:<5} {:>6} {:>10} {:>10} {:>8} {:>8} {:>9}",
+        "Size", "Type", "Files", "Avg KB", "Total MB", "Quality", "SSIM", "Min SSIM"
+    );
+
+    for size in ImageSize::renditions() {
+        for kind in [ImageType::Jpeg, ImageType::Webp] {
+            let group: Vec<&PhotoRendition> = renditions
+                .iter()
+                .filter(|r| r.size == size && r.kind == kind)
+                .collect();
+
+            if group.is_empty() {
+                continue;
+            }
+
+            let count = group.len() as f64;
+            let total = group.iter().map(|r| r.bytes).sum::<i64>() as f64;
+            let quality = group.iter().map(|r| r.quality as f64).sum::<f64>() / count;
+            let scores: Vec<f64> = group.iter().filter_map(|r| r.ssim).collect();
+            let (ssim, min_ssim) = if scores.is_empty() {
+                ("-".to_string(), "-".to_string())
+            } else {
+                let ssim = scores.iter().sum::<f64>() / scores.len() as f64;
+                let min_ssim = scores.iter().copied().fold(f64::INFINITY, f64::min);
+
+                (format!("{:.4}", ssim), format!("{:.4}", min_ssim))
+            };
+
+            println!(
+                "{:<8} {:<5} {:>6} {:>10.1} {:>10.2} {:>8.1} {:>8} {:>9}",
+                size.to_string(),
+                kind.to_string(),
+                group.len(),
+                total / count / 1024.0,
+                total / 1024.0 / 1024.0,
+                quality,
+                ssim,
+                min_ssim
+            );
+        }
+    }
+
+    let measured: HashSet<&str> = renditions.iter().map(|r| r.photo_id.as_str()).collect();
+    let missing = photos.keys().filter(|id| !measured.contains(*id)).count();
+    if missing > 0 {
+        println!(
+            "\n{} photos have no measurements, `rebuild --all` adds them",
+            missing
+        );
+    }
+
+    let mut lowest: Vec<(f64, &PhotoRendition)> = renditions
+        .iter()
+        .filter_map(|r| r.ssim.map(|ssim| (ssim, r)))
+        .collect();
+
+    let unmeasured = renditions.len() - lowest.len();
+    if unmeasured > 0 {
+        println!(
+            "\n{} files have no SSIM, `rebuild --all --measure` adds it",
+            unmeasured
+        );
+    }
+
+    if worst == 0 || lowest.is_empty() {
+        return Ok(());
+    }
+
+    lowest.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    println!("\nLowest SSIM");
+    for (ssim, rendition) in lowest.into_iter().take(worst) {
+        let filename = photos
+            .get(rendition.photo_id.as_str())
+            .map_or("", |p| p.filename.as_str());
+
+        println!(
+            "{:.4}  {:<8} {:<5} q{:<3} {:>8.1} KB  {} ({})",
+            ssim,
+            rendition.size.to_string(),
+            rendition.kind.to_string(),
+            rendition.quality,
+            rendition.bytes as f64 / 1024.0,
+            rendition.photo_id,
+            filename
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to connect to db: {}", source))]
+    Connection { source: SqlxError },
+
+    #[snafu(display("Failed to get the photos: {}", source))]
+    Photos { source: PhotoDbError },
+
+    #[snafu(display("Failed to get the renditions: {}", source))]
+    Renditions { source: PhotoRenditionDbError },
+}
@@ -0,0 +1,125 @@
+use crate::{commands::create::Prompt, utils::capture};
+use console::Emoji;
+use pipeline_victorhqc_com::{publish::Answers, utils::is_valid_extension};
+use reqwest::{
+    Client, StatusCode,
+    multipart::{Form, Part},
+};
+use serde_json::Value;
+use snafu::prelude::*;
+use std::path::Path;
+
+#[cfg(target_os = "windows")]
+static ROCKET: Emoji<'_, '_> = Emoji("🚀", "");
+#[cfg(not(target_os = "windows"))]
+static ROCKET: Emoji<'_, '_> = Emoji("🚀 ", "");
+#[cfg(target_os = "windows")]
+static WARNING: Emoji<'_, '_> = Emoji("⚠️", "");
+#[cfg(not(target_os = "windows"))]
+static WARNING: Emoji<'_, '_> = Emoji("⚠️  ", "");
+
+/// Publishes a photo through the API at `remote`, which runs the whole pipeline and stores the
+/// photo in its own database. The token comes from `API_TOKEN` and needs the `write:photos`
+/// scope.
+pub async fn create_remote(src: &Path, remote: &str, watermark: bool) -> Result<(), Error> {
+    ensure!(
+        is_valid_extension(src),
+        PathSnafu {
+            path: src.display().to_string()
+        }
+    );
+
+    let token = std::env::var("API_TOKEN").ok().context(TokenSnafu)?;
+    let filename = src
+        .file_name()
+        .and_then(|f| f.to_str())
+        .context(PathSnafu {
+            path: src.display().to_string(),
+        })?
+        .to_string();
+    let original = std::fs::read(src).context(ReadOriginalSnafu)?;
+
+    let url = format!("{}/v1/photos", remote.trim_end_matches('/'));
+    let client = Client::new();
+
+    let mut prompt = Prompt;
+    let title = prompt.title();
+    let tags = prompt.tags().join(",");
+    let mut recipe: Option<String> = None;
+    let mut force = false;
+
+    // The server can't ask, it answers with a problem instead and the upload is sent again.
+    loop {
+        let photo = Part::bytes(original.clone())
+            .file_name(filename.clone())
+            .mime_str("image/jpeg")
+            .context(RequestSnafu)?;
+
+        let mut form = Form::new()
+            .part("photo", photo)
+            .text("title", title.clone())
+            .text("tags", tags.clone())
+            .text("watermark", watermark.to_string())
+            .text("force", force.to_string());
+        if let Some(recipe) = &recipe {
+            form = form.text("recipe", recipe.clone());
+        }
+
+        let response = client
+            .post(&url)
+            .bearer_auth(&token)
+            .multipart(form)
+            .send()
+            .await
+            .context(RequestSnafu)?;
+
+        let status = response.status();
+        // Errors are problem details, anything else, e.g. a proxy page, only has its status.
+        let body: Value = response.json().await.unwrap_or_default();
+        let detail = body["detail"].as_str().unwrap_or_default().to_string();
+
+        match status {
+            StatusCode::CREATED => {
+                println!(
+                    "{} Published {}",
+                    ROCKET,
+                    body["id"].as_str().unwrap_or_default()
+                );
+
+                return Ok(());
+            }
+            StatusCode::CONFLICT if !force => {
+                println!("{} {}", WARNING, detail);
+                let answer = capture("Upload it anyway? [y/N] ");
+                ensure!(answer.eq_ignore_ascii_case("y"), CancelledSnafu);
+
+                force = true;
+            }
+            StatusCode::UNPROCESSABLE_ENTITY if recipe.is_none() => {
+                recipe = Some(prompt.recipe_name().context(CancelledSnafu)?);
+            }
+            _ => return RejectedSnafu { status, detail }.fail(),
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Invalid File: {}", path))]
+    Path { path: String },
+
+    #[snafu(display("API_TOKEN env variable is missing"))]
+    Token,
+
+    #[snafu(display("Failed to read the original file: {}", source))]
+    ReadOriginal { source: std::io::Error },
+
+    #[snafu(display("Failed to reach the API: {}", source))]
+    Request { source: reqwest::Error },
+
+    #[snafu(display("The API rejected the photo, {}: {}", status, detail))]
+    Rejected { status: StatusCode, detail: String },
+
+    #[snafu(display("Cancelled"))]
+    Cancelled,
+}
@@ -1,13 +1,52 @@
-use crate::{
-    photo::process::{Error as ProcessPhotoError, ProcessedPhoto},
+use core_victorhqc_com::aws::rendition::{RENDITIONS, WATERMARK, WatermarkPosition};
+use log::debug;
+use pipeline_victorhqc_com::{
+    photo::{
+        color::{Error as ColorError, open_srgb},
+        process::{Error as ProcessPhotoError, ProcessedPhoto},
+        watermark::{Error as WatermarkError, Watermark, env_path},
+    },
     utils::is_valid_extension,
 };
-use image::error::ImageError;
-use log::debug;
 use snafu::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Overrides to try a watermark before setting it in `WATERMARK_PATH` and [`WATERMARK`].
+#[derive(Debug, Default)]
+pub struct WatermarkPreview {
+    /// Mark to draw, the one in `WATERMARK_PATH` is used when `None`.
+    pub path: Option<PathBuf>,
+    pub position: Option<WatermarkPosition>,
+    pub opacity: Option<f32>,
+    pub scale: Option<f32>,
+}
 
-pub async fn debug_compression(src: &Path) -> Result<(), Error> {
+impl WatermarkPreview {
+    fn load(self) -> Result<Option<Watermark>, WatermarkError> {
+        let Some(path) = self.path.or_else(env_path) else {
+            return Ok(None);
+        };
+
+        let mut settings = WATERMARK;
+        if let Some(position) = self.position {
+            settings.position = position;
+        }
+        if let Some(opacity) = self.opacity {
+            settings.opacity = opacity.clamp(0.0, 1.0);
+        }
+        if let Some(scale) = self.scale {
+            settings.scale = scale;
+        }
+
+        Watermark::open(&path, settings).map(Some)
+    }
+}
+
+pub async fn debug_compression(
+    src: &Path,
+    target_ssim: Option<f64>,
+    preview: WatermarkPreview,
+) -> Result<(), Error> {
     if !is_valid_extension(src) {
         return Err(Error::Extension {
             path: src.to_str().unwrap().to_string(),
@@ -20,18 +59,26 @@ pub async fn debug_compression(src: &Path) -> Result<(), Error> {
     }
 
     debug!("Opening photo");
-    let img = image::open(src).context(OpenSnafu)?;
+    let (img, color_space) = open_srgb(src).context(OpenSnafu)?;
+    debug!("Colour space: {}", color_space);
+
+    let watermark = preview.load().context(WatermarkSnafu)?;
 
     let mut processed: Vec<ProcessedPhoto> = Vec::new();
 
-    debug!("Processing HD+");
-    processed.push(ProcessedPhoto::build_hd_plus(&img).context(ProcessSnafu)?);
-    debug!("Processing HD");
-    processed.push(ProcessedPhoto::build_hd(&img).context(ProcessSnafu)?);
-    debug!("Processing MD");
-    processed.push(ProcessedPhoto::build_md(&img).context(ProcessSnafu)?);
-    debug!("Processing SM");
-    processed.push(ProcessedPhoto::build_sm(&img).context(ProcessSnafu)?);
+    for rendition in RENDITIONS {
+        debug!("Processing {}", rendition.name);
+        let target_ssim = target_ssim.or(rendition.target_ssim);
+        let photo = ProcessedPhoto::build_with_target(
+            &img,
+            rendition,
+            target_ssim,
+            watermark.as_ref(),
+            true,
+        )
+        .context(ProcessSnafu)?;
+        processed.push(photo);
+    }
 
     for photo in processed {
         let target_jpeg = debug_dir
@@ -41,8 +88,20 @@ pub async fn debug_compression(src: &Path) -> Result<(), Error> {
             .join(photo.size.to_string())
             .with_extension("webp");
 
-        std::fs::write(target_jpeg, &*photo.buffers.jpeg).expect("Failed to write JPEG");
-        std::fs::write(target_webp, &*photo.buffers.webp).expect("Failed to write JPEG");
+        let (jpeg, webp) = (&photo.buffers.jpeg, &photo.buffers.webp);
+        println!(
+            "{}: JPEG {} bytes at {} (SSIM {:.4}), WEBP {} bytes at {} (SSIM {:.4})",
+            photo.size,
+            jpeg.bytes.len(),
+            jpeg.quality,
+            jpeg.ssim.unwrap_or_default(),
+            webp.bytes.len(),
+            webp.quality,
+            webp.ssim.unwrap_or_default()
+        );
+
+        std::fs::write(target_jpeg, &jpeg.bytes).expect("Failed to write JPEG");
+        std::fs::write(target_webp, &webp.bytes).expect("Failed to write JPEG");
     }
 
     Ok(())
@@ -54,8 +113,11 @@ pub enum Error {
     Extension { path: String },
 
     #[snafu(display("Unable to open file: {}", source))]
-    Open { source: ImageError },
+    Open { source: ColorError },
 
     #[snafu(display("Failed to process photo: {}", source))]
     Process { source: ProcessPhotoError },
+
+    #[snafu(display("Failed to load the watermark: {}", source))]
+    Watermark { source: WatermarkError },
 }
@@ -1,4 +1,10 @@
+pub mod compression_report;
 pub mod create;
+pub mod create_remote;
 #[cfg(debug_assertions)]
 pub mod debug_compression;
 pub mod re_upload;
+pub mod rebuild;
+pub mod recipe;
+pub mod token;
+pub mod verify_originals;
@@ -1,51 +1,77 @@
-use crate::{
-    exiftool,
-    photo::{
-        aws::{Error as AWSError, remove, upload},
-        build_images::{Error as BuildImagesError, ImageProcess, finish_build, start_build},
-        orientation::{self, OrientationError},
-    },
-    utils::{GetFujifilmError, get_some_fujifilm_recipe},
-};
+use crate::utils::ask_recipe_name;
 use core_victorhqc_com::{
-    aws::S3,
+    aws::{S3, rendition::all_renditions},
     models::{
         exif_meta::{
             ExifMeta, PhotographyDetails, db::Error as ExifMetaDbError, from_exif::TryFromExifData,
         },
         photo::{Error as PhotoError, Photo, db::Error as PhotoDbError},
+        photo_rendition::db::Error as PhotoRenditionDbError,
     },
     sqlx::SqlitePool,
 };
 use core_victorhqc_com::{
     models::exif_meta::from_exif::PhotographyDetailsError, sqlx::error::Error as SqlxError,
 };
+use pipeline_victorhqc_com::{
+    exiftool,
+    photo::{
+        aws::{Error as AWSError, remove, upload, upload_original},
+        build_images::{Error as BuildImagesError, ImageProcess, finish_build, start_build},
+        orientation::{self, OrientationError},
+        watermark::{Error as WatermarkError, Watermark},
+    },
+    utils::{GetFujifilmError, GetGearError, get_gear, get_some_fujifilm_recipe, sha256},
+};
 use snafu::prelude::*;
-use std::{path::Path, sync::mpsc};
+use std::{
+    path::Path,
+    sync::{Arc, mpsc},
+};
 
-pub async fn re_upload(pool: &SqlitePool, id: String, src: &Path, s3: &S3) -> Result<(), Error> {
+/// Replaces the file of a photo. `watermark` changes whether the photo gets the watermark, `None`
+/// keeps what it had.
+pub async fn re_upload(
+    pool: &SqlitePool,
+    id: String,
+    src: &Path,
+    s3: &S3,
+    watermark: Option<bool>,
+) -> Result<(), Error> {
     let mut conn = pool.begin().await.context(DBConnectSnafu)?;
 
     let mut photo = Photo::find_by_id(&mut conn, &id)
         .await
         .context(PhotoByIdSnafu)?;
+    if let Some(watermark) = watermark {
+        photo.set_watermark(watermark);
+    }
 
     let data = exiftool::spawn::read_metadata(src).context(ExiftoolSnafu)?;
     trace!("Exiftool parsed data: {:?}", data);
 
-    let orientation = orientation::get_orientation(src).context(OrientationSnafu)?;
+    let (orientation, aspect_ratio) =
+        orientation::get_orientation(src).context(OrientationSnafu)?;
 
     let (tx, rx) = mpsc::channel::<ImageProcess>();
 
+    let mark = if photo.watermark {
+        Watermark::from_env().context(WatermarkSnafu)?.map(Arc::new)
+    } else {
+        None
+    };
+
     debug!("Building Images to Re-upload");
-    let main_handle = start_build(src, tx).context(BuildImagesSnafu)?;
+    let renditions = all_renditions();
+    let main_handle = start_build(src, &renditions, mark, false, tx).context(BuildImagesSnafu)?;
 
     photo
         .update_file(src, &orientation)
         .context(UpdatePhotoSnafu)?;
+    photo.set_aspect_ratio(aspect_ratio);
     photo.update(&mut conn).await.context(UpdateDbPhotoSnafu)?;
 
-    let recipe = get_some_fujifilm_recipe(&data, &mut conn)
+    let recipe = get_some_fujifilm_recipe(&data, &mut conn, ask_recipe_name)
         .await
         .context(FujifilmRecipeSnafu)?;
     debug!("{:?}", recipe);
@@ -54,20 +80,41 @@ pub async fn re_upload(pool: &SqlitePool, id: String, src: &Path, s3: &S3) -> Re
         PhotographyDetails::try_from_exif(data.as_slice()).context(PhotographyDetailsSnafu)?;
     debug!("{:?}", photography_details);
 
-    let exif = ExifMeta::new(photography_details, &photo, &recipe);
+    let (camera, lens) = get_gear(&photography_details, &mut conn)
+        .await
+        .context(GearSnafu)?;
+
+    let exif = ExifMeta::new(photography_details, &photo, &recipe, &camera, &lens);
     exif.replace(&mut conn).await.context(SaveExifSnafu)?;
     debug!("{:?}", exif);
 
     debug!("Removing Photos from AWS");
     remove(&photo, s3).await.context(RemoveSnafu)?;
 
-    let buffers = finish_build(rx, main_handle).context(BuildImagesSnafu)?;
+    let buffers = finish_build(rx, main_handle, &renditions).context(BuildImagesSnafu)?;
+
+    let original = std::fs::read(src).context(ReadOriginalSnafu)?;
 
     photo.set_blurhash(buffers.blurhash.clone());
+    photo.set_palette(buffers.palette.clone());
+    photo.set_perceptual_hash(buffers.perceptual_hash);
+    photo.set_color_space(buffers.color_space);
+    photo.set_original_sha256(sha256(&original));
+    photo.touch();
     photo.update(&mut conn).await.context(UpdateDbPhotoSnafu)?;
 
+    for rendition in buffers.renditions_of(&photo.id) {
+        rendition
+            .save(&mut conn)
+            .await
+            .context(SaveRenditionSnafu)?;
+    }
+
     debug!("About to upload to S3");
     upload(&photo, s3, buffers).await.context(UploadSnafu)?;
+    upload_original(&photo, s3, original)
+        .await
+        .context(UploadSnafu)?;
     debug!("Uploaded to S3");
 
     conn.commit().await.context(TxSnafu)?;
@@ -86,6 +133,9 @@ pub enum Error {
     #[snafu(display("Failed to update photo in the db: {}", source))]
     UpdateDbPhoto { source: PhotoDbError },
 
+    #[snafu(display("Failed to save the renditions: {}", source))]
+    SaveRendition { source: PhotoRenditionDbError },
+
     #[snafu(display("Failed to execute Transaction: {}", source))]
     Tx { source: SqlxError },
 
@@ -95,6 +145,9 @@ pub enum Error {
     #[snafu(display("Failed to build images: {}", source))]
     BuildImages { source: BuildImagesError },
 
+    #[snafu(display("Failed to read the original file: {}", source))]
+    ReadOriginal { source: std::io::Error },
+
     #[snafu(display("Failed to Upload {}", source))]
     Upload { source: AWSError },
 
@@ -113,6 +166,12 @@ pub enum Error {
     #[snafu(display("Failed to get Fujifilm Recipe: {}", source))]
     FujifilmRecipe { source: GetFujifilmError },
 
+    #[snafu(display("Failed to get the camera or lens: {}", source))]
+    Gear { source: GetGearError },
+
     #[snafu(display("Failed to get orientation: {}", source))]
     Orientation { source: OrientationError },
+
+    #[snafu(display("Failed to load the watermark: {}", source))]
+    Watermark { source: WatermarkError },
 }
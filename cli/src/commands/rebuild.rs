@@ -0,0 +1,368 @@
+use console::Emoji;
+use core_victorhqc_com::{
+    aws::{
+        S3,
+        rendition::{Rendition, all_renditions},
+    },
+    models::{
+        photo::{Photo, db::Error as PhotoDbError},
+        photo_rendition::db::Error as PhotoRenditionDbError,
+        rebuild_journal::{RebuildJournal, db::Error as JournalDbError},
+    },
+    sqlx::{SqlitePool, error::Error as SqlxError},
+};
+use futures::{StreamExt, stream};
+use log::{debug, error, info};
+use pipeline_victorhqc_com::{
+    photo::{
+        aws::{Error as AWSError, download_original, upload},
+        build_images::{
+            Error as BuildImagesError, ImageBuffers, ImageProcess, finish_build, start_build,
+        },
+        orientation::{OrientationError, get_orientation},
+        watermark::{Error as WatermarkError, Watermark},
+    },
+    utils::sha256,
+};
+use snafu::prelude::*;
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::{Arc, mpsc},
+};
+use tokio::task::JoinError;
+
+#[cfg(target_os = "windows")]
+static CHECK: Emoji<'_, '_> = Emoji("✅", "");
+#[cfg(not(target_os = "windows"))]
+static CHECK: Emoji<'_, '_> = Emoji("✅ ", "");
+
+pub struct Options {
+    /// Only rebuild this rendition, every rendition is rebuilt when `None`.
+    pub only_size: Option<&'static Rendition>,
+    /// Directory with the files as they came from the camera. Photos found there by filename are
+    /// read from it, the rest are downloaded from the archived originals.
+    pub source: Option<PathBuf>,
+    /// Record the SSIM of every file for `compression-report`, not only of the sizes with a target.
+    pub measure: bool,
+}
+
+impl Options {
+    fn renditions(&self) -> Vec<&'static Rendition> {
+        match self.only_size {
+            Some(rendition) => vec![rendition],
+            None => all_renditions(),
+        }
+    }
+
+    fn journal(&self) -> RebuildJournal {
+        RebuildJournal::new(self.only_size.map_or("all", |r| r.name))
+    }
+}
+
+/// Regenerates the renditions of a photo from its original, without needing the file locally.
+pub async fn rebuild(
+    pool: &SqlitePool,
+    id: String,
+    s3: &S3,
+    options: &Options,
+) -> Result<(), Error> {
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    let photo = Photo::find_by_id(&mut conn, &id)
+        .await
+        .context(PhotoByIdSnafu)?;
+    drop(conn);
+
+    let watermark = load_watermark()?;
+
+    rebuild_photo(pool, photo, s3, options, watermark.as_ref(), None).await
+}
+
+/// Regenerates the renditions of every photo, `jobs` photos at a time. Finished photos are written
+/// to a journal, so running the same command again after an interruption skips them. The journal
+/// is cleared once every photo went through, or before starting when `restart` is set.
+pub async fn rebuild_all(
+    pool: &SqlitePool,
+    s3: &S3,
+    options: &Options,
+    jobs: usize,
+    restart: bool,
+) -> Result<(), Error> {
+    let journal = options.journal();
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    if restart {
+        journal.clear(&mut conn).await.context(JournalSnafu)?;
+    }
+
+    let completed = journal
+        .completed_ids(&mut conn)
+        .await
+        .context(JournalSnafu)?;
+    let photos: Vec<Photo> = Photo::find_all(&mut conn, None)
+        .await
+        .context(PhotosSnafu)?
+        .into_iter()
+        .filter(|p| !completed.contains(&p.id))
+        .collect();
+    drop(conn);
+
+    if !completed.is_empty() {
+        info!("Resuming, {} photos were already rebuilt", completed.len());
+    }
+    info!("Rebuilding {} photos with {} jobs", photos.len(), jobs);
+
+    let watermark = load_watermark()?;
+
+    let results: Vec<(String, Result<(), Error>)> = stream::iter(photos)
+        .map(|photo| {
+            let journal = &journal;
+            let watermark = watermark.as_ref();
+
+            async move {
+                let id = photo.id.clone();
+                let result =
+                    rebuild_photo(pool, photo, s3, options, watermark, Some(journal)).await;
+
+                (id, result)
+            }
+        })
+        .buffer_unordered(jobs.max(1))
+        .collect()
+        .await;
+
+    let mut rebuilt = 0;
+    let mut failed: Vec<String> = Vec::new();
+    for (id, result) in results {
+        match result {
+            Ok(_) => rebuilt += 1,
+            Err(e) => {
+                error!("Failed to rebuild {}: {}", id, e);
+                failed.push(id);
+            }
+        }
+    }
+
+    println!(
+        "{} {} photos rebuilt, {} failed",
+        CHECK,
+        rebuilt,
+        failed.len()
+    );
+
+    ensure!(failed.is_empty(), FailedSnafu { ids: failed });
+
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+    journal.clear(&mut conn).await.context(JournalSnafu)?;
+
+    Ok(())
+}
+
+/// The original written to a temporary file, removed when dropped whether the build worked or not.
+struct TempOriginal(PathBuf);
+
+impl TempOriginal {
+    fn write(id: &str, original: &[u8]) -> Result<Self, Error> {
+        let file = TempOriginal(std::env::temp_dir().join(format!("{}.jpg", id)));
+        std::fs::write(&file.0, original).context(TempFileSnafu)?;
+
+        Ok(file)
+    }
+}
+
+impl Drop for TempOriginal {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.0)
+            && e.kind() != ErrorKind::NotFound
+        {
+            error!("Failed to remove {:?}: {}", self.0, e);
+        }
+    }
+}
+
+async fn rebuild_photo(
+    pool: &SqlitePool,
+    mut photo: Photo,
+    s3: &S3,
+    options: &Options,
+    watermark: Option<&Arc<Watermark>>,
+    journal: Option<&RebuildJournal>,
+) -> Result<(), Error> {
+    let original = read_original(&photo, s3, options.source.as_deref()).await?;
+
+    if let Some(expected) = &photo.original_sha256 {
+        let actual = sha256(&original);
+        ensure!(
+            &actual == expected,
+            ChecksumSnafu {
+                id: photo.id.clone(),
+                expected,
+                actual
+            }
+        );
+    }
+
+    // The pipeline reads from a path, the original goes through a temporary file.
+    let src = TempOriginal::write(&photo.id, &original)?;
+
+    debug!("Building Images of {} from the original", photo.id);
+    let renditions = options.renditions();
+    let measure = options.measure;
+    let watermark = watermark.filter(|_| photo.watermark).cloned();
+    // Building blocks until every rendition is done, it can't hold the runtime while other jobs
+    // download or upload. The file is removed once the task is done with it.
+    let (buffers, orientation) = tokio::task::spawn_blocking(move || {
+        let buffers = build(&src.0, &renditions, watermark, measure);
+        let orientation = get_orientation(&src.0);
+
+        (buffers, orientation)
+    })
+    .await
+    .context(JoinSnafu)?;
+    let buffers = buffers.context(BuildImagesSnafu)?;
+
+    // Photos created before the EXIF rotation was read may have the wrong orientation.
+    let (orientation, aspect_ratio) = orientation.context(OrientationSnafu)?;
+    photo.orientation = orientation;
+    photo.set_aspect_ratio(aspect_ratio);
+
+    photo.set_blurhash(buffers.blurhash.clone());
+    photo.set_palette(buffers.palette.clone());
+    photo.set_perceptual_hash(buffers.perceptual_hash);
+    photo.set_color_space(buffers.color_space);
+    photo.touch();
+    let renditions = buffers.renditions_of(&photo.id);
+
+    debug!("About to upload {} to S3", photo.id);
+    upload(&photo, s3, buffers)
+        .await
+        .map_err(Box::new)
+        .context(UploadSnafu)?;
+    debug!("Uploaded {} to S3", photo.id);
+
+    let mut conn = pool.begin().await.context(TxSnafu)?;
+    photo.update(&mut conn).await.context(UpdateDbPhotoSnafu)?;
+    for rendition in renditions {
+        rendition
+            .save(&mut conn)
+            .await
+            .context(SaveRenditionSnafu)?;
+    }
+    if let Some(journal) = journal {
+        journal
+            .complete(&mut conn, &photo.id)
+            .await
+            .context(JournalSnafu)?;
+    }
+    conn.commit().await.context(TxSnafu)?;
+
+    Ok(())
+}
+
+async fn read_original(photo: &Photo, s3: &S3, source: Option<&Path>) -> Result<Vec<u8>, Error> {
+    if let Some(path) = source.map(|dir| dir.join(&photo.filename))
+        && path.exists()
+    {
+        debug!("Reading the original of {} from {:?}", photo.id, path);
+        return std::fs::read(&path).context(ReadOriginalSnafu);
+    }
+
+    ensure!(
+        photo.original_sha256.is_some(),
+        NotArchivedSnafu {
+            id: photo.id.clone()
+        }
+    );
+
+    debug!("Downloading the original of {}", photo.id);
+    download_original(photo, s3)
+        .await
+        .map_err(Box::new)
+        .context(DownloadSnafu)
+}
+
+/// The mark is loaded once and shared by every photo, the ones that opted out skip it.
+fn load_watermark() -> Result<Option<Arc<Watermark>>, Error> {
+    let watermark = Watermark::from_env().context(WatermarkSnafu)?;
+
+    Ok(watermark.map(Arc::new))
+}
+
+fn build(
+    src: &Path,
+    renditions: &[&'static Rendition],
+    watermark: Option<Arc<Watermark>>,
+    measure: bool,
+) -> Result<ImageBuffers, BuildImagesError> {
+    let (tx, rx) = mpsc::channel::<ImageProcess>();
+
+    let main_handle = start_build(src, renditions, watermark, measure, tx)?;
+    finish_build(rx, main_handle, renditions)
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to connect to db: {}", source))]
+    Connection { source: SqlxError },
+
+    #[snafu(display("Failed to execute Transaction: {}", source))]
+    Tx { source: SqlxError },
+
+    #[snafu(display("Failed to check for photo by id: {}", source))]
+    PhotoById { source: PhotoDbError },
+
+    #[snafu(display("Failed to get the photos: {}", source))]
+    Photos { source: PhotoDbError },
+
+    #[snafu(display("Failed to use the rebuild journal: {}", source))]
+    Journal { source: JournalDbError },
+
+    #[snafu(display("Photo {} has no archived original, use re-upload instead", id))]
+    NotArchived { id: String },
+
+    #[snafu(display("Failed to read the original file: {}", source))]
+    ReadOriginal { source: std::io::Error },
+
+    #[snafu(display("Failed to download the original: {}", source))]
+    Download { source: Box<AWSError> },
+
+    #[snafu(display(
+        "The original of {} is corrupted, expected {} but got {}",
+        id,
+        expected,
+        actual
+    ))]
+    Checksum {
+        id: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[snafu(display("Failed to handle the temporary file: {}", source))]
+    TempFile { source: std::io::Error },
+
+    #[snafu(display("Failed to get orientation: {}", source))]
+    Orientation { source: OrientationError },
+
+    #[snafu(display("Failed to load the watermark: {}", source))]
+    Watermark { source: WatermarkError },
+
+    #[snafu(display("The build task failed: {}", source))]
+    Join { source: JoinError },
+
+    #[snafu(display("Failed to build images: {}", source))]
+    BuildImages { source: BuildImagesError },
+
+    #[snafu(display("Failed to update photo in the db: {}", source))]
+    UpdateDbPhoto { source: PhotoDbError },
+
+    #[snafu(display("Failed to save the renditions: {}", source))]
+    SaveRendition { source: PhotoRenditionDbError },
+
+    #[snafu(display("Failed to Upload {}", source))]
+    Upload { source: Box<AWSError> },
+
+    #[snafu(display("Failed to rebuild: {}", ids.join(", ")))]
+    Failed { ids: Vec<String> },
+}
@@ -0,0 +1,254 @@
+use crate::utils::capture;
+use console::Emoji;
+use core_victorhqc_com::{
+    models::fujifilm::{
+        FujifilmRecipe,
+        db::Error as FujifilmDbError,
+        file::{RecipeFile, RecipeLibrary},
+    },
+    sqlx::{SqlitePool, error::Error as SqlxError},
+};
+use log::debug;
+use snafu::prelude::*;
+use std::path::Path;
+
+#[cfg(target_os = "windows")]
+static FILM: Emoji<'_, '_> = Emoji("🎞️", "");
+#[cfg(not(target_os = "windows"))]
+static FILM: Emoji<'_, '_> = Emoji("🎞️  ", "");
+
+#[derive(Debug)]
+enum RecipeFormat {
+    Toml,
+    Json,
+}
+
+impl RecipeFormat {
+    fn from_path(path: &Path) -> Result<Self, Error> {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+
+        match ext.as_deref() {
+            Some("toml") => Ok(RecipeFormat::Toml),
+            Some("json") => Ok(RecipeFormat::Json),
+            _ => Err(Error::Format {
+                path: path.display().to_string(),
+            }),
+        }
+    }
+}
+
+/// Reads a recipe library and stores its recipes. Recipes with the same settings as an existing
+/// one update it instead, and any duplicated recipe left by older versions is merged into it.
+pub async fn import(pool: &SqlitePool, src: &Path) -> Result<(), Error> {
+    let format = RecipeFormat::from_path(src)?;
+    let content = std::fs::read_to_string(src).context(ReadSnafu)?;
+
+    let library: RecipeLibrary = match format {
+        RecipeFormat::Toml => toml::from_str(&content).context(TomlDeserializeSnafu)?,
+        RecipeFormat::Json => serde_json::from_str(&content).context(JsonSnafu)?,
+    };
+    debug!("Recipes to import: {}", library.recipes.len());
+
+    let mut conn = pool.begin().await.context(TxSnafu)?;
+
+    let mut created = 0;
+    let mut updated = 0;
+    let mut relinked = 0;
+
+    for file in library.recipes {
+        let name = file.name.clone();
+        let recipe: FujifilmRecipe = file.try_into().context(InvalidRecipeSnafu { name })?;
+
+        let existing = FujifilmRecipe::find_all_by_details(&mut conn, recipe.details())
+            .await
+            .context(DbSnafu)?;
+
+        match existing.split_first() {
+            None => {
+                recipe.save(&mut conn).await.context(DbSnafu)?;
+                debug!("Created {:?}", recipe);
+
+                created += 1;
+            }
+            Some((first, duplicates)) => {
+                let mut first = first.clone();
+                first.name = recipe.name;
+                first.author = recipe.author.or(first.author);
+                first.src = recipe.src.or(first.src);
+
+                first.update(&mut conn).await.context(DbSnafu)?;
+                relinked += first
+                    .merge_duplicates(&mut conn, duplicates)
+                    .await
+                    .context(DbSnafu)?;
+                debug!("Updated {:?}", first);
+
+                updated += 1;
+            }
+        }
+    }
+
+    conn.commit().await.context(TxSnafu)?;
+
+    println!(
+        "{} {} recipes created, {} updated, {} photos re-linked",
+        FILM, created, updated, relinked
+    );
+
+    Ok(())
+}
+
+/// Writes all the recipes, or only the one with the given id, into a recipe library.
+pub async fn export(pool: &SqlitePool, output: &Path, id: Option<String>) -> Result<(), Error> {
+    let format = RecipeFormat::from_path(output)?;
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    let recipes = match id {
+        Some(id) => vec![
+            FujifilmRecipe::find_by_id(&mut conn, &id)
+                .await
+                .context(DbSnafu)?
+                .context(NotFoundSnafu { id })?,
+        ],
+        None => FujifilmRecipe::find_all(&mut conn).await.context(DbSnafu)?,
+    };
+
+    let library = RecipeLibrary {
+        recipes: recipes.iter().map(RecipeFile::from).collect(),
+    };
+
+    let content = match format {
+        RecipeFormat::Toml => toml::to_string_pretty(&library).context(TomlSerializeSnafu)?,
+        RecipeFormat::Json => serde_json::to_string_pretty(&library).context(JsonSnafu)?,
+    };
+
+    std::fs::write(output, content).context(WriteSnafu)?;
+
+    println!(
+        "{} {} recipes exported to {}",
+        FILM,
+        library.recipes.len(),
+        output.display()
+    );
+
+    Ok(())
+}
+
+pub async fn list(pool: &SqlitePool) -> Result<(), Error> {
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    let recipes = FujifilmRecipe::find_all(&mut conn).await.context(DbSnafu)?;
+
+    for recipe in recipes {
+        println!(
+            "{}  {}  [{}, {}]  {}",
+            recipe.id,
+            recipe.name,
+            recipe.details().film_simulation,
+            recipe.details().sensor,
+            recipe.author.as_deref().unwrap_or("Unknown author"),
+        );
+    }
+
+    Ok(())
+}
+
+/// Changes the name, author or source of a recipe. When no value is given, each one is asked for,
+/// leaving the answer empty keeps the current value.
+pub async fn edit(
+    pool: &SqlitePool,
+    id: String,
+    name: Option<String>,
+    author: Option<String>,
+    src: Option<String>,
+) -> Result<(), Error> {
+    let mut conn = pool.begin().await.context(TxSnafu)?;
+
+    let mut recipe = FujifilmRecipe::find_by_id(&mut conn, &id)
+        .await
+        .context(DbSnafu)?
+        .context(NotFoundSnafu { id: &id })?;
+
+    if name.is_none() && author.is_none() && src.is_none() {
+        let name = capture(&format!("{} Name [{}]: ", FILM, recipe.name));
+        let author = capture(&format!(
+            "{} Author [{}]: ",
+            FILM,
+            recipe.author.as_deref().unwrap_or("")
+        ));
+        let src = capture(&format!(
+            "{} Source [{}]: ",
+            FILM,
+            recipe.src.as_deref().unwrap_or("")
+        ));
+
+        if !name.is_empty() {
+            recipe.name = name;
+        }
+        if !author.is_empty() {
+            recipe.author = Some(author);
+        }
+        if !src.is_empty() {
+            recipe.src = Some(src);
+        }
+    } else {
+        if let Some(name) = name {
+            recipe.name = name;
+        }
+        if author.is_some() {
+            recipe.author = author;
+        }
+        if src.is_some() {
+            recipe.src = src;
+        }
+    }
+
+    recipe.update(&mut conn).await.context(DbSnafu)?;
+    conn.commit().await.context(TxSnafu)?;
+
+    debug!("Updated {:?}", recipe);
+
+    Ok(())
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Unknown recipe format for {}, use .toml or .json", path))]
+    Format { path: String },
+
+    #[snafu(display("Failed to read the recipes file: {}", source))]
+    Read { source: std::io::Error },
+
+    #[snafu(display("Failed to write the recipes file: {}", source))]
+    Write { source: std::io::Error },
+
+    #[snafu(display("Failed to parse TOML: {}", source))]
+    TomlDeserialize { source: toml::de::Error },
+
+    #[snafu(display("Failed to write TOML: {}", source))]
+    TomlSerialize { source: toml::ser::Error },
+
+    #[snafu(display("Failed to parse JSON: {}", source))]
+    Json { source: serde_json::Error },
+
+    #[snafu(display("Recipe {} is not valid: {}", name, source))]
+    InvalidRecipe {
+        name: String,
+        source: FujifilmDbError,
+    },
+
+    #[snafu(display("Failed to query recipes: {}", source))]
+    Db { source: FujifilmDbError },
+
+    #[snafu(display("Recipe {} does not exist", id))]
+    NotFound { id: String },
+
+    #[snafu(display("Failed to connect to db: {}", source))]
+    Connection { source: SqlxError },
+
+    #[snafu(display("Failed to execute Transaction: {}", source))]
+    Tx { source: SqlxError },
+}
@@ -0,0 +1,79 @@
+use console::Emoji;
+use core_victorhqc_com::{
+    models::api_token::{ApiToken, Scope, db::Error as ApiTokenDbError},
+    sqlx::{SqlitePool, error::Error as SqlxError},
+};
+use snafu::prelude::*;
+use time::{Duration, OffsetDateTime, format_description::well_known::Rfc3339};
+
+#[cfg(target_os = "windows")]
+static KEY: Emoji<'_, '_> = Emoji("🔑", "");
+#[cfg(not(target_os = "windows"))]
+static KEY: Emoji<'_, '_> = Emoji("🔑 ", "");
+
+/// Stores a new token and prints its secret, the only time it is shown.
+pub async fn create(
+    pool: &SqlitePool,
+    name: String,
+    scopes: Vec<Scope>,
+    expires_in_days: Option<i64>,
+) -> Result<(), Error> {
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    let expires_at = expires_in_days.map(|days| OffsetDateTime::now_utc() + Duration::days(days));
+    let (token, secret) = ApiToken::generate(name, scopes, expires_at);
+    token.save(&mut conn).await.context(DbSnafu)?;
+
+    println!(
+        "{} Token {} created, store it now, it won't be shown again:",
+        KEY, token.id
+    );
+    println!("{}", secret);
+
+    Ok(())
+}
+
+pub async fn revoke(pool: &SqlitePool, id: String) -> Result<(), Error> {
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    ApiToken::revoke(&mut conn, &id).await.context(DbSnafu)?;
+
+    println!("{} Token {} revoked", KEY, id);
+
+    Ok(())
+}
+
+pub async fn list(pool: &SqlitePool) -> Result<(), Error> {
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    let now = OffsetDateTime::now_utc();
+    let tokens = ApiToken::find_all(&mut conn).await.context(DbSnafu)?;
+
+    for token in tokens {
+        let scopes: Vec<String> = token.scopes.iter().map(|s| s.to_string()).collect();
+        let expires = match token.expires_at {
+            Some(_) if token.is_expired(now) => "expired".to_string(),
+            Some(expires_at) => format!("expires {}", expires_at.format(&Rfc3339).unwrap()),
+            None => "never expires".to_string(),
+        };
+
+        println!(
+            "{}  {}  [{}]  {}",
+            token.id,
+            token.name,
+            scopes.join(", "),
+            expires
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to manage the tokens: {}", source))]
+    Db { source: ApiTokenDbError },
+
+    #[snafu(display("Failed to connect to db: {}", source))]
+    Connection { source: SqlxError },
+}
@@ -0,0 +1,85 @@
+use console::Emoji;
+use core_victorhqc_com::{
+    aws::S3,
+    models::photo::{Photo, db::Error as PhotoDbError},
+    sqlx::{SqlitePool, error::Error as SqlxError},
+};
+use log::{debug, error, warn};
+use pipeline_victorhqc_com::{photo::aws::download_original, utils::sha256};
+use snafu::prelude::*;
+
+#[cfg(target_os = "windows")]
+static CHECK: Emoji<'_, '_> = Emoji("✅", "");
+#[cfg(not(target_os = "windows"))]
+static CHECK: Emoji<'_, '_> = Emoji("✅ ", "");
+
+/// Downloads every archived original and compares its SHA-256 with the one recorded when it was
+/// uploaded. Photos created before originals were archived are reported but don't fail the check.
+pub async fn verify_originals(pool: &SqlitePool, s3: &S3) -> Result<(), Error> {
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    let photos = Photo::find_all(&mut conn, None)
+        .await
+        .context(PhotosSnafu)?;
+
+    let mut verified = 0;
+    let mut not_archived = 0;
+    let mut failed: Vec<String> = Vec::new();
+
+    for photo in photos {
+        let Some(expected) = &photo.original_sha256 else {
+            warn!("{} ({}) has no archived original", photo.id, photo.filename);
+            not_archived += 1;
+
+            continue;
+        };
+
+        match download_original(&photo, s3).await {
+            Ok(data) => {
+                let actual = sha256(&data);
+
+                if &actual == expected {
+                    debug!("{} verified", photo.id);
+                    verified += 1;
+                } else {
+                    error!(
+                        "{} ({}) checksum mismatch, expected {} but got {}",
+                        photo.id, photo.filename, expected, actual
+                    );
+                    failed.push(photo.id);
+                }
+            }
+            Err(e) => {
+                error!(
+                    "{} ({}) could not be downloaded: {}",
+                    photo.id, photo.filename, e
+                );
+                failed.push(photo.id);
+            }
+        }
+    }
+
+    println!(
+        "{} {} originals verified, {} failed, {} not archived",
+        CHECK,
+        verified,
+        failed.len(),
+        not_archived
+    );
+
+    ensure!(failed.is_empty(), CorruptedSnafu { ids: failed });
+
+    Ok(())
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to connect to db: {}", source))]
+    Connection { source: SqlxError },
+
+    #[snafu(display("Failed to get the photos: {}", source))]
+    Photos { source: PhotoDbError },
+
+    #[snafu(display("Originals missing or corrupted: {}", ids.join(", ")))]
+    Corrupted { ids: Vec<String> },
+}
@@ -2,13 +2,18 @@
 extern crate log;
 
 mod commands;
-mod exiftool;
-mod photo;
 mod utils;
 
 use clap::{Parser, Subcommand};
-use core_victorhqc_com::{aws::S3, db::get_pool};
-use std::path::Path;
+use core_victorhqc_com::{
+    aws::{
+        S3,
+        rendition::{Rendition, WatermarkPosition, find_rendition},
+    },
+    db::get_pool,
+    models::api_token::Scope,
+};
+use std::path::{Path, PathBuf};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -31,11 +36,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     debug!("CLI: {:?}", args);
 
     match args.command {
-        Commands::Create { source } => {
+        Commands::Create {
+            source,
+            no_watermark,
+            remote,
+        } => {
             let src = Path::new(&source);
 
-            commands::create::create(&pool, src, &s3)
-                .await
+            let result: Result<(), Box<dyn std::error::Error>> = match remote {
+                Some(remote) => commands::create_remote::create_remote(src, &remote, !no_watermark)
+                    .await
+                    .map_err(Into::into),
+                None => commands::create::create(&pool, src, &s3, !no_watermark)
+                    .await
+                    .map_err(Into::into),
+            };
+
+            result
                 .map_err(|e| {
                     error!("Failed to create Image: {}", e);
 
@@ -43,10 +60,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 })
                 .unwrap();
         }
-        Commands::ReUpload { id, source } => {
+        Commands::ReUpload {
+            id,
+            source,
+            watermark,
+            no_watermark,
+        } => {
             let src = Path::new(&source);
+            let watermark = match (watermark, no_watermark) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            };
 
-            commands::re_upload::re_upload(&pool, id, src, &s3)
+            commands::re_upload::re_upload(&pool, id, src, &s3, watermark)
                 .await
                 .map_err(|e| {
                     error!("Failed to re-upload Image: {}", e);
@@ -55,11 +82,116 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 })
                 .unwrap();
         }
+        Commands::Rebuild {
+            id,
+            only_size,
+            jobs,
+            source,
+            restart,
+            measure,
+            ..
+        } => {
+            let options = commands::rebuild::Options {
+                only_size,
+                source: source.map(PathBuf::from),
+                measure,
+            };
+
+            let result = match id {
+                Some(id) => commands::rebuild::rebuild(&pool, id, &s3, &options).await,
+                None => commands::rebuild::rebuild_all(&pool, &s3, &options, jobs, restart).await,
+            };
+
+            result
+                .map_err(|e| {
+                    error!("Failed to rebuild Image: {}", e);
+
+                    e
+                })
+                .unwrap();
+        }
+        Commands::VerifyOriginals => {
+            commands::verify_originals::verify_originals(&pool, &s3)
+                .await
+                .map_err(|e| {
+                    error!("Failed to verify originals: {}", e);
+
+                    e
+                })
+                .unwrap();
+        }
+        Commands::Recipe { command } => {
+            let result = match command {
+                RecipeCommands::Import { source } => {
+                    commands::recipe::import(&pool, Path::new(&source)).await
+                }
+                RecipeCommands::Export { output, id } => {
+                    commands::recipe::export(&pool, Path::new(&output), id).await
+                }
+                RecipeCommands::List => commands::recipe::list(&pool).await,
+                RecipeCommands::Edit {
+                    id,
+                    name,
+                    author,
+                    src,
+                } => commands::recipe::edit(&pool, id, name, author, src).await,
+            };
+
+            result
+                .map_err(|e| {
+                    error!("Failed to manage recipes: {}", e);
+
+                    e
+                })
+                .unwrap();
+        }
+        Commands::Token { command } => {
+            let result = match command {
+                TokenCommands::Create {
+                    name,
+                    scopes,
+                    expires_in_days,
+                } => commands::token::create(&pool, name, scopes, expires_in_days).await,
+                TokenCommands::Revoke { id } => commands::token::revoke(&pool, id).await,
+                TokenCommands::List => commands::token::list(&pool).await,
+            };
+
+            result
+                .map_err(|e| {
+                    error!("Failed to manage tokens: {}", e);
+
+                    e
+                })
+                .unwrap();
+        }
+        Commands::CompressionReport { worst } => {
+            commands::compression_report::compression_report(&pool, worst)
+                .await
+                .map_err(|e| {
+                    error!("Failed to build the compression report: {}", e);
+
+                    e
+                })
+                .unwrap();
+        }
         #[cfg(debug_assertions)]
-        Commands::DebugCompression { source } => {
+        Commands::DebugCompression {
+            source,
+            target_ssim,
+            watermark,
+            watermark_position,
+            watermark_opacity,
+            watermark_scale,
+        } => {
             let src = Path::new(&source);
+            let preview = commands::debug_compression::WatermarkPreview {
+                path: watermark.map(PathBuf::from),
+                position: watermark_position,
+                opacity: watermark_opacity,
+                scale: watermark_scale,
+            };
 
-            commands::debug_compression::debug_compression(src)
+            commands::debug_compression::debug_compression(src, target_ssim, preview)
                 .await
                 .unwrap();
         }
@@ -68,6 +200,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+fn parse_rendition(name: &str) -> Result<&'static Rendition, String> {
+    find_rendition(name).ok_or_else(|| format!("unknown size {}", name))
+}
+
 #[derive(Debug, Parser)]
 struct Cli {
     #[command(subcommand)]
@@ -80,6 +216,15 @@ enum Commands {
     Create {
         #[arg(short, long)]
         source: String,
+
+        /// Never draw the watermark on this photo
+        #[arg(long)]
+        no_watermark: bool,
+
+        /// Publish through the API at this URL instead of the local database, with the token
+        /// in API_TOKEN
+        #[arg(long)]
+        remote: Option<String>,
     },
     #[command(arg_required_else_help = true)]
     ReUpload {
@@ -88,10 +233,152 @@ enum Commands {
 
         #[arg(short, long)]
         source: String,
+
+        /// Draw the watermark again on a photo that opted out
+        #[arg(long, conflicts_with = "no_watermark")]
+        watermark: bool,
+
+        /// Stop drawing the watermark on this photo
+        #[arg(long)]
+        no_watermark: bool,
+    },
+    /// Regenerate the sizes of one or every photo from their originals
+    #[command(arg_required_else_help = true)]
+    Rebuild {
+        #[arg(short, long, required_unless_present = "all", conflicts_with = "all")]
+        id: Option<String>,
+
+        /// Rebuild every photo, resuming an interrupted run
+        #[arg(short, long)]
+        all: bool,
+
+        /// Only rebuild this size, e.g. Md
+        #[arg(long, value_parser = parse_rendition)]
+        only_size: Option<&'static Rendition>,
+
+        /// How many photos are rebuilt at the same time
+        #[arg(short, long, default_value_t = 2)]
+        jobs: usize,
+
+        /// Directory with the original files, missing ones are downloaded from the archive
+        #[arg(short, long)]
+        source: Option<String>,
+
+        /// Forget the progress of an interrupted run and start over
+        #[arg(long, requires = "all")]
+        restart: bool,
+
+        /// Measure the SSIM of every file for the compression report, it takes about twice as long
+        #[arg(long)]
+        measure: bool,
+    },
+    /// Check that every archived original matches its recorded SHA-256
+    VerifyOriginals,
+    /// Manage the Fujifilm recipe library
+    #[command(arg_required_else_help = true)]
+    Recipe {
+        #[command(subcommand)]
+        command: RecipeCommands,
+    },
+    /// Manage the tokens of the API write operations
+    #[command(arg_required_else_help = true)]
+    Token {
+        #[command(subcommand)]
+        command: TokenCommands,
+    },
+    /// Compare sizes, qualities and SSIM of every rendition in the library
+    CompressionReport {
+        /// How many of the lowest scoring files to list
+        #[arg(short, long, default_value_t = 10)]
+        worst: usize,
     },
     #[cfg(debug_assertions)]
     DebugCompression {
         #[arg(short, long)]
         source: String,
+
+        /// Search the lowest quality reaching this SSIM instead of the configured one
+        #[arg(short, long)]
+        target_ssim: Option<f64>,
+
+        /// Preview this mark instead of the one in WATERMARK_PATH, PNG or SVG
+        #[arg(short, long)]
+        watermark: Option<String>,
+
+        /// Where the mark goes, e.g. bottom-right or center
+        #[arg(long)]
+        watermark_position: Option<WatermarkPosition>,
+
+        /// From 0, invisible, to 1, fully opaque
+        #[arg(long)]
+        watermark_opacity: Option<f32>,
+
+        /// Long side of the mark relative to the short edge of the photo, e.g. 0.12
+        #[arg(long)]
+        watermark_scale: Option<f32>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum RecipeCommands {
+    /// Import recipes from a .toml or .json file
+    #[command(arg_required_else_help = true)]
+    Import {
+        #[arg(short, long)]
+        source: String,
+    },
+    /// Export recipes into a .toml or .json file
+    #[command(arg_required_else_help = true)]
+    Export {
+        #[arg(short, long)]
+        output: String,
+
+        /// Only export the recipe with this id
+        #[arg(short, long)]
+        id: Option<String>,
+    },
+    /// List the stored recipes
+    List,
+    /// Change the name, author or source of a recipe
+    #[command(arg_required_else_help = true)]
+    Edit {
+        #[arg(short, long)]
+        id: String,
+
+        #[arg(short, long)]
+        name: Option<String>,
+
+        #[arg(short, long)]
+        author: Option<String>,
+
+        #[arg(short, long)]
+        src: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum TokenCommands {
+    /// Create a token and print its secret
+    #[command(arg_required_else_help = true)]
+    Create {
+        /// What the token is for, e.g. the machine using it
+        #[arg(short, long)]
+        name: String,
+
+        /// read, write:photos, write:tags or admin, can be repeated
+        #[arg(short, long = "scope", required = true)]
+        scopes: Vec<Scope>,
+
+        /// Days until the token stops working, never by default
+        #[arg(short, long)]
+        expires_in_days: Option<i64>,
+    },
+    /// Revoke a token, requests using it are rejected from now on
+    #[command(arg_required_else_help = true)]
+    Revoke {
+        #[arg(short, long)]
+        id: String,
     },
+    /// List the tokens, without their secrets
+    List,
 }
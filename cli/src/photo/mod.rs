@@ -1,4 +0,0 @@
-pub mod aws;
-pub mod build_images;
-pub mod orientation;
-pub mod process;
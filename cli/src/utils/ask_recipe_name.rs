@@ -0,0 +1,15 @@
+use super::capture;
+use console::Emoji;
+
+#[cfg(target_os = "windows")]
+static FILM: Emoji<'_, '_> = Emoji("🎞️", "");
+#[cfg(not(target_os = "windows"))]
+static FILM: Emoji<'_, '_> = Emoji("🎞️  ", "");
+
+/// Asks for the name of a recipe seen for the first time.
+pub fn ask_recipe_name() -> Option<String> {
+    Some(capture(&format!(
+        "{} Please, specify the name of the recipe used: ",
+        FILM
+    )))
+}
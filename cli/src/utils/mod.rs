@@ -1,7 +1,5 @@
+mod ask_recipe_name;
 mod capture;
-mod get_some_fujifilm_recipe;
-mod is_valid_extension;
 
+pub use ask_recipe_name::*;
 pub use capture::*;
-pub use get_some_fujifilm_recipe::*;
-pub use is_valid_extension::*;
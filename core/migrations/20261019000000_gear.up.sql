@@ -0,0 +1,105 @@
+CREATE TABLE IF NOT EXISTS cameras (
+  id TEXT PRIMARY KEY NOT NULL,
+  name TEXT NOT NULL UNIQUE,
+  display_name TEXT NOT NULL,
+  maker TEXT NOT NULL,
+  mount TEXT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS lenses (
+  id TEXT PRIMARY KEY NOT NULL,
+  name TEXT NOT NULL UNIQUE,
+  display_name TEXT NOT NULL,
+  maker TEXT NOT NULL,
+  mount TEXT NULL,
+  min_focal_length REAL NULL,
+  max_focal_length REAL NULL,
+  max_aperture REAL NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL
+);
+
+ALTER TABLE exif_metas
+ADD COLUMN camera_id TEXT NULL REFERENCES cameras (id) ON DELETE SET NULL ON UPDATE CASCADE;
+
+ALTER TABLE exif_metas
+ADD COLUMN lens_id TEXT NULL REFERENCES lenses (id) ON DELETE SET NULL ON UPDATE CASCADE;
+
+-- Backfill the catalogue from the free strings already stored in exif_metas.
+INSERT INTO
+  cameras (id, name, display_name, maker, mount)
+SELECT
+  lower(
+    hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6))
+  ),
+  camera_name,
+  camera_maker || ' ' || camera_name,
+  camera_maker,
+  CASE
+    WHEN camera_maker = 'FUJIFILM' AND camera_name LIKE 'GFX%' THEN 'G'
+    WHEN camera_maker = 'FUJIFILM' THEN 'X'
+    ELSE NULL
+  END
+FROM
+  exif_metas
+GROUP BY
+  camera_name;
+
+-- Focal range and max aperture are seeded from what the photos report, new lenses get them
+-- parsed from their name.
+INSERT INTO
+  lenses (
+    id,
+    name,
+    display_name,
+    maker,
+    mount,
+    min_focal_length,
+    max_focal_length,
+    max_aperture
+  )
+SELECT
+  lower(
+    hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6))
+  ),
+  lens_name,
+  CASE
+    WHEN lens_maker = 'Unknown' THEN lens_name
+    ELSE lens_maker || ' ' || lens_name
+  END,
+  lens_maker,
+  CASE
+    WHEN lens_maker = 'FUJIFILM' AND lens_name LIKE 'GF%' THEN 'G'
+    WHEN lens_maker = 'FUJIFILM' THEN 'X'
+    ELSE NULL
+  END,
+  MIN(focal_length),
+  MAX(focal_length),
+  MIN(aperture)
+FROM
+  exif_metas
+WHERE
+  lens_name IS NOT NULL
+GROUP BY
+  lens_name;
+
+UPDATE exif_metas
+SET
+  camera_id = (
+    SELECT
+      id
+    FROM
+      cameras
+    WHERE
+      cameras.name = exif_metas.camera_name
+  ),
+  lens_id = (
+    SELECT
+      id
+    FROM
+      lenses
+    WHERE
+      lenses.name = exif_metas.lens_name
+  );
@@ -0,0 +1,85 @@
+-- Dropping fuji_recipes would set every exif_metas.fuji_recipe_id to NULL, keep the links aside.
+CREATE TEMPORARY TABLE exif_metas_recipes AS
+SELECT
+  id,
+  fuji_recipe_id
+FROM
+  exif_metas
+WHERE
+  fuji_recipe_id IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS fuji_recipes_NEW (
+  id TEXT PRIMARY KEY NOT NULL,
+  name TEXT NOT NULL,
+  author TEXT NULL,
+  sensor TEXT NOT NULL,
+  src TEXT NULL,
+  film_simulation TEXT NOT NULL,
+  white_balance TEXT NOT NULL,
+  white_balance_shift TEXT NOT NULL,
+  dynamic_range TEXT NOT NULL,
+  d_range_priority TEXT NULL,
+  highlight_tone REAL NOT NULL,
+  shadow_tone REAL NOT NULL,
+  color INTEGER NOT NULL,
+  sharpness INTEGER NOT NULL,
+  clarity INTEGER NULL,
+  high_iso_noise_reduction INTEGER NOT NULL,
+  grain_strength TEXT NULL,
+  grain_size TEXT NULL,
+  color_chrome_effect TEXT NULL,
+  color_chrome_fx_blue TEXT NULL,
+  monochromatic_color TEXT NULL
+);
+
+INSERT INTO
+  fuji_recipes_NEW
+SELECT
+  id,
+  name,
+  NULLIF(author, 'todo'),
+  sensor,
+  NULLIF(src, 'todo'),
+  film_simulation,
+  white_balance,
+  white_balance_shift,
+  dynamic_range,
+  d_range_priority,
+  highlight_tone,
+  shadow_tone,
+  color,
+  sharpness,
+  clarity,
+  high_iso_noise_reduction,
+  grain_strength,
+  grain_size,
+  color_chrome_effect,
+  color_chrome_fx_blue,
+  monochromatic_color
+FROM
+  fuji_recipes;
+
+DROP TABLE fuji_recipes;
+
+ALTER TABLE fuji_recipes_NEW
+RENAME TO fuji_recipes;
+
+UPDATE exif_metas
+SET
+  fuji_recipe_id = (
+    SELECT
+      fuji_recipe_id
+    FROM
+      exif_metas_recipes r
+    WHERE
+      r.id = exif_metas.id
+  )
+WHERE
+  id IN (
+    SELECT
+      id
+    FROM
+      exif_metas_recipes
+  );
+
+DROP TABLE exif_metas_recipes;
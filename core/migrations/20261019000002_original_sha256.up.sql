@@ -0,0 +1 @@
+ALTER TABLE photos ADD COLUMN original_sha256 TEXT;
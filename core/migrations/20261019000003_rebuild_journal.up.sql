@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS rebuild_journal (
+  photo_id TEXT NOT NULL,
+  sizes TEXT NOT NULL,
+  completed_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  FOREIGN KEY (photo_id) REFERENCES photos (id) ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (photo_id, sizes)
+);
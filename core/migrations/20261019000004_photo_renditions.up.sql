@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS photo_renditions (
+  photo_id TEXT NOT NULL,
+  size TEXT NOT NULL,
+  kind TEXT NOT NULL,
+  quality INTEGER NOT NULL,
+  bytes INTEGER NOT NULL,
+  ssim REAL NOT NULL,
+  FOREIGN KEY (photo_id) REFERENCES photos (id) ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (photo_id, size, kind)
+);
@@ -0,0 +1 @@
+ALTER TABLE photos ADD COLUMN color_space TEXT;
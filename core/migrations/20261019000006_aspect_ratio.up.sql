@@ -0,0 +1 @@
+ALTER TABLE photos ADD COLUMN aspect_ratio REAL;
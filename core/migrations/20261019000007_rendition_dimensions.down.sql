@@ -0,0 +1,3 @@
+ALTER TABLE photo_renditions DROP COLUMN md5;
+ALTER TABLE photo_renditions DROP COLUMN height;
+ALTER TABLE photo_renditions DROP COLUMN width;
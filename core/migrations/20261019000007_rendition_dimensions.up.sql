@@ -0,0 +1,3 @@
+ALTER TABLE photo_renditions ADD COLUMN width INTEGER;
+ALTER TABLE photo_renditions ADD COLUMN height INTEGER;
+ALTER TABLE photo_renditions ADD COLUMN md5 TEXT;
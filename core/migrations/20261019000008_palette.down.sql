@@ -0,0 +1,2 @@
+ALTER TABLE photos DROP COLUMN dominant_color;
+ALTER TABLE photos DROP COLUMN palette;
@@ -0,0 +1,2 @@
+ALTER TABLE photos ADD COLUMN palette TEXT;
+ALTER TABLE photos ADD COLUMN dominant_color TEXT;
@@ -0,0 +1 @@
+ALTER TABLE photos ADD COLUMN perceptual_hash INTEGER;
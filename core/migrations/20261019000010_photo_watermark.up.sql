@@ -0,0 +1 @@
+ALTER TABLE photos ADD COLUMN watermark BOOLEAN NOT NULL DEFAULT true;
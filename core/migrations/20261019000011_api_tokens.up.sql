@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS api_tokens (
+  id TEXT PRIMARY KEY NOT NULL,
+  name TEXT NOT NULL,
+  token_hash TEXT NOT NULL UNIQUE,
+  scopes TEXT NOT NULL,
+  expires_at INTEGER,
+  created_at INTEGER NOT NULL
+);
@@ -0,0 +1,21 @@
+CREATE TABLE photo_renditions_new (
+  photo_id TEXT NOT NULL,
+  size TEXT NOT NULL,
+  kind TEXT NOT NULL,
+  quality INTEGER NOT NULL,
+  bytes INTEGER NOT NULL,
+  ssim REAL NOT NULL,
+  width INTEGER,
+  height INTEGER,
+  md5 TEXT,
+  FOREIGN KEY (photo_id) REFERENCES photos (id) ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (photo_id, size, kind)
+);
+
+-- Files that were never measured get a 0, the report lists them first.
+INSERT INTO photo_renditions_new (photo_id, size, kind, quality, bytes, ssim, width, height, md5)
+SELECT photo_id, size, kind, quality, bytes, COALESCE(ssim, 0), width, height, md5
+FROM photo_renditions;
+
+DROP TABLE photo_renditions;
+ALTER TABLE photo_renditions_new RENAME TO photo_renditions;
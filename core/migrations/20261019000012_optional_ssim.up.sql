@@ -0,0 +1,20 @@
+-- SQLite can't drop a NOT NULL constraint, the table is copied into a new one.
+CREATE TABLE photo_renditions_new (
+  photo_id TEXT NOT NULL,
+  size TEXT NOT NULL,
+  kind TEXT NOT NULL,
+  quality INTEGER NOT NULL,
+  bytes INTEGER NOT NULL,
+  ssim REAL,
+  width INTEGER,
+  height INTEGER,
+  md5 TEXT,
+  FOREIGN KEY (photo_id) REFERENCES photos (id) ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (photo_id, size, kind)
+);
+
+INSERT INTO photo_renditions_new (photo_id, size, kind, quality, bytes, ssim, width, height, md5)
+SELECT photo_id, size, kind, quality, bytes, ssim, width, height, md5 FROM photo_renditions;
+
+DROP TABLE photo_renditions;
+ALTER TABLE photo_renditions_new RENAME TO photo_renditions;
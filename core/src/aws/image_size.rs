@@ -1,26 +1,44 @@
+use super::rendition::{RENDITIONS, Rendition, find_rendition};
 use snafu::Snafu;
+use std::fmt;
 use std::str::FromStr;
 use strum_macros::Display;
 
-#[derive(Debug, Display, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ImageSize {
-    HdPlus,
-    Hd,
-    Md,
-    Sm,
+    /// One of the sizes built from the original, see [`RENDITIONS`].
+    Rendition(&'static Rendition),
+    /// The file as it came out of the camera, kept as the master every other size is built from.
+    Original,
+}
+
+impl ImageSize {
+    /// Every size served to visitors, the original is not included.
+    pub fn renditions() -> impl Iterator<Item = ImageSize> {
+        RENDITIONS.iter().map(ImageSize::Rendition)
+    }
+}
+
+impl fmt::Display for ImageSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageSize::Rendition(rendition) => write!(f, "{}", rendition.name),
+            ImageSize::Original => write!(f, "Original"),
+        }
+    }
 }
 
 impl FromStr for ImageSize {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "hdplus" => Ok(ImageSize::HdPlus),
-            "hd" => Ok(ImageSize::Hd),
-            "md" => Ok(ImageSize::Md),
-            "sm" => Ok(ImageSize::Sm),
-            _ => Err(Error::Invalid),
+        if s.eq_ignore_ascii_case("original") {
+            return Ok(ImageSize::Original);
         }
+
+        find_rendition(s)
+            .map(ImageSize::Rendition)
+            .ok_or(Error::Invalid)
     }
 }
 
@@ -48,3 +66,34 @@ pub enum Error {
     #[snafu(display("Invalid string"))]
     Invalid,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_every_rendition() {
+        for rendition in RENDITIONS {
+            let size = ImageSize::from_str(rendition.name).unwrap();
+            assert_eq!(size, ImageSize::Rendition(rendition));
+            assert_eq!(size.to_string(), rendition.name);
+        }
+    }
+
+    #[test]
+    fn it_parses_ignoring_case() {
+        assert_eq!(
+            ImageSize::from_str("hdplus").unwrap(),
+            ImageSize::from_str("HdPlus").unwrap()
+        );
+        assert_eq!(
+            ImageSize::from_str("ORIGINAL").unwrap(),
+            ImageSize::Original
+        );
+    }
+
+    #[test]
+    fn it_fails_on_unknown_sizes() {
+        assert!(ImageSize::from_str("xl").is_err());
+    }
+}
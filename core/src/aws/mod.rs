@@ -1,5 +1,6 @@
 pub mod image_size;
 pub mod photo;
+pub mod rendition;
 
 use aws_sdk_s3::Client;
 pub use aws_sdk_s3::primitives::ByteStream;
@@ -22,4 +23,14 @@ impl S3 {
             bucket_name: bucket_name.to_string(),
         }
     }
+
+    /// Whether the bucket can be reached with the credentials of the environment.
+    pub async fn is_reachable(&self) -> bool {
+        self.client
+            .head_bucket()
+            .bucket(&self.bucket_name)
+            .send()
+            .await
+            .is_ok()
+    }
 }
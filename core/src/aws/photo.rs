@@ -6,6 +6,7 @@ use aws_sdk_s3::{
     operation::{
         delete_object::{DeleteObjectError, DeleteObjectOutput},
         get_object::{GetObjectError, GetObjectOutput},
+        head_object::{HeadObjectError, HeadObjectOutput},
         put_object::{PutObjectError, PutObjectOutput},
     },
 };
@@ -44,6 +45,20 @@ impl S3 {
             .context(DownloadSnafu)
     }
 
+    /// Metadata of the stored object, without downloading it.
+    pub async fn head_from_aws_s3(
+        &self,
+        data: (&Photo, &ImageSize, &ImageType),
+    ) -> Result<HeadObjectOutput, Error> {
+        self.client
+            .head_object()
+            .bucket(&self.bucket_name)
+            .key(key(data))
+            .send()
+            .await
+            .context(HeadSnafu)
+    }
+
     pub async fn remove_from_aws_s3(
         &self,
         data: (&Photo, &ImageSize, &ImageType),
@@ -59,6 +74,11 @@ impl S3 {
 }
 
 fn key((photo, size, photo_type): (&Photo, &ImageSize, &ImageType)) -> String {
+    // Originals live apart from the renditions, they are never served and only one exists.
+    if size == &ImageSize::Original {
+        return format!("originals/{}", photo.id);
+    }
+
     if photo_type == &ImageType::Webp {
         return format!("{}_{}_{}", photo.id, photo_type, size);
     }
@@ -66,6 +86,19 @@ fn key((photo, size, photo_type): (&Photo, &ImageSize, &ImageType)) -> String {
     format!("{}_{}", photo.id, size)
 }
 
+impl Error {
+    /// Whether the object isn't in the bucket, as opposed to S3 failing to answer.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Error::Download { source } => source
+                .as_service_error()
+                .is_some_and(|e| e.is_no_such_key()),
+            Error::Head { source } => source.as_service_error().is_some_and(|e| e.is_not_found()),
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug, Snafu)]
 pub enum Error {
     #[snafu(display("Failed to upload file: {}", source))]
@@ -74,6 +107,9 @@ pub enum Error {
     #[snafu(display("Failed to download file: {}", source))]
     Download { source: SdkError<GetObjectError> },
 
+    #[snafu(display("Failed to get the file metadata: {}", source))]
+    Head { source: SdkError<HeadObjectError> },
+
     #[snafu(display("Failed to remove file: {}", source))]
     Remove { source: SdkError<DeleteObjectError> },
 }
@@ -0,0 +1,119 @@
+use strum_macros::{Display, EnumString};
+
+/// Every size the pipeline builds for a photo. The CLI builds, uploads and removes each one of
+/// them, and the API serves and warms its cache from the same list, so adding a new size only
+/// needs a new entry here.
+///
+/// The name is part of the S3 key and of the `/v1/images/<size>/<id>` URL, renaming an existing
+/// entry orphans every object that was already uploaded with it.
+///
+/// Only the sizes big enough to be worth reposting carry the watermark.
+pub const RENDITIONS: &[Rendition] = &[
+    Rendition {
+        name: "HdPlus",
+        short_edge: 1440,
+        jpeg_quality: 85,
+        webp_quality: 85,
+        sharpen: None,
+        target_ssim: None,
+        watermark: true,
+    },
+    Rendition {
+        name: "Hd",
+        short_edge: 1080,
+        jpeg_quality: 75,
+        webp_quality: 75,
+        sharpen: None,
+        target_ssim: None,
+        watermark: true,
+    },
+    Rendition {
+        name: "Md",
+        short_edge: 720,
+        jpeg_quality: 75,
+        webp_quality: 75,
+        sharpen: None,
+        target_ssim: None,
+        watermark: false,
+    },
+    Rendition {
+        name: "Sm",
+        short_edge: 480,
+        jpeg_quality: 70,
+        webp_quality: 70,
+        sharpen: None,
+        target_ssim: None,
+        watermark: false,
+    },
+];
+
+#[derive(Debug, PartialEq)]
+pub struct Rendition {
+    pub name: &'static str,
+    /// Length in pixels of the short side, the height in landscape and the width in portrait.
+    pub short_edge: u32,
+    /// JPEG quality, from 1 to 100.
+    pub jpeg_quality: u8,
+    /// Lossy WEBP quality, from 0 to 100.
+    pub webp_quality: u8,
+    /// Unsharp mask applied after resizing, `None` leaves the resized image untouched.
+    pub sharpen: Option<Sharpen>,
+    /// When set, the qualities above become an upper bound and each photo is encoded with the
+    /// lowest quality that still reaches this SSIM against the resized image, e.g. `0.95`.
+    pub target_ssim: Option<f64>,
+    /// Whether the watermark is drawn on this size, see [`WATERMARK`].
+    pub watermark: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Sharpen {
+    /// Blur radius of the mask.
+    pub sigma: f32,
+    /// Minimum brightness difference for a pixel to be sharpened.
+    pub threshold: i32,
+}
+
+/// How the watermark is drawn on the sizes that have it. The mark itself is a PNG or SVG file set
+/// in the CLI environment.
+pub const WATERMARK: WatermarkSettings = WatermarkSettings {
+    position: WatermarkPosition::BottomRight,
+    opacity: 0.6,
+    scale: 0.12,
+    margin: 0.03,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WatermarkSettings {
+    pub position: WatermarkPosition,
+    /// From 0, invisible, to 1, as opaque as the mark itself.
+    pub opacity: f32,
+    /// Length of the long side of the mark, relative to the short edge of the rendition.
+    pub scale: f32,
+    /// Distance to the closest edges, relative to the short edge of the rendition.
+    pub margin: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumString, Display)]
+pub enum WatermarkPosition {
+    #[strum(serialize = "top-left")]
+    TopLeft,
+    #[strum(serialize = "top-right")]
+    TopRight,
+    #[strum(serialize = "bottom-left")]
+    BottomLeft,
+    #[strum(serialize = "bottom-right")]
+    BottomRight,
+    #[strum(serialize = "center")]
+    Center,
+}
+
+pub fn find_rendition(name: &str) -> Option<&'static Rendition> {
+    RENDITIONS
+        .iter()
+        .find(|r| r.name.eq_ignore_ascii_case(name))
+}
+
+/// Every rendition, for the callers that can also build only some of them.
+pub fn all_renditions() -> Vec<&'static Rendition> {
+    RENDITIONS.iter().collect()
+}
@@ -4,6 +4,7 @@ extern crate log;
 pub mod aws;
 pub mod db;
 pub mod models;
+pub mod telemetry;
 pub mod utils;
 
 pub use sqlx;
@@ -0,0 +1,180 @@
+use super::{ApiToken, Scope};
+use snafu::prelude::*;
+use sqlx::{Error as SqlxError, FromRow, SqliteConnection};
+use std::str::FromStr;
+use strum::ParseError;
+use time::OffsetDateTime;
+
+#[derive(FromRow)]
+struct DBApiToken {
+    pub id: String,
+    pub name: String,
+    pub token_hash: String,
+    /// Comma separated.
+    pub scopes: String,
+    /// Unix seconds.
+    pub expires_at: Option<i64>,
+    pub created_at: i64,
+}
+
+impl ApiToken {
+    /// Every token, oldest first.
+    pub async fn find_all(conn: &mut SqliteConnection) -> Result<Vec<ApiToken>, Error> {
+        let tokens = sqlx::query_as!(
+            DBApiToken,
+            r#"
+    SELECT
+        id,
+        name,
+        token_hash,
+        scopes,
+        expires_at,
+        created_at
+    FROM
+        api_tokens
+    ORDER BY
+        created_at ASC
+    "#
+        )
+        .fetch_all(conn)
+        .await
+        .context(SqlxSnafu)?;
+
+        tokens.into_iter().map(|t| t.try_into()).collect()
+    }
+
+    /// The token with the given secret, `None` when it doesn't exist or was revoked. Expired
+    /// tokens are still returned.
+    pub async fn find_by_secret(
+        conn: &mut SqliteConnection,
+        secret: &str,
+    ) -> Result<Option<ApiToken>, Error> {
+        let token_hash = ApiToken::hash(secret);
+
+        let token = sqlx::query_as!(
+            DBApiToken,
+            r#"
+    SELECT
+        id,
+        name,
+        token_hash,
+        scopes,
+        expires_at,
+        created_at
+    FROM
+        api_tokens
+    WHERE
+        token_hash = ?
+    "#,
+            token_hash
+        )
+        .fetch_optional(conn)
+        .await
+        .context(SqlxSnafu)?;
+
+        token.map(|t| t.try_into()).transpose()
+    }
+
+    pub async fn save(&self, conn: &mut SqliteConnection) -> Result<(), Error> {
+        let token: DBApiToken = self.into();
+
+        sqlx::query!(
+            r#"
+    INSERT INTO api_tokens (id, name, token_hash, scopes, expires_at, created_at)
+    VALUES (?, ?, ?, ?, ?, ?)
+    "#,
+            token.id,
+            token.name,
+            token.token_hash,
+            token.scopes,
+            token.expires_at,
+            token.created_at,
+        )
+        .execute(conn)
+        .await
+        .context(SqlxSnafu)?;
+
+        Ok(())
+    }
+
+    /// Deletes the token, requests using it are rejected from now on.
+    pub async fn revoke(conn: &mut SqliteConnection, id: &str) -> Result<(), Error> {
+        let result = sqlx::query!(
+            r#"
+    DELETE FROM api_tokens
+    WHERE id = ?
+    "#,
+            id
+        )
+        .execute(conn)
+        .await
+        .context(SqlxSnafu)?;
+
+        ensure!(
+            result.rows_affected() > 0,
+            NotFoundSnafu { id: id.to_string() }
+        );
+
+        Ok(())
+    }
+}
+
+impl TryFrom<DBApiToken> for ApiToken {
+    type Error = Error;
+
+    fn try_from(value: DBApiToken) -> Result<Self, Self::Error> {
+        let scopes = value
+            .scopes
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(|s| Scope::from_str(s).context(ScopeSnafu { value: s }))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let expires_at = value
+            .expires_at
+            .map(OffsetDateTime::from_unix_timestamp)
+            .transpose()
+            .context(TimestampSnafu)?;
+        let created_at =
+            OffsetDateTime::from_unix_timestamp(value.created_at).context(TimestampSnafu)?;
+
+        Ok(ApiToken {
+            id: value.id,
+            name: value.name,
+            token_hash: value.token_hash,
+            scopes,
+            expires_at,
+            created_at,
+        })
+    }
+}
+
+impl From<&ApiToken> for DBApiToken {
+    fn from(token: &ApiToken) -> Self {
+        let scopes: Vec<String> = token.scopes.iter().map(|s| s.to_string()).collect();
+
+        DBApiToken {
+            id: token.id.clone(),
+            name: token.name.clone(),
+            token_hash: token.token_hash.clone(),
+            scopes: scopes.join(","),
+            expires_at: token.expires_at.map(|e| e.unix_timestamp()),
+            created_at: token.created_at.unix_timestamp(),
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to execute query: {:?}", source))]
+    Sqlx { source: SqlxError },
+
+    #[snafu(display("Token {} does not exist", id))]
+    NotFound { id: String },
+
+    #[snafu(display("Unknown scope {}: {}", value, source))]
+    Scope { value: String, source: ParseError },
+
+    #[snafu(display("Invalid timestamp: {}", source))]
+    Timestamp { source: time::error::ComponentRange },
+}
@@ -0,0 +1,138 @@
+pub mod db;
+
+use sha2::{Digest, Sha256};
+use strum_macros::{Display, EnumString};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Prefix of every secret, so a leaked token is easy to recognise.
+const PREFIX: &str = "vhqc_";
+
+/// A credential for the write operations of the API. Only the SHA-256 of the secret is stored,
+/// the secret itself is shown once when the token is created.
+#[derive(Clone, Debug)]
+pub struct ApiToken {
+    pub id: String,
+    /// What the token is for, e.g. the machine using it.
+    pub name: String,
+    pub token_hash: String,
+    pub scopes: Vec<Scope>,
+    /// `None` for tokens that never expire.
+    pub expires_at: Option<OffsetDateTime>,
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Display)]
+pub enum Scope {
+    #[strum(serialize = "read")]
+    Read,
+    #[strum(serialize = "write:photos")]
+    WritePhotos,
+    #[strum(serialize = "write:tags")]
+    WriteTags,
+    /// Grants every other scope.
+    #[strum(serialize = "admin")]
+    Admin,
+}
+
+impl ApiToken {
+    /// A new token and its secret, the only time the secret is known.
+    pub fn generate(
+        name: String,
+        scopes: Vec<Scope>,
+        expires_at: Option<OffsetDateTime>,
+    ) -> (ApiToken, String) {
+        // Two v4 UUIDs give 244 random bits.
+        let secret = format!(
+            "{}{}{}",
+            PREFIX,
+            Uuid::new_v4().simple(),
+            Uuid::new_v4().simple()
+        );
+
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let token = ApiToken {
+            id: Uuid::new_v4().to_string(),
+            name,
+            token_hash: ApiToken::hash(&secret),
+            scopes,
+            expires_at,
+            created_at: OffsetDateTime::from_unix_timestamp(now).unwrap(),
+        };
+
+        (token, secret)
+    }
+
+    /// Hex encoded SHA-256 of a secret. The secrets are random enough to not need a salt.
+    pub fn hash(secret: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(secret.as_bytes());
+
+        format!("{:x}", hasher.finalize())
+    }
+
+    pub fn is_expired(&self, now: OffsetDateTime) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// Whether the token grants the scope. Every token can read, and admin tokens can do anything.
+    pub fn allows(&self, scope: Scope) -> bool {
+        scope == Scope::Read
+            || self
+                .scopes
+                .iter()
+                .any(|s| *s == Scope::Admin || *s == scope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use time::Duration;
+
+    #[test]
+    fn it_generates_unique_secrets() {
+        let (token, secret) = ApiToken::generate("ci".to_string(), vec![Scope::Read], None);
+        let (_, other) = ApiToken::generate("ci".to_string(), vec![Scope::Read], None);
+
+        assert!(secret.starts_with("vhqc_"));
+        assert_eq!(secret.len(), 69);
+        assert_ne!(secret, other);
+        assert_eq!(token.token_hash, ApiToken::hash(&secret));
+        assert_ne!(token.token_hash, secret);
+    }
+
+    #[test]
+    fn it_parses_scopes() {
+        assert_eq!(Scope::from_str("write:photos").unwrap(), Scope::WritePhotos);
+        assert_eq!(Scope::from_str("admin").unwrap(), Scope::Admin);
+        assert_eq!(Scope::WriteTags.to_string(), "write:tags");
+        assert!(Scope::from_str("write").is_err());
+    }
+
+    #[test]
+    fn it_checks_scopes() {
+        let (token, _) = ApiToken::generate("ci".to_string(), vec![Scope::WritePhotos], None);
+
+        assert!(token.allows(Scope::Read));
+        assert!(token.allows(Scope::WritePhotos));
+        assert!(!token.allows(Scope::WriteTags));
+        assert!(!token.allows(Scope::Admin));
+
+        let (admin, _) = ApiToken::generate("ci".to_string(), vec![Scope::Admin], None);
+        assert!(admin.allows(Scope::WriteTags));
+    }
+
+    #[test]
+    fn it_expires() {
+        let now = OffsetDateTime::now_utc();
+        let (token, _) = ApiToken::generate("ci".to_string(), vec![], Some(now));
+
+        assert!(token.is_expired(now));
+        assert!(!token.is_expired(now - Duration::seconds(1)));
+
+        let (token, _) = ApiToken::generate("ci".to_string(), vec![], None);
+        assert!(!token.is_expired(now));
+    }
+}
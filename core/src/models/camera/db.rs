@@ -0,0 +1,193 @@
+use super::Camera;
+use crate::models::Timestamp;
+use crate::models::exif_meta::{CameraMaker, str::maker::Error as MakerError};
+use snafu::prelude::*;
+use sqlx::{Error as SqlxError, FromRow, SqliteConnection};
+use std::str::FromStr;
+use time::OffsetDateTime;
+
+#[derive(FromRow)]
+struct DBCamera {
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+    pub maker: String,
+    pub mount: Option<String>,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+impl Camera {
+    pub async fn find_by_name(conn: &mut SqliteConnection, name: &str) -> Result<Camera, Error> {
+        find_by_name(conn, name).await
+    }
+
+    pub async fn find_by_name_or_create(
+        conn: &mut SqliteConnection,
+        name: &str,
+        maker: CameraMaker,
+    ) -> Result<Camera, Error> {
+        let camera = Camera::find_by_name(conn, name).await;
+
+        match camera {
+            Ok(camera) => Ok(camera),
+            Err(e) => match e {
+                Error::NotFound { .. } => {
+                    let camera = Camera::new(name.to_string(), maker);
+                    camera.save(conn).await?;
+
+                    Ok(camera)
+                }
+                _ => Err(e),
+            },
+        }
+    }
+
+    pub async fn find_all(conn: &mut SqliteConnection) -> Result<Vec<Camera>, Error> {
+        find_all(conn).await
+    }
+
+    pub async fn save(&self, conn: &mut SqliteConnection) -> Result<String, Error> {
+        let camera: DBCamera = self.into();
+        insert(conn, &camera).await
+    }
+}
+
+async fn find_by_name(conn: &mut SqliteConnection, name: &str) -> Result<Camera, Error> {
+    let camera = sqlx::query_as!(
+        DBCamera,
+        r#"
+    SELECT
+        id,
+        name,
+        display_name,
+        maker,
+        mount,
+        created_at,
+        updated_at
+    FROM
+        cameras
+    WHERE
+        name = ?
+    "#,
+        name
+    )
+    .fetch_optional(conn)
+    .await
+    .context(SqlxSnafu)?;
+
+    let camera = camera.context(NotFoundSnafu {
+        name: name.to_string(),
+    })?;
+
+    camera.try_into()
+}
+
+async fn find_all(conn: &mut SqliteConnection) -> Result<Vec<Camera>, Error> {
+    let cameras = sqlx::query_as!(
+        DBCamera,
+        r#"
+    SELECT
+        id,
+        name,
+        display_name,
+        maker,
+        mount,
+        created_at,
+        updated_at
+    FROM
+        cameras
+    ORDER BY
+        display_name ASC
+    "#
+    )
+    .fetch_all(conn)
+    .await
+    .context(SqlxSnafu)?;
+
+    let cameras: Vec<Camera> = cameras.into_iter().map(|c| c.try_into().unwrap()).collect();
+
+    Ok(cameras)
+}
+
+async fn insert(conn: &mut SqliteConnection, camera: &DBCamera) -> Result<String, Error> {
+    let id = camera.id.clone();
+
+    sqlx::query!(
+        r#"
+    INSERT INTO cameras(id, name, display_name, maker, mount, created_at, updated_at)
+    VALUES (?, ?, ?, ?, ?, ?, ?)
+    "#,
+        camera.id,
+        camera.name,
+        camera.display_name,
+        camera.maker,
+        camera.mount,
+        camera.created_at,
+        camera.updated_at,
+    )
+    .execute(conn)
+    .await
+    .context(SqlxSnafu)?;
+
+    Ok(id)
+}
+
+impl TryFrom<DBCamera> for Camera {
+    type Error = Error;
+
+    fn try_from(value: DBCamera) -> Result<Self, Self::Error> {
+        let maker = CameraMaker::from_str(&value.maker).context(MakerSnafu)?;
+
+        let created_at = {
+            let timestamp = value.created_at.0 / 1000;
+
+            OffsetDateTime::from_unix_timestamp(timestamp).context(TimestampSnafu)?
+        };
+
+        let updated_at = {
+            let timestamp = value.updated_at.0 / 1000;
+
+            OffsetDateTime::from_unix_timestamp(timestamp).context(TimestampSnafu)?
+        };
+
+        Ok(Camera {
+            id: value.id,
+            name: value.name,
+            display_name: value.display_name,
+            maker,
+            mount: value.mount,
+            created_at,
+            updated_at,
+        })
+    }
+}
+
+impl From<&Camera> for DBCamera {
+    fn from(value: &Camera) -> Self {
+        DBCamera {
+            id: value.id.clone(),
+            name: value.name.clone(),
+            display_name: value.display_name.clone(),
+            maker: value.maker.to_string(),
+            mount: value.mount.clone(),
+            created_at: value.created_at.into(),
+            updated_at: value.updated_at.into(),
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to execute query: {:?}", source))]
+    Sqlx { source: SqlxError },
+
+    #[snafu(display("Failed to parse timestamp: {:?}", source))]
+    Timestamp { source: time::error::ComponentRange },
+
+    #[snafu(display("Failed to parse Camera Maker {:?}", source))]
+    Maker { source: MakerError },
+
+    #[snafu(display("Could not find camera: {}", name))]
+    NotFound { name: String },
+}
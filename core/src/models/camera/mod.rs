@@ -0,0 +1,49 @@
+pub mod db;
+
+use crate::models::exif_meta::CameraMaker;
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Camera {
+    pub id: String,
+    /// Model name as reported by the EXIF data, e.g. `X-T5`.
+    pub name: String,
+    pub display_name: String,
+    pub maker: CameraMaker,
+    pub mount: Option<String>,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
+
+impl Camera {
+    pub fn new(name: String, maker: CameraMaker) -> Self {
+        let id = Uuid::new_v4().to_string();
+
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let created_at = OffsetDateTime::from_unix_timestamp(now).unwrap();
+        let updated_at = OffsetDateTime::from_unix_timestamp(now).unwrap();
+
+        let display_name = format!("{} {}", maker, name);
+        let mount = mount_from_name(&maker, &name);
+
+        Camera {
+            id,
+            name,
+            display_name,
+            maker,
+            mount,
+            created_at,
+            updated_at,
+        }
+    }
+}
+
+fn mount_from_name(maker: &CameraMaker, name: &str) -> Option<String> {
+    match maker {
+        CameraMaker::Fujifilm if name.starts_with("GFX") => Some("G".to_string()),
+        CameraMaker::Fujifilm => Some("X".to_string()),
+        _ => None,
+    }
+}
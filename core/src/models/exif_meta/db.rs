@@ -26,6 +26,8 @@ struct DBExifMeta {
     pub crop_factor: f64,
     pub photo_id: String,
     pub fuji_recipe_id: Option<String>,
+    pub camera_id: Option<String>,
+    pub lens_id: Option<String>,
 }
 
 impl ExifMeta {
@@ -74,7 +76,9 @@ async fn find_by_id(conn: &mut SqliteConnection, id: &str) -> Result<ExifMeta, E
         lens_name,
         crop_factor,
         photo_id,
-        fuji_recipe_id
+        fuji_recipe_id,
+        camera_id,
+        lens_id
     FROM
         exif_metas
     WHERE
@@ -113,7 +117,9 @@ async fn find_by_photo_ids(
         lens_name,
         crop_factor,
         photo_id,
-        fuji_recipe_id
+        fuji_recipe_id,
+        camera_id,
+        lens_id
     FROM
         exif_metas
     WHERE
@@ -170,9 +176,11 @@ async fn insert(conn: &mut SqliteConnection, exif: &DBExifMeta) -> Result<String
         lens_name,
         crop_factor,
         photo_id,
-        fuji_recipe_id
+        fuji_recipe_id,
+        camera_id,
+        lens_id
     )
-    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
     "#,
         exif.id,
         exif.rating,
@@ -190,6 +198,8 @@ async fn insert(conn: &mut SqliteConnection, exif: &DBExifMeta) -> Result<String
         exif.crop_factor,
         exif.photo_id,
         exif.fuji_recipe_id,
+        exif.camera_id,
+        exif.lens_id,
     )
     .execute(conn)
     .await
@@ -211,6 +221,8 @@ impl TryFrom<DBExifMeta> for ExifMeta {
             id: value.id,
             photo_id: value.photo_id,
             fuji_recipe_id: value.fuji_recipe_id,
+            camera_id: value.camera_id,
+            lens_id: value.lens_id,
             details: PhotographyDetails {
                 rating: Rating(value.rating as i8),
                 city,
@@ -242,6 +254,8 @@ impl From<&ExifMeta> for DBExifMeta {
             id: exif.id.clone(),
             photo_id: exif.photo_id.clone(),
             fuji_recipe_id: exif.fuji_recipe_id.clone(),
+            camera_id: exif.camera_id.clone(),
+            lens_id: exif.lens_id.clone(),
             rating: exif.details.rating.0 as i64,
             date_taken,
             city,
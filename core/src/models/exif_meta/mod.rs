@@ -2,7 +2,9 @@ pub mod db;
 pub mod from_exif;
 pub mod str;
 
+use crate::models::camera::Camera;
 use crate::models::fujifilm::FujifilmRecipe;
+use crate::models::lens::Lens;
 use crate::models::photo::Photo;
 use serde::{Deserialize, Serialize};
 use strum_macros::Display as EnumDisplay;
@@ -14,6 +16,8 @@ pub struct ExifMeta {
     pub id: String,
     pub photo_id: String,
     pub fuji_recipe_id: Option<String>,
+    pub camera_id: Option<String>,
+    pub lens_id: Option<String>,
     pub details: PhotographyDetails,
 }
 
@@ -22,14 +26,19 @@ impl ExifMeta {
         details: PhotographyDetails,
         photo: &Photo,
         recipe: &Option<FujifilmRecipe>,
+        camera: &Camera,
+        lens: &Option<Lens>,
     ) -> Self {
         let id = Uuid::new_v4().to_string();
         let fuji_recipe_id: Option<String> = recipe.clone().map(|r| r.id);
+        let lens_id: Option<String> = lens.as_ref().map(|l| l.id.clone());
 
         ExifMeta {
             id,
             photo_id: photo.id.clone(),
             fuji_recipe_id,
+            camera_id: Some(camera.id.clone()),
+            lens_id,
             details,
         }
     }
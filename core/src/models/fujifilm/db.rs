@@ -18,8 +18,8 @@ use strum_macros::Display;
 pub struct DBFujifilmRecipe {
     pub id: String,
     pub name: String,
-    pub author: String,
-    pub src: String,
+    pub author: Option<String>,
+    pub src: Option<String>,
     pub sensor: String,
     pub film_simulation: String,
     pub white_balance: String,
@@ -44,8 +44,8 @@ pub struct DBExifMetaFujifilmRecipe {
     pub exif_meta_id: String,
     pub id: String,
     pub name: String,
-    pub author: String,
-    pub src: String,
+    pub author: Option<String>,
+    pub src: Option<String>,
     pub sensor: String,
     pub film_simulation: String,
     pub white_balance: String,
@@ -80,10 +80,32 @@ impl FujifilmRecipe {
         find_by_exif_meta_ids(conn, ids).await
     }
 
+    pub async fn find_all(conn: &mut SqliteConnection) -> Result<Vec<FujifilmRecipe>, Error> {
+        find_all(conn).await
+    }
+
+    pub async fn find_by_id(
+        conn: &mut SqliteConnection,
+        id: &str,
+    ) -> Result<Option<FujifilmRecipe>, Error> {
+        find_by_id(conn, id).await
+    }
+
     pub async fn find_by_details(
         conn: &mut SqliteConnection,
         details: &FujifilmRecipeDetails,
     ) -> Result<Option<FujifilmRecipe>, Error> {
+        let recipes = find_by_recipe_details(conn, details).await?;
+
+        Ok(recipes.into_iter().next())
+    }
+
+    /// All the recipes sharing the same settings, in the order they were created. There should be
+    /// only one, but older versions could store the same settings more than once.
+    pub async fn find_all_by_details(
+        conn: &mut SqliteConnection,
+        details: &FujifilmRecipeDetails,
+    ) -> Result<Vec<FujifilmRecipe>, Error> {
         find_by_recipe_details(conn, details).await
     }
 
@@ -92,6 +114,118 @@ impl FujifilmRecipe {
 
         insert(conn, recipe).await
     }
+
+    /// Updates the name, author and source of the recipe. The settings are what identifies a
+    /// recipe, so they never change.
+    pub async fn update(&self, conn: &mut SqliteConnection) -> Result<(), Error> {
+        let recipe: DBFujifilmRecipe = self.into();
+
+        update(conn, recipe).await
+    }
+
+    /// Points the `exif_metas` of the duplicated recipes to this one and removes the duplicates.
+    /// Returns how many `exif_metas` were re-linked.
+    pub async fn merge_duplicates(
+        &self,
+        conn: &mut SqliteConnection,
+        duplicates: &[FujifilmRecipe],
+    ) -> Result<u64, Error> {
+        let ids: Vec<String> = duplicates
+            .iter()
+            .filter(|d| d.id != self.id)
+            .map(|d| d.id.clone())
+            .collect();
+
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        merge_duplicates(conn, &self.id, &ids).await
+    }
+}
+
+async fn find_all(conn: &mut SqliteConnection) -> Result<Vec<FujifilmRecipe>, Error> {
+    let recipes = sqlx::query_as!(
+        DBFujifilmRecipe,
+        r#"
+    SELECT
+        id,
+        name,
+        author,
+        sensor,
+        src,
+        film_simulation,
+        white_balance,
+        white_balance_shift,
+        dynamic_range,
+        d_range_priority,
+        highlight_tone,
+        shadow_tone,
+        color,
+        sharpness,
+        clarity,
+        high_iso_noise_reduction,
+        grain_strength,
+        grain_size,
+        color_chrome_effect,
+        color_chrome_fx_blue,
+        monochromatic_color
+    FROM
+        fuji_recipes
+    ORDER BY
+        name ASC
+    "#
+    )
+    .fetch_all(conn)
+    .await
+    .context(SqlxSnafu)?;
+
+    let recipes = recipes.into_iter().map(|r| r.try_into().unwrap()).collect();
+
+    Ok(recipes)
+}
+
+async fn find_by_id(
+    conn: &mut SqliteConnection,
+    id: &str,
+) -> Result<Option<FujifilmRecipe>, Error> {
+    let recipe = sqlx::query_as!(
+        DBFujifilmRecipe,
+        r#"
+    SELECT
+        id,
+        name,
+        author,
+        sensor,
+        src,
+        film_simulation,
+        white_balance,
+        white_balance_shift,
+        dynamic_range,
+        d_range_priority,
+        highlight_tone,
+        shadow_tone,
+        color,
+        sharpness,
+        clarity,
+        high_iso_noise_reduction,
+        grain_strength,
+        grain_size,
+        color_chrome_effect,
+        color_chrome_fx_blue,
+        monochromatic_color
+    FROM
+        fuji_recipes
+    WHERE
+        id = ?
+    "#,
+        id,
+    )
+    .fetch_optional(conn)
+    .await
+    .context(SqlxSnafu)?;
+
+    recipe.map(|r| r.try_into()).transpose()
 }
 
 async fn find_by_film_simulation(
@@ -226,7 +360,7 @@ async fn find_by_exif_meta_ids(
 async fn find_by_recipe_details(
     conn: &mut SqliteConnection,
     details: &FujifilmRecipeDetails,
-) -> Result<Option<FujifilmRecipe>, Error> {
+) -> Result<Vec<FujifilmRecipe>, Error> {
     let (
         white_balance,
         dynamic_range,
@@ -258,6 +392,8 @@ async fn find_by_recipe_details(
     let color_chrome_fx_blue = color_chrome_fx_blue.map(|c| c.to_string());
     let monochromatic_color = monochromatic_color.map(|c| c.to_string());
 
+    // Optional settings are compared with `IS`, with `=` a NULL never matches and the recipe would
+    // be stored again.
     let query = sqlx::query_as!(
         DBFujifilmRecipe,
         r#"
@@ -288,21 +424,23 @@ async fn find_by_recipe_details(
     WHERE
         film_simulation = ?
         AND sensor = ?
-        AND white_balance =?
+        AND white_balance = ?
         AND white_balance_shift = ?
         AND dynamic_range = ?
-        AND d_range_priority = ?
+        AND d_range_priority IS ?
         AND highlight_tone = ?
         AND shadow_tone = ?
         AND color = ?
         AND sharpness = ?
-        AND clarity = ?
+        AND clarity IS ?
         AND high_iso_noise_reduction = ?
-        AND grain_strength = ?
-        AND grain_size = ?
-        AND color_chrome_effect = ?
-        AND color_chrome_fx_blue = ?
-        AND monochromatic_color = ?
+        AND grain_strength IS ?
+        AND grain_size IS ?
+        AND color_chrome_effect IS ?
+        AND color_chrome_fx_blue IS ?
+        AND monochromatic_color IS ?
+    ORDER BY
+        rowid ASC
     "#,
         film_simulation,
         sensor,
@@ -323,15 +461,14 @@ async fn find_by_recipe_details(
         monochromatic_color,
     );
 
-    let recipe = query.fetch_optional(conn).await.context(SqlxSnafu)?;
+    let recipes = query.fetch_all(conn).await.context(SqlxSnafu)?;
 
-    let recipe = if let Some(r) = recipe.map(|r| r.try_into()) {
-        Some(r?)
-    } else {
-        None
-    };
+    let recipes = recipes
+        .into_iter()
+        .map(|r| r.try_into())
+        .collect::<Result<Vec<FujifilmRecipe>, Error>>()?;
 
-    Ok(recipe)
+    Ok(recipes)
 }
 
 async fn insert(conn: &mut SqliteConnection, recipe: DBFujifilmRecipe) -> Result<String, Error> {
@@ -393,6 +530,79 @@ async fn insert(conn: &mut SqliteConnection, recipe: DBFujifilmRecipe) -> Result
     Ok(id)
 }
 
+async fn update(conn: &mut SqliteConnection, recipe: DBFujifilmRecipe) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+    UPDATE fuji_recipes
+    SET
+        name = ?,
+        author = ?,
+        src = ?
+    WHERE
+        id = ?
+    "#,
+        recipe.name,
+        recipe.author,
+        recipe.src,
+        recipe.id,
+    )
+    .execute(conn)
+    .await
+    .context(SqlxSnafu)?;
+
+    Ok(())
+}
+
+async fn merge_duplicates(
+    conn: &mut SqliteConnection,
+    id: &str,
+    duplicates: &Vec<String>,
+) -> Result<u64, Error> {
+    let params = format!("?{}", ", ?".repeat(duplicates.len() - 1));
+
+    let query = format!(
+        r#"
+    UPDATE exif_metas
+    SET
+        fuji_recipe_id = ?
+    WHERE
+        fuji_recipe_id IN ( { } )
+    "#,
+        params
+    );
+
+    let mut query = sqlx::query(&query).bind(id);
+
+    for duplicate in duplicates {
+        query = query.bind(duplicate);
+    }
+
+    let relinked = query
+        .execute(&mut *conn)
+        .await
+        .context(SqlxSnafu)?
+        .rows_affected();
+
+    let query = format!(
+        r#"
+    DELETE FROM fuji_recipes
+    WHERE
+        id IN ( { } )
+    "#,
+        params
+    );
+
+    let mut query = sqlx::query(&query);
+
+    for duplicate in duplicates {
+        query = query.bind(duplicate);
+    }
+
+    query.execute(&mut *conn).await.context(SqlxSnafu)?;
+
+    Ok(relinked)
+}
+
 impl TryFrom<DBFujifilmRecipe> for FujifilmRecipe {
     type Error = Error;
 
@@ -0,0 +1,166 @@
+//! Portable format used to import and export recipes. The same structure is written as TOML or
+//! JSON, a library file holds a list of recipes under `recipes`:
+//!
+//! ```toml
+//! [[recipes]]
+//! name = "Kodak Portra 400"
+//! author = "Fuji X Weekly"
+//! src = "https://fujixweekly.com/..."
+//! sensor = "..."
+//! film_simulation = "..."
+//! # ...the rest of the settings
+//! ```
+//!
+//! Settings use the same text representation stored in the database, the simplest way to get a
+//! valid file is to start from `recipe export`. Settings the sensor doesn't support are left out.
+use super::{FujifilmRecipe, db::DBFujifilmRecipe, db::Error as DbError};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct RecipeLibrary {
+    pub recipes: Vec<RecipeFile>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RecipeFile {
+    pub name: String,
+    /// Who published the recipe.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Where the recipe was published, usually a URL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub src: Option<String>,
+    pub sensor: String,
+    pub film_simulation: String,
+    pub white_balance: String,
+    pub white_balance_shift: String,
+    pub dynamic_range: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub d_range_priority: Option<String>,
+    pub highlight_tone: f64,
+    pub shadow_tone: f64,
+    pub color: i64,
+    pub sharpness: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clarity: Option<i64>,
+    pub high_iso_noise_reduction: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grain_strength: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grain_size: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_chrome_effect: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_chrome_fx_blue: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monochromatic_color: Option<String>,
+}
+
+impl From<&FujifilmRecipe> for RecipeFile {
+    fn from(value: &FujifilmRecipe) -> Self {
+        let recipe: DBFujifilmRecipe = value.into();
+
+        RecipeFile {
+            name: recipe.name,
+            author: recipe.author,
+            src: recipe.src,
+            sensor: recipe.sensor,
+            film_simulation: recipe.film_simulation,
+            white_balance: recipe.white_balance,
+            white_balance_shift: recipe.white_balance_shift,
+            dynamic_range: recipe.dynamic_range,
+            d_range_priority: recipe.d_range_priority,
+            highlight_tone: recipe.highlight_tone,
+            shadow_tone: recipe.shadow_tone,
+            color: recipe.color,
+            sharpness: recipe.sharpness,
+            clarity: recipe.clarity,
+            high_iso_noise_reduction: recipe.high_iso_noise_reduction,
+            grain_strength: recipe.grain_strength,
+            grain_size: recipe.grain_size,
+            color_chrome_effect: recipe.color_chrome_effect,
+            color_chrome_fx_blue: recipe.color_chrome_fx_blue,
+            monochromatic_color: recipe.monochromatic_color,
+        }
+    }
+}
+
+impl TryFrom<RecipeFile> for FujifilmRecipe {
+    type Error = DbError;
+
+    fn try_from(value: RecipeFile) -> Result<Self, Self::Error> {
+        DBFujifilmRecipe {
+            id: Uuid::new_v4().to_string(),
+            name: value.name,
+            author: value.author,
+            src: value.src,
+            sensor: value.sensor,
+            film_simulation: value.film_simulation,
+            white_balance: value.white_balance,
+            white_balance_shift: value.white_balance_shift,
+            dynamic_range: value.dynamic_range,
+            d_range_priority: value.d_range_priority,
+            highlight_tone: value.highlight_tone,
+            shadow_tone: value.shadow_tone,
+            color: value.color,
+            sharpness: value.sharpness,
+            clarity: value.clarity,
+            high_iso_noise_reduction: value.high_iso_noise_reduction,
+            grain_strength: value.grain_strength,
+            grain_size: value.grain_size,
+            color_chrome_effect: value.color_chrome_effect,
+            color_chrome_fx_blue: value.color_chrome_fx_blue,
+            monochromatic_color: value.monochromatic_color,
+        }
+        .try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn portra() -> RecipeFile {
+        RecipeFile {
+            name: "Kodak Portra 400".to_string(),
+            author: Some("Fuji X Weekly".to_string()),
+            src: None,
+            sensor: "TransIV".to_string(),
+            film_simulation: "Classic Chrome".to_string(),
+            white_balance: "Auto".to_string(),
+            white_balance_shift: "R2, B-4".to_string(),
+            dynamic_range: "DR400".to_string(),
+            d_range_priority: Some("Off".to_string()),
+            highlight_tone: -1.0,
+            shadow_tone: -1.0,
+            color: 2,
+            sharpness: -2,
+            clarity: Some(0),
+            high_iso_noise_reduction: -4,
+            grain_strength: Some("Weak".to_string()),
+            grain_size: Some("Small".to_string()),
+            color_chrome_effect: Some("Strong".to_string()),
+            color_chrome_fx_blue: Some("Weak".to_string()),
+            monochromatic_color: Some("WC 0, MG 0".to_string()),
+        }
+    }
+
+    #[test]
+    fn it_imports_exported_recipes() {
+        let recipe = FujifilmRecipe::try_from(portra()).unwrap();
+
+        let exported = RecipeLibrary {
+            recipes: vec![RecipeFile::from(&recipe)],
+        };
+        assert_eq!(exported.recipes[0], portra());
+
+        let json = serde_json::to_string(&exported).unwrap();
+        let library: RecipeLibrary = serde_json::from_str(&json).unwrap();
+        let imported = FujifilmRecipe::try_from(library.recipes[0].clone()).unwrap();
+
+        assert_eq!(imported.name, recipe.name);
+        assert_eq!(imported.author, recipe.author);
+        assert_eq!(imported.details(), recipe.details());
+    }
+}
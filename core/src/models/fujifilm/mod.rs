@@ -1,5 +1,8 @@
 pub mod db;
+pub mod file;
 mod from_tuple;
 mod recipe;
+mod swatch;
 
 pub use recipe::*;
+pub use swatch::*;
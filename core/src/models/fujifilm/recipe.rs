@@ -7,8 +7,8 @@ use uuid::Uuid;
 pub struct FujifilmRecipe {
     pub id: String,
     pub name: String,
-    pub author: String,
-    pub src: String,
+    pub author: Option<String>,
+    pub src: Option<String>,
     pub inner: _FujifilmRecipe,
 }
 
@@ -22,8 +22,8 @@ impl FujifilmRecipe {
         FujifilmRecipe {
             id,
             name,
-            author: "todo".to_string(),
-            src: "todo".to_string(),
+            author: None,
+            src: None,
             inner: recipe,
         }
     }
@@ -31,8 +31,8 @@ impl FujifilmRecipe {
     pub fn from_db(
         id: String,
         name: String,
-        author: String,
-        src: String,
+        author: Option<String>,
+        src: Option<String>,
         recipe: _FujifilmRecipe,
     ) -> Self {
         FujifilmRecipe {
@@ -0,0 +1,32 @@
+use fuji::recipe::{FilmSimulation, MonochromaticFilter};
+
+/// Name of the image representing the film simulation. The images live in
+/// `web/public/film-simulations`.
+pub fn swatch_filename(film_simulation: &FilmSimulation) -> &'static str {
+    match film_simulation {
+        FilmSimulation::ProviaStandard => "provia_1.png",
+        FilmSimulation::RealaAce => "realaace_1.png",
+        FilmSimulation::VelviaVivid => "velvia_1.png",
+        FilmSimulation::AstiaSoft => "astia_1.png",
+        FilmSimulation::ClassicChrome => "classicchrome_1.png",
+        FilmSimulation::ClassicNeg => "classicneg_1.png",
+        FilmSimulation::ProNegHi => "proneghi_1.png",
+        FilmSimulation::ProNegStd => "pronegstd_1.png",
+        FilmSimulation::NostalgicNeg => "nostalgicneg_1.png",
+        FilmSimulation::EternaCinema => "etrna_1.png",
+        FilmSimulation::BleachBypass => "eternabb_1.png",
+        FilmSimulation::Sepia => "sepia_1.png",
+        FilmSimulation::Monochrome { filter } => match filter {
+            MonochromaticFilter::Std => "monostd_1.png",
+            MonochromaticFilter::Red => "monor_1.png",
+            MonochromaticFilter::Green => "monog_1.png",
+            MonochromaticFilter::Yellow => "monoye_1.png",
+        },
+        FilmSimulation::Acros { filter } => match filter {
+            MonochromaticFilter::Std => "acrosstd_1.png",
+            MonochromaticFilter::Red => "acrosr_1.png",
+            MonochromaticFilter::Green => "acrosg_1.png",
+            MonochromaticFilter::Yellow => "acrosye_1.png",
+        },
+    }
+}
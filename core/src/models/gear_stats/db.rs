@@ -0,0 +1,134 @@
+use super::{GearStats, HistogramBucket};
+use snafu::prelude::*;
+use sqlx::{Error as SqlxError, FromRow, SqliteConnection};
+use std::collections::HashMap;
+
+#[derive(FromRow)]
+struct DBHistogramRow {
+    pub gear_id: String,
+    pub value: f64,
+    pub count: i64,
+}
+
+enum Gear {
+    Camera,
+    Lens,
+}
+
+impl Gear {
+    fn column(&self) -> &'static str {
+        match self {
+            Gear::Camera => "camera_id",
+            Gear::Lens => "lens_id",
+        }
+    }
+}
+
+enum Histogram {
+    FocalLength,
+    Aperture,
+}
+
+impl Histogram {
+    fn column(&self) -> &'static str {
+        match self {
+            Histogram::FocalLength => "focal_length",
+            Histogram::Aperture => "aperture",
+        }
+    }
+}
+
+impl GearStats {
+    pub async fn find_by_camera_ids(
+        conn: &mut SqliteConnection,
+        ids: &Vec<String>,
+    ) -> Result<Vec<(String, GearStats)>, Error> {
+        find_by_gear_ids(conn, Gear::Camera, ids).await
+    }
+
+    pub async fn find_by_lens_ids(
+        conn: &mut SqliteConnection,
+        ids: &Vec<String>,
+    ) -> Result<Vec<(String, GearStats)>, Error> {
+        find_by_gear_ids(conn, Gear::Lens, ids).await
+    }
+}
+
+async fn find_by_gear_ids(
+    conn: &mut SqliteConnection,
+    gear: Gear,
+    ids: &Vec<String>,
+) -> Result<Vec<(String, GearStats)>, Error> {
+    let focal_lengths = find_histogram(conn, &gear, Histogram::FocalLength, ids).await?;
+    let apertures = find_histogram(conn, &gear, Histogram::Aperture, ids).await?;
+
+    let mut stats: HashMap<String, GearStats> = HashMap::new();
+
+    for row in focal_lengths {
+        let entry = stats.entry(row.gear_id).or_default();
+        entry.photo_count += row.count;
+        entry.focal_lengths.push(HistogramBucket {
+            value: row.value,
+            count: row.count,
+        });
+    }
+
+    for row in apertures {
+        let entry = stats.entry(row.gear_id).or_default();
+        entry.apertures.push(HistogramBucket {
+            value: row.value,
+            count: row.count,
+        });
+    }
+
+    Ok(stats.into_iter().collect())
+}
+
+async fn find_histogram(
+    conn: &mut SqliteConnection,
+    gear: &Gear,
+    histogram: Histogram,
+    ids: &Vec<String>,
+) -> Result<Vec<DBHistogramRow>, Error> {
+    let params = format!("?{}", ", ?".repeat(ids.len() - 1));
+
+    let query = format!(
+        r#"
+    SELECT
+        e.{gear} AS gear_id,
+        e.{value} AS value,
+        COUNT(e.id) AS count
+    FROM
+        exif_metas AS e
+    JOIN
+        photos AS p ON p.id = e.photo_id
+    WHERE
+        p.deleted = false
+        AND e.{gear} IN ( {params} )
+    GROUP BY
+        e.{gear},
+        e.{value}
+    ORDER BY
+        e.{value} ASC
+    "#,
+        gear = gear.column(),
+        value = histogram.column(),
+        params = params
+    );
+
+    let mut query = sqlx::query_as::<_, DBHistogramRow>(&query);
+
+    for id in ids {
+        query = query.bind(id);
+    }
+
+    let rows = query.fetch_all(conn).await.context(SqlxSnafu)?;
+
+    Ok(rows)
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to execute query: {:?}", source))]
+    Sqlx { source: SqlxError },
+}
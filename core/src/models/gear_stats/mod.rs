@@ -0,0 +1,26 @@
+pub mod db;
+
+use serde::Serialize;
+
+/// Usage numbers for a camera or a lens, computed from the photos that are not deleted.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct GearStats {
+    pub photo_count: i64,
+    pub focal_lengths: Vec<HistogramBucket>,
+    pub apertures: Vec<HistogramBucket>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct HistogramBucket {
+    pub value: f64,
+    pub count: i64,
+}
+
+impl GearStats {
+    pub fn most_used_focal_length(&self) -> Option<f64> {
+        self.focal_lengths
+            .iter()
+            .max_by_key(|b| b.count)
+            .map(|b| b.value)
+    }
+}
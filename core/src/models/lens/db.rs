@@ -0,0 +1,222 @@
+use super::Lens;
+use crate::models::Timestamp;
+use crate::models::exif_meta::{LensMaker, str::maker::Error as MakerError};
+use snafu::prelude::*;
+use sqlx::{Error as SqlxError, FromRow, SqliteConnection};
+use std::str::FromStr;
+use time::OffsetDateTime;
+
+#[derive(FromRow)]
+struct DBLens {
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+    pub maker: String,
+    pub mount: Option<String>,
+    pub min_focal_length: Option<f64>,
+    pub max_focal_length: Option<f64>,
+    pub max_aperture: Option<f64>,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+impl Lens {
+    pub async fn find_by_name(conn: &mut SqliteConnection, name: &str) -> Result<Lens, Error> {
+        find_by_name(conn, name).await
+    }
+
+    pub async fn find_by_name_or_create(
+        conn: &mut SqliteConnection,
+        name: &str,
+        maker: LensMaker,
+    ) -> Result<Lens, Error> {
+        let lens = Lens::find_by_name(conn, name).await;
+
+        match lens {
+            Ok(lens) => Ok(lens),
+            Err(e) => match e {
+                Error::NotFound { .. } => {
+                    let lens = Lens::new(name.to_string(), maker);
+                    lens.save(conn).await?;
+
+                    Ok(lens)
+                }
+                _ => Err(e),
+            },
+        }
+    }
+
+    pub async fn find_all(conn: &mut SqliteConnection) -> Result<Vec<Lens>, Error> {
+        find_all(conn).await
+    }
+
+    pub async fn save(&self, conn: &mut SqliteConnection) -> Result<String, Error> {
+        let lens: DBLens = self.into();
+        insert(conn, &lens).await
+    }
+}
+
+async fn find_by_name(conn: &mut SqliteConnection, name: &str) -> Result<Lens, Error> {
+    let lens = sqlx::query_as!(
+        DBLens,
+        r#"
+    SELECT
+        id,
+        name,
+        display_name,
+        maker,
+        mount,
+        min_focal_length,
+        max_focal_length,
+        max_aperture,
+        created_at,
+        updated_at
+    FROM
+        lenses
+    WHERE
+        name = ?
+    "#,
+        name
+    )
+    .fetch_optional(conn)
+    .await
+    .context(SqlxSnafu)?;
+
+    let lens = lens.context(NotFoundSnafu {
+        name: name.to_string(),
+    })?;
+
+    lens.try_into()
+}
+
+async fn find_all(conn: &mut SqliteConnection) -> Result<Vec<Lens>, Error> {
+    let lenses = sqlx::query_as!(
+        DBLens,
+        r#"
+    SELECT
+        id,
+        name,
+        display_name,
+        maker,
+        mount,
+        min_focal_length,
+        max_focal_length,
+        max_aperture,
+        created_at,
+        updated_at
+    FROM
+        lenses
+    ORDER BY
+        display_name ASC
+    "#
+    )
+    .fetch_all(conn)
+    .await
+    .context(SqlxSnafu)?;
+
+    let lenses: Vec<Lens> = lenses.into_iter().map(|l| l.try_into().unwrap()).collect();
+
+    Ok(lenses)
+}
+
+async fn insert(conn: &mut SqliteConnection, lens: &DBLens) -> Result<String, Error> {
+    let id = lens.id.clone();
+
+    sqlx::query!(
+        r#"
+    INSERT INTO lenses(
+        id,
+        name,
+        display_name,
+        maker,
+        mount,
+        min_focal_length,
+        max_focal_length,
+        max_aperture,
+        created_at,
+        updated_at
+    )
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+    "#,
+        lens.id,
+        lens.name,
+        lens.display_name,
+        lens.maker,
+        lens.mount,
+        lens.min_focal_length,
+        lens.max_focal_length,
+        lens.max_aperture,
+        lens.created_at,
+        lens.updated_at,
+    )
+    .execute(conn)
+    .await
+    .context(SqlxSnafu)?;
+
+    Ok(id)
+}
+
+impl TryFrom<DBLens> for Lens {
+    type Error = Error;
+
+    fn try_from(value: DBLens) -> Result<Self, Self::Error> {
+        let maker = LensMaker::from_str(&value.maker).context(MakerSnafu)?;
+
+        let created_at = {
+            let timestamp = value.created_at.0 / 1000;
+
+            OffsetDateTime::from_unix_timestamp(timestamp).context(TimestampSnafu)?
+        };
+
+        let updated_at = {
+            let timestamp = value.updated_at.0 / 1000;
+
+            OffsetDateTime::from_unix_timestamp(timestamp).context(TimestampSnafu)?
+        };
+
+        Ok(Lens {
+            id: value.id,
+            name: value.name,
+            display_name: value.display_name,
+            maker,
+            mount: value.mount,
+            min_focal_length: value.min_focal_length,
+            max_focal_length: value.max_focal_length,
+            max_aperture: value.max_aperture,
+            created_at,
+            updated_at,
+        })
+    }
+}
+
+impl From<&Lens> for DBLens {
+    fn from(value: &Lens) -> Self {
+        DBLens {
+            id: value.id.clone(),
+            name: value.name.clone(),
+            display_name: value.display_name.clone(),
+            maker: value.maker.to_string(),
+            mount: value.mount.clone(),
+            min_focal_length: value.min_focal_length,
+            max_focal_length: value.max_focal_length,
+            max_aperture: value.max_aperture,
+            created_at: value.created_at.into(),
+            updated_at: value.updated_at.into(),
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to execute query: {:?}", source))]
+    Sqlx { source: SqlxError },
+
+    #[snafu(display("Failed to parse timestamp: {:?}", source))]
+    Timestamp { source: time::error::ComponentRange },
+
+    #[snafu(display("Failed to parse Lens Maker {:?}", source))]
+    Maker { source: MakerError },
+
+    #[snafu(display("Could not find lens: {}", name))]
+    NotFound { name: String },
+}
@@ -0,0 +1,163 @@
+pub mod db;
+
+use crate::models::exif_meta::LensMaker;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Lens {
+    pub id: String,
+    /// Model name as reported by the EXIF data, e.g. `XF23mmF1.4 R LM WR`.
+    pub name: String,
+    pub display_name: String,
+    pub maker: LensMaker,
+    pub mount: Option<String>,
+    pub min_focal_length: Option<f64>,
+    pub max_focal_length: Option<f64>,
+    pub max_aperture: Option<f64>,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
+
+impl Lens {
+    pub fn new(name: String, maker: LensMaker) -> Self {
+        let id = Uuid::new_v4().to_string();
+
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let created_at = OffsetDateTime::from_unix_timestamp(now).unwrap();
+        let updated_at = OffsetDateTime::from_unix_timestamp(now).unwrap();
+
+        let display_name = match maker {
+            LensMaker::Unknown => name.clone(),
+            _ => format!("{} {}", maker, name),
+        };
+        let mount = mount_from_name(&maker, &name);
+        let optics = Optics::from_name(&name);
+
+        Lens {
+            id,
+            name,
+            display_name,
+            maker,
+            mount,
+            min_focal_length: optics.min_focal_length,
+            max_focal_length: optics.max_focal_length,
+            max_aperture: optics.max_aperture,
+            created_at,
+            updated_at,
+        }
+    }
+
+    pub fn is_zoom(&self) -> bool {
+        match (self.min_focal_length, self.max_focal_length) {
+            (Some(min), Some(max)) => max > min,
+            _ => false,
+        }
+    }
+}
+
+fn mount_from_name(maker: &LensMaker, name: &str) -> Option<String> {
+    match maker {
+        LensMaker::Fujifilm if name.starts_with("GF") => Some("G".to_string()),
+        LensMaker::Fujifilm => Some("X".to_string()),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Optics {
+    min_focal_length: Option<f64>,
+    max_focal_length: Option<f64>,
+    max_aperture: Option<f64>,
+}
+
+impl Optics {
+    /// Reads the focal range and widest aperture from names such as `XF16-55mmF2.8 R LM WR` or
+    /// `NOKTON 35mm F1.2`.
+    fn from_name(name: &str) -> Self {
+        static RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"(?i)(?<min>\d+(?:\.\d+)?)(?:-(?<max>\d+(?:\.\d+)?))?mm\s*F/?(?<aperture>\d+(?:\.\d+)?)").unwrap()
+        });
+
+        let Some(caps) = RE.captures(name) else {
+            return Optics::default();
+        };
+
+        let min_focal_length = caps
+            .name("min")
+            .and_then(|m| m.as_str().parse::<f64>().ok());
+        let max_focal_length = caps
+            .name("max")
+            .and_then(|m| m.as_str().parse::<f64>().ok())
+            .or(min_focal_length);
+        let max_aperture = caps
+            .name("aperture")
+            .and_then(|m| m.as_str().parse::<f64>().ok());
+
+        Optics {
+            min_focal_length,
+            max_focal_length,
+            max_aperture,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_prime_lenses() {
+        assert_eq!(
+            Optics::from_name("XF23mmF1.4 R LM WR"),
+            Optics {
+                min_focal_length: Some(23.0),
+                max_focal_length: Some(23.0),
+                max_aperture: Some(1.4),
+            }
+        );
+    }
+
+    #[test]
+    fn it_parses_zoom_lenses() {
+        assert_eq!(
+            Optics::from_name("XF18-55mmF2.8-4 R LM OIS"),
+            Optics {
+                min_focal_length: Some(18.0),
+                max_focal_length: Some(55.0),
+                max_aperture: Some(2.8),
+            }
+        );
+    }
+
+    #[test]
+    fn it_parses_spaced_names() {
+        assert_eq!(
+            Optics::from_name("NOKTON 35mm F1.2"),
+            Optics {
+                min_focal_length: Some(35.0),
+                max_focal_length: Some(35.0),
+                max_aperture: Some(1.2),
+            }
+        );
+    }
+
+    #[test]
+    fn it_ignores_unknown_names() {
+        assert_eq!(Optics::from_name("Manual Lens"), Optics::default());
+    }
+
+    #[test]
+    fn it_detects_the_mount() {
+        let lens = Lens::new("GF63mmF2.8 R WR".to_string(), LensMaker::Fujifilm);
+        assert_eq!(lens.mount, Some("G".to_string()));
+        assert!(!lens.is_zoom());
+
+        let lens = Lens::new("XF16-55mmF2.8 R LM WR".to_string(), LensMaker::Fujifilm);
+        assert_eq!(lens.mount, Some("X".to_string()));
+        assert!(lens.is_zoom());
+    }
+}
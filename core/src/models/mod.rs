@@ -1,8 +1,14 @@
 use time::OffsetDateTime;
 
+pub mod api_token;
+pub mod camera;
 pub mod exif_meta;
 pub mod fujifilm;
+pub mod gear_stats;
+pub mod lens;
 pub mod photo;
+pub mod photo_rendition;
+pub mod rebuild_journal;
 pub mod tag;
 
 #[derive(sqlx::Type, Debug, Clone)]
@@ -1,9 +1,10 @@
-use super::{FileType, Photo};
+use super::{FileType, Photo, hash_distance, rank_similar};
 use crate::models::{
     Timestamp,
-    photo::Orientation,
+    photo::{ColorSpace, Orientation},
     tag::{Tag, db::Error as TagDbError},
 };
+use crate::utils::color::{Error as ColorError, Palette, Rgb};
 use snafu::prelude::*;
 use sqlx::error::Error as SqlxError;
 use sqlx::{FromRow, SqliteConnection};
@@ -20,6 +21,13 @@ struct DBPhoto {
     filetype: String,
     orientation: String,
     blurhash: Option<String>,
+    original_sha256: Option<String>,
+    color_space: Option<String>,
+    aspect_ratio: Option<f64>,
+    palette: Option<String>,
+    dominant_color: Option<String>,
+    perceptual_hash: Option<i64>,
+    watermark: bool,
     created_at: Timestamp,
     updated_at: Timestamp,
     deleted: bool,
@@ -34,6 +42,34 @@ struct DBTagPhoto {
     filetype: String,
     orientation: String,
     blurhash: Option<String>,
+    original_sha256: Option<String>,
+    color_space: Option<String>,
+    aspect_ratio: Option<f64>,
+    palette: Option<String>,
+    dominant_color: Option<String>,
+    perceptual_hash: Option<i64>,
+    watermark: bool,
+    created_at: Timestamp,
+    updated_at: Timestamp,
+    deleted: bool,
+}
+
+#[derive(FromRow)]
+struct DBRecipePhoto {
+    fuji_recipe_id: String,
+    id: String,
+    title: String,
+    filename: String,
+    filetype: String,
+    orientation: String,
+    blurhash: Option<String>,
+    original_sha256: Option<String>,
+    color_space: Option<String>,
+    aspect_ratio: Option<f64>,
+    palette: Option<String>,
+    dominant_color: Option<String>,
+    perceptual_hash: Option<i64>,
+    watermark: bool,
     created_at: Timestamp,
     updated_at: Timestamp,
     deleted: bool,
@@ -63,6 +99,13 @@ impl Photo {
         }
     }
 
+    pub async fn find_by_fuji_recipe_ids(
+        conn: &mut SqliteConnection,
+        ids: &Vec<String>,
+    ) -> Result<Vec<(String, Photo)>, Error> {
+        find_by_fuji_recipe_ids(conn, ids).await
+    }
+
     pub async fn find_all(
         conn: &mut SqliteConnection,
         orientation: Option<Orientation>,
@@ -70,6 +113,54 @@ impl Photo {
         find_all(conn, &orientation).await
     }
 
+    /// Up to `limit` photos, oldest first, after the one with the given creation date, in seconds,
+    /// and id. Only the photos with the tag when there's one.
+    pub async fn find_page(
+        conn: &mut SqliteConnection,
+        tag_id: Option<&str>,
+        after: Option<(i64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<Photo>, Error> {
+        find_page(conn, tag_id, after, limit).await
+    }
+
+    /// Photos that look alike, the closest first along with how far they are.
+    pub async fn find_similar(
+        conn: &mut SqliteConnection,
+        id: &str,
+        hash: u64,
+        limit: usize,
+    ) -> Result<Vec<(u32, Photo)>, Error> {
+        let hashes = find_perceptual_hashes(conn).await?;
+        let ids = rank_similar(&hashes, id, hash, limit);
+
+        let mut photos = find_by_ids(conn, &ids).await?;
+        photos.sort_by_key(|p| ids.iter().position(|id| id == &p.id));
+
+        let similar = photos
+            .into_iter()
+            .filter_map(|p| Some((hash_distance(hash, p.perceptual_hash?), p)))
+            .collect();
+
+        Ok(similar)
+    }
+
+    /// The perceptual hash of every photo that has one, to compare them without loading the
+    /// photos.
+    pub async fn find_perceptual_hashes(
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<(String, u64)>, Error> {
+        find_perceptual_hashes(conn).await
+    }
+
+    /// The photos with the given ids, in no particular order.
+    pub async fn find_by_ids(
+        conn: &mut SqliteConnection,
+        ids: &[String],
+    ) -> Result<Vec<Photo>, Error> {
+        find_by_ids(conn, ids).await
+    }
+
     pub async fn save(&self, conn: &mut SqliteConnection) -> Result<String, Error> {
         let photo: DBPhoto = self.into();
         insert(conn, photo).await
@@ -109,6 +200,13 @@ async fn find_by_id(conn: &mut SqliteConnection, id: &str) -> Result<Photo, Erro
         filetype,
         orientation,
         blurhash,
+        original_sha256,
+        color_space,
+        aspect_ratio,
+        palette,
+        dominant_color,
+        perceptual_hash,
+        watermark,
         created_at,
         updated_at,
         deleted
@@ -145,6 +243,13 @@ async fn find_by_filename(
         filetype,
         orientation,
         blurhash,
+        original_sha256,
+        color_space,
+        aspect_ratio,
+        palette,
+        dominant_color,
+        perceptual_hash,
+        watermark,
         created_at,
         updated_at,
         deleted
@@ -179,8 +284,9 @@ async fn find_all(
             sqlx::query_as!(
                 DBPhoto,
                 r#"
-                SELECT id, title, filename, filetype, orientation, blurhash,
-                       created_at, updated_at, deleted
+                SELECT id, title, filename, filetype, orientation, blurhash, original_sha256,
+                       color_space, aspect_ratio, palette, dominant_color, perceptual_hash,
+                       watermark, created_at, updated_at, deleted
                 FROM photos AS p
                 WHERE deleted = false AND orientation = ?
                 ORDER BY created_at ASC
@@ -194,8 +300,9 @@ async fn find_all(
         None => sqlx::query_as!(
             DBPhoto,
             r#"
-                SELECT id, title, filename, filetype, orientation, blurhash,
-                       created_at, updated_at, deleted
+                SELECT id, title, filename, filetype, orientation, blurhash, original_sha256,
+                       color_space, aspect_ratio, palette, dominant_color, perceptual_hash,
+                       watermark, created_at, updated_at, deleted
                 FROM photos AS p
                 WHERE deleted = false
                 ORDER BY created_at ASC
@@ -211,6 +318,108 @@ async fn find_all(
     Ok(photos)
 }
 
+async fn find_perceptual_hashes(conn: &mut SqliteConnection) -> Result<Vec<(String, u64)>, Error> {
+    let hashes = sqlx::query!(
+        r#"
+    SELECT
+        id,
+        perceptual_hash
+    FROM
+        photos
+    WHERE
+        deleted = false
+        AND perceptual_hash IS NOT NULL
+    "#
+    )
+    .fetch_all(conn)
+    .await
+    .context(SqlxSnafu)?;
+
+    let hashes = hashes
+        .into_iter()
+        .filter_map(|h| Some((h.id, h.perceptual_hash? as u64)))
+        .collect();
+
+    Ok(hashes)
+}
+
+async fn find_page(
+    conn: &mut SqliteConnection,
+    tag_id: Option<&str>,
+    after: Option<(i64, &str)>,
+    limit: usize,
+) -> Result<Vec<Photo>, Error> {
+    let join = if tag_id.is_some() {
+        "JOIN photo_tags AS pt ON pt.photo_id = p.id AND pt.tag_id = ?"
+    } else {
+        ""
+    };
+    let after_filter = if after.is_some() {
+        "AND (p.created_at, p.id) > (?, ?)"
+    } else {
+        ""
+    };
+
+    let query = format!(
+        r#"
+    SELECT p.id, title, filename, filetype, orientation, blurhash, original_sha256, color_space,
+           aspect_ratio, palette, dominant_color, perceptual_hash, watermark, p.created_at,
+           p.updated_at, p.deleted
+    FROM photos AS p
+    {}
+    WHERE p.deleted = false {}
+    ORDER BY p.created_at ASC, p.id ASC
+    LIMIT ?
+    "#,
+        join, after_filter
+    );
+
+    let mut query = sqlx::query_as::<_, DBPhoto>(&query);
+
+    if let Some(tag_id) = tag_id {
+        query = query.bind(tag_id);
+    }
+    if let Some((created_at, id)) = after {
+        query = query.bind(created_at).bind(id);
+    }
+
+    let photos = query
+        .bind(limit as i64)
+        .fetch_all(conn)
+        .await
+        .context(SqlxSnafu)?;
+
+    photos.into_iter().map(|p| p.try_into()).collect()
+}
+
+async fn find_by_ids(conn: &mut SqliteConnection, ids: &[String]) -> Result<Vec<Photo>, Error> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let params = format!("?{}", ", ?".repeat(ids.len() - 1));
+    let query = format!(
+        r#"
+    SELECT id, title, filename, filetype, orientation, blurhash, original_sha256, color_space,
+           aspect_ratio, palette, dominant_color, perceptual_hash, watermark, created_at,
+           updated_at, deleted
+    FROM photos
+    WHERE deleted = false AND id IN ( {} )
+    "#,
+        params
+    );
+
+    let mut query = sqlx::query_as::<_, DBPhoto>(&query);
+
+    for id in ids {
+        query = query.bind(id);
+    }
+
+    let photos = query.fetch_all(conn).await.context(SqlxSnafu)?;
+
+    photos.into_iter().map(|p| p.try_into()).collect()
+}
+
 async fn find_by_tag_ids(
     conn: &mut SqliteConnection,
     ids: &Vec<String>,
@@ -229,6 +438,13 @@ async fn find_by_tag_ids(
         filetype,
         orientation,
         blurhash,
+        original_sha256,
+        color_space,
+        aspect_ratio,
+        palette,
+        dominant_color,
+        perceptual_hash,
+        watermark,
         p.created_at,
         p.updated_at,
         p.deleted
@@ -265,6 +481,13 @@ async fn find_by_tag_ids(
                     filetype: p.filetype,
                     orientation: p.orientation,
                     blurhash: p.blurhash,
+                    original_sha256: p.original_sha256,
+                    color_space: p.color_space,
+                    aspect_ratio: p.aspect_ratio,
+                    palette: p.palette,
+                    dominant_color: p.dominant_color,
+                    perceptual_hash: p.perceptual_hash,
+                    watermark: p.watermark,
                     created_at: p.created_at,
                     updated_at: p.updated_at,
                     deleted: p.deleted,
@@ -296,6 +519,13 @@ async fn find_by_tag_ids_and_orientation(
         filetype,
         orientation,
         blurhash,
+        original_sha256,
+        color_space,
+        aspect_ratio,
+        palette,
+        dominant_color,
+        perceptual_hash,
+        watermark,
         p.created_at,
         p.updated_at,
         p.deleted
@@ -334,6 +564,90 @@ async fn find_by_tag_ids_and_orientation(
                     filetype: p.filetype,
                     orientation: p.orientation,
                     blurhash: p.blurhash,
+                    original_sha256: p.original_sha256,
+                    color_space: p.color_space,
+                    aspect_ratio: p.aspect_ratio,
+                    palette: p.palette,
+                    dominant_color: p.dominant_color,
+                    perceptual_hash: p.perceptual_hash,
+                    watermark: p.watermark,
+                    created_at: p.created_at,
+                    updated_at: p.updated_at,
+                    deleted: p.deleted,
+                },
+            )
+        })
+        .map(|(id, t)| (id, t.try_into().unwrap()))
+        .collect();
+
+    Ok(photos)
+}
+
+async fn find_by_fuji_recipe_ids(
+    conn: &mut SqliteConnection,
+    ids: &Vec<String>,
+) -> Result<Vec<(String, Photo)>, Error> {
+    let params = format!("?{}", ", ?".repeat(ids.len() - 1));
+
+    let query = format!(
+        r#"
+    SELECT
+        e.fuji_recipe_id,
+        p.id,
+        title,
+        filename,
+        filetype,
+        orientation,
+        blurhash,
+        original_sha256,
+        color_space,
+        aspect_ratio,
+        palette,
+        dominant_color,
+        perceptual_hash,
+        watermark,
+        p.created_at,
+        p.updated_at,
+        p.deleted
+    FROM
+        photos as p
+    JOIN
+        exif_metas as e ON e.photo_id = p.id
+    WHERE
+        e.fuji_recipe_id IN ( { } )
+        AND deleted = false
+    ORDER BY  p.created_at DESC
+    "#,
+        params
+    );
+
+    let mut query = sqlx::query_as::<_, DBRecipePhoto>(&query);
+
+    for id in ids {
+        query = query.bind(id);
+    }
+
+    let photos = query.fetch_all(conn).await.context(SqlxSnafu)?;
+
+    let photos: Vec<(String, Photo)> = photos
+        .into_iter()
+        .map(|p| {
+            (
+                p.fuji_recipe_id,
+                DBPhoto {
+                    id: p.id,
+                    title: p.title,
+                    filename: p.filename,
+                    filetype: p.filetype,
+                    orientation: p.orientation,
+                    blurhash: p.blurhash,
+                    original_sha256: p.original_sha256,
+                    color_space: p.color_space,
+                    aspect_ratio: p.aspect_ratio,
+                    palette: p.palette,
+                    dominant_color: p.dominant_color,
+                    perceptual_hash: p.perceptual_hash,
+                    watermark: p.watermark,
                     created_at: p.created_at,
                     updated_at: p.updated_at,
                     deleted: p.deleted,
@@ -351,8 +665,8 @@ async fn insert(conn: &mut SqliteConnection, photo: DBPhoto) -> Result<String, E
 
     sqlx::query!(
         r#"
-    INSERT INTO photos (id, title, filename, filetype, orientation, blurhash, created_at, updated_at, deleted)
-    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+    INSERT INTO photos (id, title, filename, filetype, orientation, blurhash, original_sha256, color_space, aspect_ratio, palette, dominant_color, perceptual_hash, watermark, created_at, updated_at, deleted)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
     "#,
         photo.id,
         photo.title,
@@ -360,6 +674,13 @@ async fn insert(conn: &mut SqliteConnection, photo: DBPhoto) -> Result<String, E
         photo.filetype,
         photo.orientation,
         photo.blurhash,
+        photo.original_sha256,
+        photo.color_space,
+        photo.aspect_ratio,
+        photo.palette,
+        photo.dominant_color,
+        photo.perceptual_hash,
+        photo.watermark,
         photo.created_at,
         photo.updated_at,
         photo.deleted
@@ -381,6 +702,13 @@ async fn update(conn: &mut SqliteConnection, photo: DBPhoto) -> Result<(), Error
         filetype = ?,
         orientation = ?,
         blurhash = ?,
+        original_sha256 = ?,
+        color_space = ?,
+        aspect_ratio = ?,
+        palette = ?,
+        dominant_color = ?,
+        perceptual_hash = ?,
+        watermark = ?,
         updated_at = ?,
         deleted = ?
     WHERE id = ?
@@ -390,6 +718,13 @@ async fn update(conn: &mut SqliteConnection, photo: DBPhoto) -> Result<(), Error
         photo.filetype,
         photo.orientation,
         photo.blurhash,
+        photo.original_sha256,
+        photo.color_space,
+        photo.aspect_ratio,
+        photo.palette,
+        photo.dominant_color,
+        photo.perceptual_hash,
+        photo.watermark,
         photo.updated_at,
         photo.deleted,
         photo.id,
@@ -428,17 +763,26 @@ impl TryFrom<DBPhoto> for Photo {
 
         let orientation = Orientation::from_str(&value.orientation).context(OrientationSnafu)?;
 
-        let created_at = {
-            let timestamp = value.created_at.0 / 1000;
-
-            OffsetDateTime::from_unix_timestamp(timestamp).context(TimestampSnafu)?
+        let color_space = value
+            .color_space
+            .as_deref()
+            .map(ColorSpace::from_str)
+            .transpose()
+            .context(ColorSpaceSnafu)?;
+
+        let palette = match (value.palette, value.dominant_color) {
+            (Some(colors), Some(dominant)) => Some(Palette {
+                colors: Palette::parse_colors(&colors).context(PaletteSnafu)?,
+                dominant: Rgb::from_str(&dominant).context(PaletteSnafu)?,
+            }),
+            _ => None,
         };
 
-        let updated_at = {
-            let timestamp = value.updated_at.0 / 1000;
+        let created_at =
+            OffsetDateTime::from_unix_timestamp(value.created_at.0).context(TimestampSnafu)?;
 
-            OffsetDateTime::from_unix_timestamp(timestamp).context(TimestampSnafu)?
-        };
+        let updated_at =
+            OffsetDateTime::from_unix_timestamp(value.updated_at.0).context(TimestampSnafu)?;
 
         Ok(Photo {
             id: value.id,
@@ -447,6 +791,12 @@ impl TryFrom<DBPhoto> for Photo {
             filetype,
             orientation,
             blurhash: value.blurhash,
+            original_sha256: value.original_sha256,
+            color_space,
+            aspect_ratio: value.aspect_ratio,
+            palette,
+            perceptual_hash: value.perceptual_hash.map(|h| h as u64),
+            watermark: value.watermark,
             created_at,
             updated_at,
             deleted: value.deleted,
@@ -463,6 +813,14 @@ impl From<&Photo> for DBPhoto {
             filetype: photo.filetype.to_string(),
             orientation: photo.orientation.to_string(),
             blurhash: photo.blurhash.clone(),
+            original_sha256: photo.original_sha256.clone(),
+            color_space: photo.color_space.map(|c| c.to_string()),
+            aspect_ratio: photo.aspect_ratio,
+            palette: photo.palette.as_ref().map(|p| p.colors_to_string()),
+            dominant_color: photo.palette.as_ref().map(|p| p.dominant.to_string()),
+            // SQLite has no unsigned integers, the bits are stored as they are.
+            perceptual_hash: photo.perceptual_hash.map(|h| h as i64),
+            watermark: photo.watermark,
             created_at: photo.created_at.into(),
             updated_at: photo.updated_at.into(),
             deleted: photo.deleted,
@@ -483,6 +841,12 @@ pub enum Error {
     #[snafu(display("Failed to parse Orientation {}", source))]
     Orientation { source: strum::ParseError },
 
+    #[snafu(display("Failed to parse ColorSpace {}", source))]
+    ColorSpace { source: strum::ParseError },
+
+    #[snafu(display("Failed to parse the palette: {}", source))]
+    Palette { source: ColorError },
+
     #[snafu(display("Failed to parse timestamp: {}", source))]
     Timestamp { source: time::error::ComponentRange },
 
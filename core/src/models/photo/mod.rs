@@ -1,6 +1,7 @@
 pub mod db;
 mod str;
 
+use crate::utils::color::Palette;
 use serde::{Deserialize, Serialize};
 use snafu::prelude::*;
 use std::hash::{Hash, Hasher};
@@ -19,6 +20,21 @@ pub struct Photo {
     pub filetype: FileType,
     pub orientation: Orientation,
     pub blurhash: Option<String>,
+    /// SHA-256 of the archived original file, `None` for photos created before originals were
+    /// archived.
+    pub original_sha256: Option<String>,
+    /// Colour space of the original, the renditions are always sRGB. `None` for photos built
+    /// before it was detected.
+    pub color_space: Option<ColorSpace>,
+    /// Width over height, once rotated. `None` for photos created before it was recorded.
+    pub aspect_ratio: Option<f64>,
+    /// Most common colours, computed along the blurhash. `None` for photos built before it was.
+    pub palette: Option<Palette>,
+    /// dHash of the photo, close hashes mean photos that look alike. `None` for photos built
+    /// before it was computed.
+    pub perceptual_hash: Option<u64>,
+    /// Whether the renditions that allow it get the watermark drawn, photos can opt out.
+    pub watermark: bool,
     pub created_at: OffsetDateTime,
     pub updated_at: OffsetDateTime,
     pub deleted: bool,
@@ -45,6 +61,12 @@ impl Photo {
             filename: filename.to_string(),
             orientation,
             blurhash: None,
+            original_sha256: None,
+            color_space: None,
+            aspect_ratio: None,
+            palette: None,
+            perceptual_hash: None,
+            watermark: true,
             created_at,
             updated_at,
             deleted: false,
@@ -55,6 +77,36 @@ impl Photo {
         self.blurhash = Some(hash);
     }
 
+    pub fn set_original_sha256(&mut self, hash: String) {
+        self.original_sha256 = Some(hash);
+    }
+
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.color_space = Some(color_space);
+    }
+
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f64) {
+        self.aspect_ratio = Some(aspect_ratio);
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = Some(palette);
+    }
+
+    pub fn set_perceptual_hash(&mut self, hash: u64) {
+        self.perceptual_hash = Some(hash);
+    }
+
+    pub fn set_watermark(&mut self, watermark: bool) {
+        self.watermark = watermark;
+    }
+
+    /// Marks the photo as changed, so anything cached for it gets invalidated.
+    pub fn touch(&mut self) {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        self.updated_at = OffsetDateTime::from_unix_timestamp(now).unwrap();
+    }
+
     pub fn update_file(&mut self, path: &Path, orientation: &Orientation) -> Result<(), Error> {
         let ext = path.extension().context(ExtensionSnafu)?;
         let filetype = FileType::from_str(ext.to_str().unwrap()).context(FiletypeSnafu)?;
@@ -96,6 +148,74 @@ pub enum Orientation {
     Landscape,
     #[strum(serialize = "portrait")]
     Portrait,
+    #[strum(serialize = "square")]
+    Square,
+    /// Twice as wide as tall or more, like the 65:24 XPan-style crops.
+    #[strum(serialize = "panorama")]
+    Panorama,
+}
+
+/// How far from 1:1 a photo can be and still count as square, so 1:1 crops that lost a pixel or
+/// two still do.
+const SQUARE_TOLERANCE: f64 = 0.02;
+/// Width over height from which a photo counts as a panorama.
+const PANORAMA_RATIO: f64 = 2.0;
+
+impl Orientation {
+    /// Classifies a photo from its dimensions, once rotated the way it's meant to be seen.
+    pub fn from_dimensions(width: u32, height: u32) -> Orientation {
+        let ratio = width as f64 / height as f64;
+
+        if (ratio - 1.0).abs() <= SQUARE_TOLERANCE {
+            Orientation::Square
+        } else if ratio >= PANORAMA_RATIO {
+            Orientation::Panorama
+        } else if width > height {
+            Orientation::Landscape
+        } else {
+            Orientation::Portrait
+        }
+    }
+}
+
+/// How many bits two perceptual hashes differ by, from 0 for photos that look the same to 64.
+pub fn hash_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// The ids of the `limit` photos closest to `hash`, the closest first, leaving out the photo `id`.
+/// `hashes` are the ones `Photo::find_perceptual_hashes` returns.
+pub fn rank_similar(hashes: &[(String, u64)], id: &str, hash: u64, limit: usize) -> Vec<String> {
+    let mut ranked: Vec<(u32, &String)> = hashes
+        .iter()
+        .filter(|(other, _)| other != id)
+        .map(|(other, h)| (hash_distance(hash, *h), other))
+        .collect();
+
+    ranked.sort_by_key(|(distance, _)| *distance);
+
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(_, id)| id.clone())
+        .collect()
+}
+
+/// Colour space the original was encoded in, as described by its embedded ICC profile.
+#[derive(
+    Clone, Copy, Debug, Deserialize, Serialize, EnumString, EnumDisplay, sqlx::Type, Eq, PartialEq,
+)]
+pub enum ColorSpace {
+    /// Also used for originals without an embedded profile, as that's what browsers assume.
+    #[strum(serialize = "srgb")]
+    Srgb,
+    #[strum(serialize = "adobe_rgb")]
+    AdobeRgb,
+    #[strum(serialize = "display_p3")]
+    DisplayP3,
+    /// Any other embedded profile, it is still converted to sRGB.
+    #[strum(serialize = "other")]
+    Other,
 }
 
 #[derive(Debug, Snafu)]
@@ -109,3 +229,55 @@ pub enum Error {
     #[snafu(display("Invalid FileType: {:?}", source))]
     Filetype { source: FiletypeError },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_classifies_by_dimensions() {
+        assert_eq!(
+            Orientation::from_dimensions(6000, 4000),
+            Orientation::Landscape
+        );
+        assert_eq!(
+            Orientation::from_dimensions(4000, 6000),
+            Orientation::Portrait
+        );
+        assert_eq!(
+            Orientation::from_dimensions(4000, 4000),
+            Orientation::Square
+        );
+        assert_eq!(
+            Orientation::from_dimensions(4000, 3990),
+            Orientation::Square
+        );
+        assert_eq!(
+            Orientation::from_dimensions(6000, 2215),
+            Orientation::Panorama
+        );
+    }
+
+    #[test]
+    fn it_counts_differing_bits() {
+        assert_eq!(hash_distance(0b1011, 0b1011), 0);
+        assert_eq!(hash_distance(0b1011, 0b0010), 2);
+        assert_eq!(hash_distance(0, u64::MAX), 64);
+    }
+
+    #[test]
+    fn it_ranks_similar_photos() {
+        let hashes = vec![
+            ("a".to_string(), 0b0000),
+            ("b".to_string(), 0b0111),
+            ("c".to_string(), 0b0001),
+            ("d".to_string(), 0b0011),
+        ];
+
+        assert_eq!(rank_similar(&hashes, "a", 0b0000, 2), vec!["c", "d"]);
+        assert_eq!(
+            rank_similar(&hashes, "x", 0b0111, 10),
+            vec!["b", "d", "c", "a"]
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use super::PhotoRendition;
+use crate::aws::image_size::{Error as ParseError, ImageSize, ImageType};
+use snafu::prelude::*;
+use sqlx::{Error as SqlxError, FromRow, SqliteConnection};
+use std::str::FromStr;
+
+#[derive(FromRow)]
+struct DBPhotoRendition {
+    pub photo_id: String,
+    pub size: String,
+    pub kind: String,
+    pub quality: i64,
+    pub bytes: i64,
+    pub ssim: Option<f64>,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    pub md5: Option<String>,
+}
+
+impl PhotoRendition {
+    pub async fn find_all(conn: &mut SqliteConnection) -> Result<Vec<PhotoRendition>, Error> {
+        find_all(conn).await
+    }
+
+    /// Renditions of each photo, smallest first.
+    pub async fn find_by_photo_ids(
+        conn: &mut SqliteConnection,
+        ids: &[String],
+    ) -> Result<Vec<PhotoRendition>, Error> {
+        find_by_photo_ids(conn, ids).await
+    }
+
+    /// The rendition of a photo for the given size and type, `None` when it was never recorded.
+    pub async fn find_one(
+        conn: &mut SqliteConnection,
+        photo_id: &str,
+        size: &ImageSize,
+        kind: &ImageType,
+    ) -> Result<Option<PhotoRendition>, Error> {
+        find_one(conn, photo_id, size, kind).await
+    }
+
+    /// Stores the rendition, replacing the previous one of the same size and type.
+    pub async fn save(&self, conn: &mut SqliteConnection) -> Result<(), Error> {
+        let rendition: DBPhotoRendition = self.into();
+        upsert(conn, &rendition).await
+    }
+}
+
+async fn find_all(conn: &mut SqliteConnection) -> Result<Vec<PhotoRendition>, Error> {
+    let renditions = sqlx::query_as!(
+        DBPhotoRendition,
+        r#"
+    SELECT
+        photo_id,
+        size,
+        kind,
+        quality,
+        bytes,
+        ssim,
+        width,
+        height,
+        md5
+    FROM
+        photo_renditions
+    "#
+    )
+    .fetch_all(conn)
+    .await
+    .context(SqlxSnafu)?;
+
+    renditions.into_iter().map(|r| r.try_into()).collect()
+}
+
+async fn find_by_photo_ids(
+    conn: &mut SqliteConnection,
+    ids: &[String],
+) -> Result<Vec<PhotoRendition>, Error> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let params = format!("?{}", ", ?".repeat(ids.len() - 1));
+
+    let query = format!(
+        r#"
+    SELECT
+        photo_id,
+        size,
+        kind,
+        quality,
+        bytes,
+        ssim,
+        width,
+        height,
+        md5
+    FROM
+        photo_renditions
+    WHERE
+        photo_id IN ({ })
+    ORDER BY
+        width ASC
+    "#,
+        params
+    );
+
+    let mut query = sqlx::query_as::<_, DBPhotoRendition>(&query);
+
+    for id in ids {
+        query = query.bind(id);
+    }
+
+    let renditions = query.fetch_all(conn).await.context(SqlxSnafu)?;
+
+    renditions.into_iter().map(|r| r.try_into()).collect()
+}
+
+async fn find_one(
+    conn: &mut SqliteConnection,
+    photo_id: &str,
+    size: &ImageSize,
+    kind: &ImageType,
+) -> Result<Option<PhotoRendition>, Error> {
+    let size = size.to_string();
+    let kind = kind.to_string();
+
+    let rendition = sqlx::query_as!(
+        DBPhotoRendition,
+        r#"
+    SELECT
+        photo_id,
+        size,
+        kind,
+        quality,
+        bytes,
+        ssim,
+        width,
+        height,
+        md5
+    FROM
+        photo_renditions
+    WHERE
+        photo_id = ?
+        AND size = ?
+        AND kind = ?
+    "#,
+        photo_id,
+        size,
+        kind
+    )
+    .fetch_optional(conn)
+    .await
+    .context(SqlxSnafu)?;
+
+    rendition.map(|r| r.try_into()).transpose()
+}
+
+async fn upsert(conn: &mut SqliteConnection, rendition: &DBPhotoRendition) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+    INSERT OR REPLACE INTO photo_renditions(
+        photo_id, size, kind, quality, bytes, ssim, width, height, md5
+    )
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+    "#,
+        rendition.photo_id,
+        rendition.size,
+        rendition.kind,
+        rendition.quality,
+        rendition.bytes,
+        rendition.ssim,
+        rendition.width,
+        rendition.height,
+        rendition.md5,
+    )
+    .execute(conn)
+    .await
+    .context(SqlxSnafu)?;
+
+    Ok(())
+}
+
+impl TryFrom<DBPhotoRendition> for PhotoRendition {
+    type Error = Error;
+
+    fn try_from(value: DBPhotoRendition) -> Result<Self, Self::Error> {
+        let size = ImageSize::from_str(&value.size).context(ParseSnafu { value: value.size })?;
+        let kind = ImageType::from_str(&value.kind).context(ParseSnafu { value: value.kind })?;
+
+        Ok(PhotoRendition {
+            photo_id: value.photo_id,
+            size,
+            kind,
+            quality: value.quality as u8,
+            bytes: value.bytes,
+            ssim: value.ssim,
+            width: value.width.map(|w| w as u32),
+            height: value.height.map(|h| h as u32),
+            md5: value.md5,
+        })
+    }
+}
+
+impl From<&PhotoRendition> for DBPhotoRendition {
+    fn from(value: &PhotoRendition) -> Self {
+        DBPhotoRendition {
+            photo_id: value.photo_id.clone(),
+            size: value.size.to_string(),
+            kind: value.kind.to_string(),
+            quality: value.quality as i64,
+            bytes: value.bytes,
+            ssim: value.ssim,
+            width: value.width.map(|w| w as i64),
+            height: value.height.map(|h| h as i64),
+            md5: value.md5.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to execute query: {:?}", source))]
+    Sqlx { source: SqlxError },
+
+    #[snafu(display("Unknown size or type {}: {}", value, source))]
+    Parse { value: String, source: ParseError },
+}
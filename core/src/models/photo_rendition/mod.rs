@@ -0,0 +1,22 @@
+pub mod db;
+
+use crate::aws::image_size::{ImageSize, ImageType};
+
+/// One of the files uploaded for a photo, with how it was encoded.
+#[derive(Clone, Debug)]
+pub struct PhotoRendition {
+    pub photo_id: String,
+    pub size: ImageSize,
+    pub kind: ImageType,
+    pub quality: u8,
+    pub bytes: i64,
+    /// Structural similarity against the resized image before encoding, 1 means identical. Only
+    /// measured for the sizes with a target SSIM, or with `rebuild --measure`.
+    pub ssim: Option<f64>,
+    /// Pixel dimensions of the file. `None` for renditions built before they were recorded,
+    /// `rebuild` fills them.
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// MD5 of the file, the same hash the API uses as ETag.
+    pub md5: Option<String>,
+}
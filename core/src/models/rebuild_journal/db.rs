@@ -0,0 +1,67 @@
+use super::RebuildJournal;
+use snafu::prelude::*;
+use sqlx::{Error as SqlxError, SqliteConnection};
+use std::collections::HashSet;
+
+impl RebuildJournal {
+    /// Ids of the photos already rebuilt by this journal's run.
+    pub async fn completed_ids(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<HashSet<String>, Error> {
+        let rows = sqlx::query!(
+            r#"
+    SELECT
+        photo_id
+    FROM
+        rebuild_journal
+    WHERE
+        sizes = ?
+    "#,
+            self.sizes
+        )
+        .fetch_all(conn)
+        .await
+        .context(SqlxSnafu)?;
+
+        Ok(rows.into_iter().map(|r| r.photo_id).collect())
+    }
+
+    pub async fn complete(&self, conn: &mut SqliteConnection, photo_id: &str) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+    INSERT OR REPLACE INTO rebuild_journal(photo_id, sizes)
+    VALUES (?, ?)
+    "#,
+            photo_id,
+            self.sizes
+        )
+        .execute(conn)
+        .await
+        .context(SqlxSnafu)?;
+
+        Ok(())
+    }
+
+    /// Forgets every photo of this journal's run, the next run starts from scratch.
+    pub async fn clear(&self, conn: &mut SqliteConnection) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+    DELETE FROM rebuild_journal
+    WHERE sizes = ?
+    "#,
+            self.sizes
+        )
+        .execute(conn)
+        .await
+        .context(SqlxSnafu)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to execute query: {:?}", source))]
+    Sqlx { source: SqlxError },
+}
@@ -0,0 +1,17 @@
+pub mod db;
+
+/// Keeps track of the photos a `rebuild --all` run has already finished, so an interrupted run can
+/// resume instead of starting over. Runs that rebuild different sizes have separate journals.
+#[derive(Clone, Debug)]
+pub struct RebuildJournal {
+    /// Name of the rebuilt size, or `all` when every rendition is rebuilt.
+    pub sizes: String,
+}
+
+impl RebuildJournal {
+    pub fn new(sizes: &str) -> Self {
+        RebuildJournal {
+            sizes: sizes.to_string(),
+        }
+    }
+}
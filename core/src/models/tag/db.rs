@@ -24,6 +24,11 @@ struct DBPhotoTag {
 }
 
 impl Tag {
+    /// Every tag, sorted by name.
+    pub async fn find_all(conn: &mut SqliteConnection) -> Result<Vec<Tag>, Error> {
+        find_all(conn).await
+    }
+
     pub async fn find_by_name(conn: &mut SqliteConnection, name: &str) -> Result<Tag, Error> {
         let tags = find_by_names(conn, &[name]).await?;
 
@@ -83,6 +88,31 @@ impl Tag {
     }
 }
 
+async fn find_all(conn: &mut SqliteConnection) -> Result<Vec<Tag>, Error> {
+    let tags = sqlx::query_as!(
+        DBTag,
+        r#"
+    SELECT
+        id,
+        name,
+        created_at,
+        updated_at,
+        deleted
+    FROM
+        tags
+    WHERE
+        deleted = false
+    ORDER BY
+        name ASC
+    "#
+    )
+    .fetch_all(conn)
+    .await
+    .context(SqlxSnafu)?;
+
+    tags.into_iter().map(|t| t.try_into()).collect()
+}
+
 async fn find_by_names(conn: &mut SqliteConnection, names: &[&str]) -> Result<Vec<Tag>, Error> {
     let params = format!("?{}", ", ?".repeat(names.len() - 1));
 
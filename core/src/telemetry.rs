@@ -0,0 +1,127 @@
+//! Logs of the API and the website, written as JSON lines to stdout. `RUST_LOG` filters them like
+//! it did before, and the `log` records of the dependencies end up in the same output.
+//!
+//! With the `otlp` feature the spans are also exported to an OpenTelemetry collector, the one at
+//! `OTEL_EXPORTER_OTLP_ENDPOINT` or `http://localhost:4317` by default.
+use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+use uuid::Uuid;
+
+/// The header carrying the id of a request, from the website to the API and back to clients.
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Longest id accepted from a client, longer ones are replaced.
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+/// Keeps the exporter alive, the spans that are left are sent when it is dropped.
+#[must_use]
+pub struct Telemetry {
+    #[cfg(feature = "otlp")]
+    provider: Option<opentelemetry_sdk::trace::TracerProvider>,
+}
+
+/// Installs the subscriber, `service` names the spans sent to the collector.
+#[cfg_attr(not(feature = "otlp"), allow(unused_variables))]
+pub fn init(service: &'static str) -> Telemetry {
+    let registry = tracing_subscriber::registry()
+        .with(EnvFilter::from_default_env())
+        .with(fmt::layer().json().with_current_span(true));
+
+    #[cfg(feature = "otlp")]
+    let (registry, provider) = {
+        use opentelemetry::trace::TracerProvider as _;
+
+        let provider = otlp::provider(service);
+        let layer = provider
+            .as_ref()
+            .map(|p| tracing_opentelemetry::layer().with_tracer(p.tracer(service)));
+
+        (registry.with(layer), provider)
+    };
+
+    registry.init();
+
+    Telemetry {
+        #[cfg(feature = "otlp")]
+        provider,
+    }
+}
+
+#[cfg(feature = "otlp")]
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Some(provider) = &self.provider
+            && let Err(e) = provider.shutdown()
+        {
+            eprintln!("Failed to flush the OTLP exporter: {}", e);
+        }
+    }
+}
+
+/// The id a client sent, when it is safe to log, or a new one.
+pub fn request_id(header: Option<&str>) -> String {
+    match header {
+        Some(id) if is_valid(id) => id.to_string(),
+        _ => Uuid::new_v4().to_string(),
+    }
+}
+
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(feature = "otlp")]
+mod otlp {
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::SpanExporter;
+    use opentelemetry_sdk::{Resource, runtime, trace::TracerProvider};
+
+    /// A provider batching spans to the collector, `None` when the exporter can't be built. It
+    /// needs a Tokio runtime.
+    pub fn provider(service: &'static str) -> Option<TracerProvider> {
+        let exporter = match SpanExporter::builder().with_tonic().build() {
+            Ok(exporter) => exporter,
+            Err(e) => {
+                eprintln!("Failed to create the OTLP exporter: {}", e);
+                return None;
+            }
+        };
+
+        let provider = TracerProvider::builder()
+            .with_batch_exporter(exporter, runtime::Tokio)
+            .with_resource(Resource::new([KeyValue::new("service.name", service)]))
+            .build();
+
+        Some(provider)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_keeps_valid_request_ids() {
+        assert_eq!(request_id(Some("abc-123_DEF")), "abc-123_DEF");
+    }
+
+    #[test]
+    fn it_replaces_invalid_request_ids() {
+        let long = "a".repeat(MAX_REQUEST_ID_LEN + 1);
+
+        for header in [
+            None,
+            Some(""),
+            Some("a b"),
+            Some("a\nb"),
+            Some(long.as_str()),
+        ] {
+            let id = request_id(header);
+
+            assert!(Uuid::parse_str(&id).is_ok(), "{:?} was kept", header);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use serde::{Serialize, Serializer};
+use snafu::prelude::*;
+use std::{fmt, str::FromStr};
+
+/// A colour in sRGB, written as `#rrggbb`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Rgb { r, g, b }
+    }
+
+    /// Perceptual distance to another colour, as the CIE76 ΔE between both in CIELAB. Around 2 is
+    /// barely noticeable, above 50 they are clearly different colours.
+    pub fn distance(&self, other: &Rgb) -> f64 {
+        let [l1, a1, b1] = self.to_lab();
+        let [l2, a2, b2] = other.to_lab();
+
+        ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+    }
+
+    /// Converts to CIELAB through CIEXYZ, with the D65 white point sRGB uses.
+    fn to_lab(self) -> [f64; 3] {
+        let linear = |c: u8| {
+            let c = c as f64 / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        let (r, g, b) = (linear(self.r), linear(self.g), linear(self.b));
+
+        let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+        let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+        let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+
+        let f = |t: f64| {
+            if t > 0.008856 {
+                t.cbrt()
+            } else {
+                7.787 * t + 16.0 / 116.0
+            }
+        };
+
+        let (fx, fy, fz) = (f(x), f(y), f(z));
+
+        [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl Serialize for Rgb {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.trim().trim_start_matches('#');
+        ensure!(
+            hex.len() == 6 && hex.is_ascii(),
+            InvalidSnafu {
+                value: s.to_string()
+            }
+        );
+
+        let channel = |i: usize| {
+            u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| Error::Invalid {
+                value: s.to_string(),
+            })
+        };
+
+        Ok(Rgb::new(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+/// The most common colours of a photo, from the most to the least common.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Palette {
+    pub colors: Vec<Rgb>,
+    /// The colour covering most of the photo, meant for tinted backgrounds.
+    pub dominant: Rgb,
+}
+
+impl Palette {
+    /// Whether any colour of the palette is within `threshold` ΔE of `color`.
+    pub fn matches(&self, color: &Rgb, threshold: f64) -> bool {
+        self.colors.iter().any(|c| c.distance(color) <= threshold)
+    }
+
+    /// Parses the palette colours as stored, comma separated.
+    pub fn parse_colors(value: &str) -> Result<Vec<Rgb>, Error> {
+        value.split(',').map(Rgb::from_str).collect()
+    }
+
+    pub fn colors_to_string(&self) -> String {
+        self.colors
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Invalid colour {}, expected #rrggbb", value))]
+    Invalid { value: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_and_prints_hex() {
+        let color = Rgb::from_str("#1A2b3c").unwrap();
+
+        assert_eq!(color, Rgb::new(0x1a, 0x2b, 0x3c));
+        assert_eq!(color.to_string(), "#1a2b3c");
+        assert_eq!(Rgb::from_str("1a2b3c").unwrap(), color);
+    }
+
+    #[test]
+    fn it_fails_on_invalid_hex() {
+        assert!(Rgb::from_str("#12345").is_err());
+        assert!(Rgb::from_str("#12345g").is_err());
+        assert!(Rgb::from_str("#12345é").is_err());
+    }
+
+    #[test]
+    fn it_measures_perceptual_distance() {
+        let black = Rgb::new(0, 0, 0);
+        let white = Rgb::new(255, 255, 255);
+        let red = Rgb::new(255, 0, 0);
+        let dark_red = Rgb::new(240, 10, 10);
+
+        assert!((black.distance(&white) - 100.0).abs() < 0.1);
+        assert!(red.distance(&dark_red) < 10.0);
+        assert!(red.distance(&white) > 50.0);
+    }
+
+    #[test]
+    fn it_matches_any_palette_colour() {
+        let palette = Palette {
+            colors: vec![Rgb::new(20, 40, 160), Rgb::new(230, 200, 40)],
+            dominant: Rgb::new(20, 40, 160),
+        };
+
+        assert!(palette.matches(&Rgb::new(240, 205, 50), 15.0));
+        assert!(!palette.matches(&Rgb::new(200, 30, 30), 15.0));
+    }
+}
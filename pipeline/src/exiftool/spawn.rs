@@ -36,8 +36,12 @@ pub fn read_metadata(img_path: &Path) -> Result<Vec<ExifData>, Error> {
 
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
-        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
-        let json = json.as_array().unwrap().first().unwrap();
+        let json: serde_json::Value = serde_json::from_str(&stdout).context(OutputSnafu)?;
+        // One object per file read.
+        let json = json
+            .as_array()
+            .and_then(|files| files.first())
+            .context(NoMetadataSnafu)?;
         let value = JsonValue(json.clone());
         let result: Vec<ExifData> = value.try_into().context(JsonSnafu)?;
 
@@ -51,7 +55,13 @@ pub fn read_metadata(img_path: &Path) -> Result<Vec<ExifData>, Error> {
     }
 }
 
+/// Where exiftool is, `EXIFTOOL_PATH` when it's set, e.g. to an installed `/usr/bin/exiftool`, or
+/// the copy the install scripts put in `deps/exiftool`.
 fn exiftool_path() -> Result<PathBuf, Error> {
+    if let Some(path) = std::env::var_os("EXIFTOOL_PATH") {
+        return Ok(PathBuf::from(path));
+    }
+
     let root = std::env::current_dir().context(CurrentDirSnafu)?;
     #[cfg(target_os = "windows")]
     let path = root.join("deps").join("exiftool").join("exiftool(-k).exe");
@@ -89,6 +99,12 @@ pub enum Error {
     #[snafu(display("Something went wrong while running exiftool: {}", stderr))]
     Stderr { stderr: String },
 
+    #[snafu(display("Failed to read the output of exiftool: {}", source))]
+    Output { source: serde_json::Error },
+
+    #[snafu(display("Exiftool returned no metadata"))]
+    NoMetadata,
+
     #[snafu(display("Failed to parse JSON: {:?}", source))]
     Json { source: JsonError },
 }
@@ -0,0 +1,9 @@
+//! The photo pipeline: EXIF, recipes, renditions and storage. The CLI runs it locally, the API
+//! runs the same code for remote uploads.
+#[macro_use]
+extern crate log;
+
+pub mod exiftool;
+pub mod photo;
+pub mod publish;
+pub mod utils;
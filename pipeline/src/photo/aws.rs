@@ -0,0 +1,118 @@
+use crate::photo::build_images::ImageBuffers;
+use core_victorhqc_com::{
+    aws::{
+        S3,
+        image_size::{ImageSize, ImageType},
+        photo::{ByteStreamError, Error as AWSError},
+    },
+    models::photo::Photo,
+};
+use log::error;
+use snafu::prelude::*;
+
+pub async fn upload(photo: &Photo, s3: &S3, buffers: ImageBuffers) -> Result<(), Error> {
+    let uploads = buffers
+        .renditions
+        .into_iter()
+        .map(|(size, buffers)| async move {
+            let pairs = futures::join!(
+                s3.upload_to_aws_s3((photo, &size, &ImageType::Jpeg), buffers.jpeg.bytes),
+                s3.upload_to_aws_s3((photo, &size, &ImageType::Webp), buffers.webp.bytes)
+            );
+
+            pairs.0.context(UploadSnafu {
+                size,
+                kind: ImageType::Jpeg,
+            })?;
+
+            pairs.1.context(UploadSnafu {
+                size,
+                kind: ImageType::Webp,
+            })?;
+
+            Ok(())
+        });
+
+    futures::future::join_all(uploads)
+        .await
+        .into_iter()
+        .collect::<Result<(), Error>>()
+}
+
+/// Archives the file as it came from the camera, so the renditions can be rebuilt from it.
+pub async fn upload_original(photo: &Photo, s3: &S3, data: Vec<u8>) -> Result<(), Error> {
+    s3.upload_to_aws_s3((photo, &ImageSize::Original, &ImageType::Jpeg), data)
+        .await
+        .context(UploadSnafu {
+            size: ImageSize::Original,
+            kind: ImageType::Jpeg,
+        })?;
+
+    Ok(())
+}
+
+pub async fn download_original(photo: &Photo, s3: &S3) -> Result<Vec<u8>, Error> {
+    let response = s3
+        .download_from_aws_s3((photo, &ImageSize::Original, &ImageType::Jpeg))
+        .await
+        .context(DownloadSnafu)?;
+
+    let data = response.body.collect().await.context(StreamSnafu)?;
+
+    Ok(data.into_bytes().to_vec())
+}
+
+/// Removes every rendition of the photo. The archived original is kept.
+pub async fn remove(photo: &Photo, s3: &S3) -> Result<(), Error> {
+    let removals = ImageSize::renditions().map(|size| async move {
+        let pairs = futures::join!(
+            s3.remove_from_aws_s3((photo, &size, &ImageType::Jpeg)),
+            s3.remove_from_aws_s3((photo, &size, &ImageType::Webp))
+        );
+
+        pairs
+            .0
+            .context(RemoveSnafu {
+                size,
+                kind: ImageType::Jpeg,
+            })
+            .map_err(|err| error!("{}", err))
+            .ok();
+
+        pairs
+            .1
+            .context(RemoveSnafu {
+                size,
+                kind: ImageType::Webp,
+            })
+            .map_err(|err| error!("{}", err))
+            .ok();
+    });
+
+    futures::future::join_all(removals).await;
+
+    Ok(())
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to upload {} photo of type {}: {}", kind, size, source))]
+    Upload {
+        size: ImageSize,
+        kind: ImageType,
+        source: AWSError,
+    },
+
+    #[snafu(display("Failed to download the original photo: {}", source))]
+    Download { source: AWSError },
+
+    #[snafu(display("Failed to read the original photo: {}", source))]
+    Stream { source: ByteStreamError },
+
+    #[snafu(display("Failed to remove {} photo of type {}: {}", kind, size, source))]
+    Remove {
+        size: ImageSize,
+        kind: ImageType,
+        source: AWSError,
+    },
+}
@@ -0,0 +1,302 @@
+use super::{
+    color::{Error as ColorError, open_srgb},
+    palette::extract_palette,
+    perceptual_hash::dhash,
+    process::{Error as ProcessPhotoError, ProcessedBuffers, ProcessedPhoto},
+    watermark::Watermark,
+};
+use crate::utils::is_valid_extension;
+use console::Emoji;
+use core_victorhqc_com::{
+    aws::{
+        image_size::{ImageSize, ImageType},
+        rendition::Rendition,
+    },
+    models::{photo::ColorSpace, photo_rendition::PhotoRendition},
+    utils::color::Palette,
+};
+use image::GenericImageView;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use log::trace;
+use snafu::prelude::*;
+use std::{
+    any::Any,
+    path::Path,
+    sync::{
+        Arc,
+        mpsc::{Receiver, SendError, Sender},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+pub struct ImageBuffers {
+    /// One entry per built rendition, in the order they were requested.
+    pub renditions: Vec<(ImageSize, ProcessedBuffers)>,
+    pub blurhash: String,
+    pub palette: Palette,
+    pub perceptual_hash: u64,
+    /// Colour space of the source, the renditions are always converted to sRGB.
+    pub color_space: ColorSpace,
+}
+
+impl ImageBuffers {
+    /// How each file was encoded, to be stored once the photo is uploaded.
+    pub fn renditions_of(&self, photo_id: &str) -> Vec<PhotoRendition> {
+        self.renditions
+            .iter()
+            .flat_map(|(size, buffers)| {
+                [
+                    (ImageType::Jpeg, &buffers.jpeg),
+                    (ImageType::Webp, &buffers.webp),
+                ]
+                .map(|(kind, encoded)| PhotoRendition {
+                    photo_id: photo_id.to_string(),
+                    size: *size,
+                    kind,
+                    quality: encoded.quality,
+                    bytes: encoded.bytes.len() as i64,
+                    ssim: encoded.ssim,
+                    width: Some(buffers.width),
+                    height: Some(buffers.height),
+                    md5: Some(format!("{:x}", md5::compute(&encoded.bytes))),
+                })
+            })
+            .collect()
+    }
+}
+
+pub enum ImageProcess {
+    Opened(ColorSpace),
+    Processed(ProcessedPhoto),
+    BlurHash(String),
+    Palette(Palette),
+    PerceptualHash(u64),
+}
+
+pub type BuildHandle = JoinHandle<Result<(), Error>>;
+pub type MainHandle = JoinHandle<Result<Vec<BuildHandle>, Error>>;
+
+#[cfg(target_os = "windows")]
+static PACKAGE: Emoji<'_, '_> = Emoji("📦", "");
+#[cfg(not(target_os = "windows"))]
+static PACKAGE: Emoji<'_, '_> = Emoji("📦 ", "");
+#[cfg(target_os = "windows")]
+static DRAWER: Emoji<'_, '_> = Emoji("🗃️", "");
+#[cfg(not(target_os = "windows"))]
+static DRAWER: Emoji<'_, '_> = Emoji("🗃️  ", "");
+
+/// Creates buffers based on a path with a valid JPG image.
+/// These buffers do not have exif metadata, one JPEG and one WEBP (lossy) is built for each of the
+/// given renditions, each on its own thread. The watermark, when given, is drawn on the renditions
+/// that have it enabled. With `measure` the SSIM of every file is recorded, even without a target.
+pub fn start_build(
+    path: &Path,
+    renditions: &[&'static Rendition],
+    watermark: Option<Arc<Watermark>>,
+    measure: bool,
+    tx: Sender<ImageProcess>,
+) -> Result<MainHandle, Error> {
+    if !is_valid_extension(path) {
+        return Err(Error::Extension {
+            path: path.to_str().unwrap().to_string(),
+        });
+    }
+
+    let p = path.to_str().unwrap().to_string();
+    let renditions = renditions.to_vec();
+    let main_handle: MainHandle = thread::spawn(move || {
+        trace!("Opening Image");
+        let (img, color_space) = open_srgb(Path::new(&p)).context(OpenSnafu)?;
+        tx.send(ImageProcess::Opened(color_space))
+            .context(ThreadSendSnafu)?;
+
+        // Compute blurhash from a small thumbnail for performance
+        let thumb = img.thumbnail(100, 100);
+        let (w, h) = thumb.dimensions();
+        let rgba = thumb.to_rgba8();
+        let hash = blurhash::encode(4, 3, w, h, rgba.as_raw()).map_err(|e| Error::BlurHash {
+            error: e.to_string(),
+        })?;
+        tx.send(ImageProcess::BlurHash(hash))
+            .context(ThreadSendSnafu)?;
+
+        // The same thumbnail is enough for the palette and the perceptual hash.
+        tx.send(ImageProcess::Palette(extract_palette(&rgba)))
+            .context(ThreadSendSnafu)?;
+        tx.send(ImageProcess::PerceptualHash(dhash(&thumb)))
+            .context(ThreadSendSnafu)?;
+
+        let handles: Vec<BuildHandle> = renditions
+            .into_iter()
+            .map(|rendition| {
+                let img = img.clone();
+                let tx = tx.clone();
+                let watermark = watermark.clone();
+
+                thread::spawn(move || {
+                    trace!("Building {} Image", rendition.name);
+
+                    let processed =
+                        ProcessedPhoto::build(&img, rendition, watermark.as_deref(), measure)
+                            .context(ProcessSnafu)?;
+
+                    trace!("{} Image Processing completed", rendition.name);
+
+                    tx.send(ImageProcess::Processed(processed))
+                        .context(ThreadSendSnafu)
+                })
+            })
+            .collect();
+
+        Ok(handles)
+    });
+
+    Ok(main_handle)
+}
+
+pub fn finish_build(
+    rx: Receiver<ImageProcess>,
+    main_handle: MainHandle,
+    renditions: &[&'static Rendition],
+) -> Result<ImageBuffers, Error> {
+    let mut buffers: Vec<Option<ProcessedBuffers>> = renditions.iter().map(|_| None).collect();
+    let mut blurhash: Option<String> = None;
+    let mut palette: Option<Palette> = None;
+    let mut perceptual_hash: Option<u64> = None;
+    let mut color_space: Option<ColorSpace> = None;
+
+    let steps = renditions.len() + 1;
+
+    let m = MultiProgress::new();
+    let s = ProgressStyle::with_template("{prefix:.bold.dim} {spinner} {wide_msg}")
+        .unwrap()
+        .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ ");
+    let s_done = ProgressStyle::with_template("{prefix:.bold.dim} {wide_msg}").unwrap();
+
+    let opened_pb = build_loader(&m, &s, format!("{} Opening Image...", DRAWER), 1, steps);
+    // The smaller renditions finish first, so they are listed first.
+    let mut rendition_pbs: Vec<ProgressBar> = renditions
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, rendition)| {
+            let message = format!("{} Processing {} Image...", PACKAGE, rendition.name);
+            build_loader(&m, &s, message, steps - i, steps)
+        })
+        .collect();
+    rendition_pbs.reverse();
+
+    for process in rx {
+        match process {
+            ImageProcess::Opened(space) => {
+                color_space = Some(space);
+                opened_pb.set_style(s_done.clone());
+                opened_pb.set_prefix(format!("[1/{}] ✓", steps));
+                opened_pb.finish_with_message(format!("{} Image Opened", DRAWER));
+            }
+            ImageProcess::BlurHash(hash) => {
+                trace!("BlurHash computed: {}", hash);
+                blurhash = Some(hash);
+            }
+            ImageProcess::Palette(colors) => {
+                trace!("Palette computed: {:?}", colors);
+                palette = Some(colors);
+            }
+            ImageProcess::PerceptualHash(hash) => {
+                trace!("Perceptual hash computed: {:016x}", hash);
+                perceptual_hash = Some(hash);
+            }
+            ImageProcess::Processed(data) => {
+                // Only the requested renditions are ever built.
+                let Some(i) = renditions
+                    .iter()
+                    .position(|r| ImageSize::Rendition(r) == data.size)
+                else {
+                    continue;
+                };
+
+                let pb = &rendition_pbs[i];
+                pb.set_style(s_done.clone());
+                pb.set_prefix(format!("[{}/{}] ✓", steps - i, steps));
+                pb.finish_with_message(format!(
+                    "{} {} Image Processing Finished",
+                    PACKAGE, data.size
+                ));
+                buffers[i] = Some(data.buffers);
+            }
+        }
+    }
+
+    let handles = main_handle
+        .join()
+        .map_err(|e| Error::ThreadPanic { err: e })??;
+    for handle in handles {
+        handle.join().map_err(|e| Error::ThreadPanic { err: e })??;
+    }
+
+    m.clear().unwrap();
+
+    let renditions = renditions
+        .iter()
+        .zip(buffers)
+        .map(|(rendition, buffers)| buffers.map(|b| (ImageSize::Rendition(rendition), b)))
+        .collect::<Option<Vec<_>>>();
+
+    match (renditions, blurhash, palette, perceptual_hash, color_space) {
+        (
+            Some(renditions),
+            Some(blurhash),
+            Some(palette),
+            Some(perceptual_hash),
+            Some(color_space),
+        ) => Ok(ImageBuffers {
+            renditions,
+            blurhash,
+            palette,
+            perceptual_hash,
+            color_space,
+        }),
+        _ => Err(Error::MissingData),
+    }
+}
+
+fn build_loader(
+    m: &MultiProgress,
+    spinner_style: &ProgressStyle,
+    message: String,
+    no: usize,
+    steps: usize,
+) -> ProgressBar {
+    let pb = m.add(ProgressBar::new_spinner());
+    pb.enable_steady_tick(Duration::from_millis(50));
+    pb.set_style(spinner_style.clone());
+    pb.set_prefix(format!("[{}/{}]", no, steps));
+    pb.set_message(message);
+
+    pb
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Invalid file, wrong extension: {}", path))]
+    Extension { path: String },
+
+    #[snafu(display("Unable to open file: {}", source))]
+    Open { source: ColorError },
+
+    #[snafu(display("Failed to process photo: {}", source))]
+    Process { source: ProcessPhotoError },
+
+    #[snafu(display("Failed to send data through TX: {}", source))]
+    ThreadSend { source: SendError<ImageProcess> },
+
+    #[snafu(display("Something went wrong while making images"))]
+    MissingData,
+
+    #[snafu(display("Thread panicked: {:?}", err))]
+    ThreadPanic { err: Box<dyn Any + Send> },
+
+    #[snafu(display("Failed to compute BlurHash: {}", error))]
+    BlurHash { error: String },
+}
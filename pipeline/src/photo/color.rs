@@ -0,0 +1,231 @@
+use core_victorhqc_com::models::photo::ColorSpace;
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use log::{debug, warn};
+use snafu::prelude::*;
+use std::path::Path;
+
+/// Opens the image upright and converts it to sRGB when it embeds another ICC profile. The
+/// renditions are stored without a profile, which browsers read as sRGB, so an Adobe RGB or Display
+/// P3 original would otherwise look washed out. They're stored without EXIF either, so the
+/// rotation the camera recorded is applied to the pixels. A profile that can't be converted is
+/// ignored, the image is kept as decoded and its colour space is `Other`.
+pub fn open_srgb(path: &Path) -> Result<(DynamicImage, ColorSpace), Error> {
+    let mut decoder = ImageReader::open(path)
+        .context(ReadSnafu)?
+        .into_decoder()
+        .context(DecodeSnafu)?;
+    let icc = decoder.icc_profile().context(DecodeSnafu)?;
+    let orientation = decoder.orientation().context(DecodeSnafu)?;
+    let mut img = DynamicImage::from_decoder(decoder).context(DecodeSnafu)?;
+    img.apply_orientation(orientation);
+
+    let Some(icc) = icc else {
+        return Ok((img, ColorSpace::Srgb));
+    };
+
+    let color_space = color_space_of(&icc);
+    debug!("Embedded ICC profile: {}", color_space);
+
+    if color_space == ColorSpace::Srgb {
+        return Ok((img, color_space));
+    }
+
+    match to_srgb(&icc) {
+        Ok(transform) => {
+            let mut rgb = img.into_rgb8();
+            transform.apply(&mut rgb);
+
+            Ok((DynamicImage::ImageRgb8(rgb), color_space))
+        }
+        Err(e) => {
+            warn!("{} of {}, keeping its colours", e, path.display());
+
+            Ok((img, ColorSpace::Other))
+        }
+    }
+}
+
+fn to_srgb(icc: &[u8]) -> Result<qcms::Transform, Error> {
+    let input = qcms::Profile::new_from_slice(icc, false).context(ProfileSnafu)?;
+    let mut output = qcms::Profile::new_sRGB();
+    output.precache_output_transform();
+
+    qcms::Transform::new(
+        &input,
+        &output,
+        qcms::DataType::RGB8,
+        qcms::Intent::Perceptual,
+    )
+    .context(TransformSnafu)
+}
+
+/// Names the colour space from the profile description, which is what editors and cameras fill
+/// with e.g. `sRGB IEC61966-2.1`, `Adobe RGB (1998)` or `Display P3`.
+pub fn color_space_of(icc: &[u8]) -> ColorSpace {
+    let Some(description) = description(icc) else {
+        return ColorSpace::Other;
+    };
+
+    let description = description.to_lowercase();
+    if description.contains("srgb") || description.contains("iec61966") {
+        ColorSpace::Srgb
+    } else if description.contains("adobe rgb") || description.contains("adobergb") {
+        ColorSpace::AdobeRgb
+    } else if description.contains("p3") {
+        ColorSpace::DisplayP3
+    } else {
+        ColorSpace::Other
+    }
+}
+
+/// Reads the `desc` tag of the profile. The tag table starts right after the 128 bytes header,
+/// each entry being the signature, offset and size of a tag.
+fn description(icc: &[u8]) -> Option<String> {
+    let count = read_u32(icc, 128)? as usize;
+
+    for i in 0..count {
+        let entry = 132 + i * 12;
+        if icc.get(entry..entry + 4)? != b"desc" {
+            continue;
+        }
+
+        let offset = read_u32(icc, entry + 4)? as usize;
+        let size = read_u32(icc, entry + 8)? as usize;
+
+        return text(icc.get(offset..offset + size)?);
+    }
+
+    None
+}
+
+fn text(tag: &[u8]) -> Option<String> {
+    match tag.get(0..4)? {
+        // ICC v2, a plain ASCII string.
+        b"desc" => {
+            let len = read_u32(tag, 8)? as usize;
+            let text = tag.get(12..12 + len)?;
+
+            Some(
+                String::from_utf8_lossy(text)
+                    .trim_end_matches('\0')
+                    .to_string(),
+            )
+        }
+        // ICC v4, UTF-16 strings per language. The first one is enough to tell the space apart.
+        b"mluc" => {
+            let len = read_u32(tag, 20)? as usize;
+            let offset = read_u32(tag, 24)? as usize;
+            let units: Vec<u16> = tag
+                .get(offset..offset + len)?
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect();
+
+            Some(String::from_utf16_lossy(&units))
+        }
+        _ => None,
+    }
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    let bytes = bytes.get(at..at + 4)?;
+
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to read image: {}", source))]
+    Read { source: std::io::Error },
+
+    #[snafu(display("Failed to decode image: {}", source))]
+    Decode { source: image::ImageError },
+
+    #[snafu(display("Failed to parse the embedded ICC profile"))]
+    Profile,
+
+    #[snafu(display("Failed to convert the embedded ICC profile to sRGB"))]
+    Transform,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageEncoder, RgbImage, codecs::png::PngEncoder};
+
+    /// A profile with only a header and a `desc` tag.
+    fn profile(tag: Vec<u8>) -> Vec<u8> {
+        let mut icc = vec![0u8; 128];
+        icc.extend_from_slice(&1u32.to_be_bytes());
+        icc.extend_from_slice(b"desc");
+        icc.extend_from_slice(&144u32.to_be_bytes());
+        icc.extend_from_slice(&(tag.len() as u32).to_be_bytes());
+        icc.extend(tag);
+
+        icc
+    }
+
+    fn v2(name: &str) -> Vec<u8> {
+        let mut tag = b"desc".to_vec();
+        tag.extend_from_slice(&[0; 4]);
+        tag.extend_from_slice(&(name.len() as u32 + 1).to_be_bytes());
+        tag.extend_from_slice(name.as_bytes());
+        tag.push(0);
+
+        profile(tag)
+    }
+
+    fn v4(name: &str) -> Vec<u8> {
+        let text: Vec<u8> = name.encode_utf16().flat_map(|u| u.to_be_bytes()).collect();
+
+        let mut tag = b"mluc".to_vec();
+        tag.extend_from_slice(&[0; 4]);
+        tag.extend_from_slice(&1u32.to_be_bytes());
+        tag.extend_from_slice(&12u32.to_be_bytes());
+        tag.extend_from_slice(b"enUS");
+        tag.extend_from_slice(&(text.len() as u32).to_be_bytes());
+        tag.extend_from_slice(&28u32.to_be_bytes());
+        tag.extend(text);
+
+        profile(tag)
+    }
+
+    #[test]
+    fn it_should_read_v2_descriptions() {
+        assert_eq!(color_space_of(&v2("sRGB IEC61966-2.1")), ColorSpace::Srgb);
+        assert_eq!(
+            color_space_of(&v2("Adobe RGB (1998)")),
+            ColorSpace::AdobeRgb
+        );
+    }
+
+    #[test]
+    fn it_should_read_v4_descriptions() {
+        assert_eq!(color_space_of(&v4("Display P3")), ColorSpace::DisplayP3);
+        assert_eq!(color_space_of(&v4("ProPhoto RGB")), ColorSpace::Other);
+    }
+
+    #[test]
+    fn it_should_keep_the_colours_of_unsupported_profiles() {
+        let path =
+            std::env::temp_dir().join("it_should_keep_the_colours_of_unsupported_profiles.png");
+        let img = RgbImage::from_pixel(2, 2, image::Rgb([10, 20, 30]));
+        let mut encoder = PngEncoder::new(std::fs::File::create(&path).unwrap());
+        encoder.set_icc_profile(v2("Display P3")).unwrap();
+        encoder
+            .write_image(img.as_raw(), 2, 2, image::ExtendedColorType::Rgb8)
+            .unwrap();
+
+        let (opened, color_space) = open_srgb(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(color_space, ColorSpace::Other);
+        assert_eq!(opened.into_rgb8(), img);
+    }
+
+    #[test]
+    fn it_should_not_panic_with_broken_profiles() {
+        assert_eq!(color_space_of(&[0u8; 40]), ColorSpace::Other);
+        assert_eq!(color_space_of(&v2("Display P3")[..140]), ColorSpace::Other);
+    }
+}
@@ -0,0 +1,8 @@
+pub mod aws;
+pub mod build_images;
+pub mod color;
+pub mod orientation;
+pub mod palette;
+pub mod perceptual_hash;
+pub mod process;
+pub mod watermark;
@@ -0,0 +1,37 @@
+use std::path::Path;
+
+use core_victorhqc_com::models::photo::Orientation;
+use image::{ImageDecoder, ImageReader, metadata::Orientation as ExifOrientation};
+use snafu::prelude::*;
+
+/// Reads the orientation and aspect ratio of the photo as it is meant to be seen, so a portrait
+/// shot stored sideways with an EXIF rotation counts as portrait. Only the header is read.
+pub fn get_orientation(path: &Path) -> Result<(Orientation, f64), OrientationError> {
+    let mut decoder = ImageReader::open(path)
+        .context(ImageReaderSnafu)?
+        .into_decoder()
+        .context(ImageDecodeSnafu)?;
+
+    let (width, height) = decoder.dimensions();
+    let (width, height) = match decoder.orientation().context(ImageDecodeSnafu)? {
+        ExifOrientation::Rotate90
+        | ExifOrientation::Rotate270
+        | ExifOrientation::Rotate90FlipH
+        | ExifOrientation::Rotate270FlipH => (height, width),
+        _ => (width, height),
+    };
+
+    Ok((
+        Orientation::from_dimensions(width, height),
+        width as f64 / height as f64,
+    ))
+}
+
+#[derive(Debug, Snafu)]
+pub enum OrientationError {
+    #[snafu(display("Failed to read image: {}", source))]
+    ImageReader { source: std::io::Error },
+
+    #[snafu(display("Failed to decode image: {}", source))]
+    ImageDecode { source: image::ImageError },
+}
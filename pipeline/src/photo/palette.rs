@@ -0,0 +1,139 @@
+use core_victorhqc_com::utils::color::{Palette, Rgb};
+use image::RgbaImage;
+
+/// How many colours the palette has.
+const COLORS: usize = 5;
+const ITERATIONS: usize = 10;
+
+/// Finds the most common colours with k-means over the pixels. It is meant for a thumbnail, the
+/// one used for the blurhash is enough and keeps it fast.
+///
+/// The initial centroids are picked farthest-first starting from the average colour, so the same
+/// image always gets the same palette.
+pub fn extract_palette(img: &RgbaImage) -> Palette {
+    let pixels: Vec<[f64; 3]> = img
+        .pixels()
+        .filter(|p| p.0[3] > 0)
+        .map(|p| [p.0[0] as f64, p.0[1] as f64, p.0[2] as f64])
+        .collect();
+
+    if pixels.is_empty() {
+        let black = Rgb::new(0, 0, 0);
+        return Palette {
+            colors: vec![black],
+            dominant: black,
+        };
+    }
+
+    let mut centroids = vec![mean(pixels.iter())];
+    while centroids.len() < COLORS {
+        let farthest = pixels
+            .iter()
+            .max_by(|a, b| {
+                nearest(&centroids, a)
+                    .1
+                    .total_cmp(&nearest(&centroids, b).1)
+            })
+            .unwrap();
+
+        // Every pixel is already a centroid, the image has fewer colours than the palette.
+        if nearest(&centroids, farthest).1 == 0.0 {
+            break;
+        }
+        centroids.push(*farthest);
+    }
+
+    let mut assignments = vec![0; pixels.len()];
+    for _ in 0..ITERATIONS {
+        for (pixel, assignment) in pixels.iter().zip(assignments.iter_mut()) {
+            *assignment = nearest(&centroids, pixel).0;
+        }
+
+        for (i, centroid) in centroids.iter_mut().enumerate() {
+            let members = pixels
+                .iter()
+                .zip(&assignments)
+                .filter(|(_, a)| **a == i)
+                .map(|(p, _)| p);
+
+            if assignments.contains(&i) {
+                *centroid = mean(members);
+            }
+        }
+    }
+
+    let mut clusters: Vec<(usize, [f64; 3])> = centroids
+        .into_iter()
+        .enumerate()
+        .map(|(i, c)| (assignments.iter().filter(|a| **a == i).count(), c))
+        .filter(|(count, _)| *count > 0)
+        .collect();
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.0));
+
+    let colors: Vec<Rgb> = clusters
+        .into_iter()
+        .map(|(_, [r, g, b])| Rgb::new(r.round() as u8, g.round() as u8, b.round() as u8))
+        .collect();
+
+    Palette {
+        dominant: colors[0],
+        colors,
+    }
+}
+
+fn mean<'a>(pixels: impl Iterator<Item = &'a [f64; 3]>) -> [f64; 3] {
+    let mut sum = [0.0; 3];
+    let mut count = 0.0;
+    for pixel in pixels {
+        sum[0] += pixel[0];
+        sum[1] += pixel[1];
+        sum[2] += pixel[2];
+        count += 1.0;
+    }
+
+    [sum[0] / count, sum[1] / count, sum[2] / count]
+}
+
+/// Index of the closest centroid and its squared distance.
+fn nearest(centroids: &[[f64; 3]], pixel: &[f64; 3]) -> (usize, f64) {
+    centroids
+        .iter()
+        .map(|c| (c[0] - pixel[0]).powi(2) + (c[1] - pixel[1]).powi(2) + (c[2] - pixel[2]).powi(2))
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn it_finds_the_dominant_colour() {
+        let img = RgbaImage::from_fn(10, 10, |x, _| {
+            if x < 7 {
+                Rgba([200, 30, 30, 255])
+            } else {
+                Rgba([20, 40, 160, 255])
+            }
+        });
+
+        let palette = extract_palette(&img);
+
+        assert_eq!(palette.dominant, Rgb::new(200, 30, 30));
+        assert_eq!(
+            palette.colors,
+            vec![Rgb::new(200, 30, 30), Rgb::new(20, 40, 160)]
+        );
+    }
+
+    #[test]
+    fn it_has_at_most_five_colours() {
+        let img = RgbaImage::from_fn(20, 20, |x, y| {
+            Rgba([(x * 12) as u8, (y * 12) as u8, 90, 255])
+        });
+
+        assert_eq!(extract_palette(&img).colors.len(), COLORS);
+    }
+}
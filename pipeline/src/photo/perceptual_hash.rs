@@ -0,0 +1,51 @@
+use image::{DynamicImage, imageops::FilterType::Triangle};
+
+/// dHash of the image: it is shrunk to 9x8 in greyscale and each bit tells whether a pixel is
+/// brighter than the one to its right. Resizing, recompressing or a small edit barely changes it,
+/// so photos that look alike end up a few bits apart.
+pub fn dhash(img: &DynamicImage) -> u64 {
+    let small = img.resize_exact(9, 8, Triangle).to_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y).0[0];
+            let right = small.get_pixel(x + 1, y).0[0];
+
+            hash = (hash << 1) | (left > right) as u64;
+        }
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_victorhqc_com::models::photo::hash_distance;
+    use image::{GrayImage, Luma};
+
+    /// Brightens to the right on the top half and to the left on the bottom one.
+    fn scene(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageLuma8(GrayImage::from_fn(width, height, |x, y| {
+            let value = (x * 255 / width) as u8;
+            Luma([if y < height / 2 { value } else { 255 - value }])
+        }))
+    }
+
+    #[test]
+    fn it_survives_resizing() {
+        let big = scene(640, 480);
+        let small = scene(320, 240);
+
+        assert!(hash_distance(dhash(&big), dhash(&small)) <= 4);
+    }
+
+    #[test]
+    fn it_tells_different_images_apart() {
+        let img = scene(640, 480);
+        let flipped = img.fliph();
+
+        assert!(hash_distance(dhash(&img), dhash(&flipped)) > 32);
+    }
+}
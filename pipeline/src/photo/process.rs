@@ -0,0 +1,270 @@
+use super::watermark::Watermark;
+use crate::utils::ssim;
+use core_victorhqc_com::aws::{image_size::ImageSize, rendition::Rendition};
+use image::{
+    DynamicImage, GenericImageView, ImageFormat, codecs::jpeg::JpegEncoder, error::ImageError,
+    imageops::FilterType::Lanczos3,
+};
+use log::{debug, trace};
+use snafu::prelude::*;
+use std::io::Cursor;
+
+pub struct ProcessedPhoto {
+    pub size: ImageSize,
+    pub buffers: ProcessedBuffers,
+}
+
+pub struct ProcessedBuffers {
+    pub jpeg: Encoded,
+    pub webp: Encoded,
+    /// Dimensions of the resized image, both files share them.
+    pub width: u32,
+    pub height: u32,
+}
+
+/// An encoded rendition, with the quality it was encoded with and how close it looks to the
+/// resized image it came from, when that was measured.
+pub struct Encoded {
+    pub bytes: Vec<u8>,
+    pub quality: u8,
+    pub ssim: Option<f64>,
+}
+
+/// Lowest quality tried when searching for a target SSIM.
+const MIN_QUALITY: u8 = 30;
+
+/// How many times the short edge the long edge can be. Panoramas would otherwise end up several
+/// times wider than the screens they're shown on.
+const MAX_LONG_EDGE_RATIO: f32 = 2.0;
+
+impl ProcessedPhoto {
+    /// Resizes the image for the rendition and encodes it. The watermark is only drawn on the
+    /// renditions that have it enabled. Decoding the files to measure their SSIM takes about as
+    /// long as encoding them, so it's only done with `measure` or when the rendition targets one.
+    pub fn build(
+        img: &DynamicImage,
+        rendition: &'static Rendition,
+        watermark: Option<&Watermark>,
+        measure: bool,
+    ) -> Result<Self, Error> {
+        Self::build_with_target(img, rendition, rendition.target_ssim, watermark, measure)
+    }
+
+    /// Same as `build`, but targets the given SSIM instead of the one set on the rendition.
+    pub fn build_with_target(
+        img: &DynamicImage,
+        rendition: &'static Rendition,
+        target_ssim: Option<f64>,
+        watermark: Option<&Watermark>,
+        measure: bool,
+    ) -> Result<Self, Error> {
+        let size = ImageSize::Rendition(rendition);
+
+        debug!("Resizing {} Image", size);
+        let resized = resize_with_known_dimensions(img, rendition.short_edge as i32);
+        let resized = match &rendition.sharpen {
+            Some(sharpen) => {
+                debug!("Sharpening {} Image", size);
+                resized.unsharpen(sharpen.sigma, sharpen.threshold)
+            }
+            None => resized,
+        };
+        let resized = match watermark {
+            Some(watermark) if rendition.watermark => {
+                debug!("Watermarking {} Image", size);
+                watermark.apply(&resized)
+            }
+            _ => resized,
+        };
+
+        debug!("Converting to Webp");
+        let webp = encode(
+            rendition.webp_quality,
+            target_ssim,
+            measure,
+            |quality| convert_to_webp(&resized, quality as f32),
+            |bytes| {
+                let decoded = webp::Decoder::new(bytes)
+                    .decode()
+                    .context(WebpDecodeSnafu)?
+                    .to_image();
+
+                Ok(ssim(&resized, &decoded))
+            },
+        )?;
+
+        debug!("Converting to JPEG");
+        let jpeg = encode(
+            rendition.jpeg_quality,
+            target_ssim,
+            measure,
+            |quality| compress(&resized, quality),
+            |bytes| {
+                let decoded = image::load_from_memory_with_format(bytes, ImageFormat::Jpeg)
+                    .context(JpegDecodeSnafu)?;
+
+                Ok(ssim(&resized, &decoded))
+            },
+        )?;
+
+        debug!(
+            "{} Image encoded, JPEG at {} (SSIM {:?}), WEBP at {} (SSIM {:?})",
+            size, jpeg.quality, jpeg.ssim, webp.quality, webp.ssim
+        );
+
+        let (width, height) = resized.dimensions();
+
+        Ok(ProcessedPhoto {
+            size,
+            buffers: ProcessedBuffers {
+                webp,
+                jpeg,
+                width,
+                height,
+            },
+        })
+    }
+}
+
+/// Encodes with `max_quality`. When there is a target SSIM, it binary-searches the lowest quality
+/// between `MIN_QUALITY` and `max_quality` that still reaches it. If not even `max_quality` does,
+/// `max_quality` is kept. Without a target the SSIM is only measured with `measure`.
+fn encode<E, M>(
+    max_quality: u8,
+    target_ssim: Option<f64>,
+    measure: bool,
+    encode_with: E,
+    measure_with: M,
+) -> Result<Encoded, Error>
+where
+    E: Fn(u8) -> Result<Vec<u8>, Error>,
+    M: Fn(&[u8]) -> Result<f64, Error>,
+{
+    let bytes = encode_with(max_quality)?;
+
+    let Some(target) = target_ssim else {
+        let ssim = if measure {
+            Some(measure_with(&bytes)?)
+        } else {
+            None
+        };
+
+        return Ok(Encoded {
+            bytes,
+            quality: max_quality,
+            ssim,
+        });
+    };
+
+    let ssim = measure_with(&bytes)?;
+    let mut best = Encoded {
+        bytes,
+        quality: max_quality,
+        ssim: Some(ssim),
+    };
+
+    if ssim < target {
+        debug!(
+            "Quality {} only reaches SSIM {:.4}, below the {} target",
+            max_quality, ssim, target
+        );
+        return Ok(best);
+    }
+
+    // `high` always reaches the target, `low` is the lowest quality that still might.
+    let (mut low, mut high) = (MIN_QUALITY, max_quality);
+    while low < high {
+        let quality = low + (high - low) / 2;
+        let bytes = encode_with(quality)?;
+        let ssim = measure_with(&bytes)?;
+        trace!("Quality {} reaches SSIM {:.4}", quality, ssim);
+
+        if ssim >= target {
+            high = quality;
+            best = Encoded {
+                bytes,
+                quality,
+                ssim: Some(ssim),
+            };
+        } else {
+            low = quality + 1;
+        }
+    }
+
+    Ok(best)
+}
+
+/// This Function will resize the image to a known height. However, the height changes depending on
+/// the photo. The height will always be the small side of the rectangle, meaning that it will be
+/// the regular height when it is in landscape, but in a portrait photo, the height would be
+/// technically, the width. Why this way? To keep the image sizes consistant.
+fn resize_with_known_dimensions(img: &DynamicImage, wanted_height: i32) -> DynamicImage {
+    let (width, height) = img.dimensions();
+
+    let is_landscape = width > height;
+
+    debug!("Is landscape? {}", is_landscape);
+
+    let current_height = if is_landscape { height } else { width };
+    debug!("Current height: {}", current_height);
+
+    // To figure out the wanted width we must calculate how much percentage the wanted height
+    // represents compared with the current height. If the current height is 10800 and the wanted
+    // height is 1080, then the percentage would be 10% (1080 * 100 / 10800) so we should calculate
+    // the width as 10% of its current size.
+    let percentage: f32 = (wanted_height as f32 * 100.00) / current_height as f32;
+
+    // Panoramas are sized by their long edge instead, so they don't come out wider than twice the
+    // wanted height.
+    let current_width = if is_landscape { width } else { height };
+    let wanted_width = wanted_height as f32 * MAX_LONG_EDGE_RATIO;
+    let percentage = percentage.min((wanted_width * 100.00) / current_width as f32);
+    debug!("Percentage to shrink: {}", percentage);
+
+    resize(img, percentage / 100.00)
+}
+
+fn resize(img: &DynamicImage, percentage: f32) -> DynamicImage {
+    let (width, height) = img.dimensions();
+
+    let width = (width as f32 * percentage).round() as u32;
+    let height = (height as f32 * percentage).round() as u32;
+
+    debug!("New dimensions: {} height, {} width", height, width);
+
+    img.resize_exact(width, height, Lanczos3)
+}
+
+fn compress(img: &DynamicImage, quality: u8) -> Result<Vec<u8>, Error> {
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut cursor = Cursor::new(&mut buffer);
+
+    let encoder = JpegEncoder::new_with_quality(&mut cursor, quality);
+    img.write_with_encoder(encoder).context(JpegSnafu)?;
+
+    Ok(buffer)
+}
+
+fn convert_to_webp(img: &DynamicImage, quality: f32) -> Result<Vec<u8>, Error> {
+    let encoder = webp::Encoder::from_image(img).map_err(|e| Error::Webp {
+        error: e.to_string(),
+    })?;
+    let webp: webp::WebPMemory = encoder.encode(quality);
+
+    Ok(webp.to_vec())
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to encode JPEG: {}", source))]
+    Jpeg { source: ImageError },
+
+    #[snafu(display("Failed to encode WEBP: {}", error))]
+    Webp { error: String },
+
+    #[snafu(display("Failed to decode the encoded JPEG: {}", source))]
+    JpegDecode { source: ImageError },
+
+    #[snafu(display("Failed to decode the encoded WEBP"))]
+    WebpDecode,
+}
@@ -0,0 +1,205 @@
+use core_victorhqc_com::aws::rendition::{WATERMARK, WatermarkPosition, WatermarkSettings};
+use image::{DynamicImage, RgbaImage, imageops::FilterType::Lanczos3};
+use resvg::{tiny_skia, usvg};
+use snafu::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Length of the long side SVG marks are rasterised to. It's well above the size the mark is drawn
+/// at in the biggest rendition, so it is only ever scaled down.
+const SVG_SIZE: f32 = 1024.0;
+
+/// A mark drawn over the renditions to credit the photo when it gets reposted.
+pub struct Watermark {
+    mark: RgbaImage,
+    settings: WatermarkSettings,
+}
+
+impl Watermark {
+    pub fn open(path: &Path, settings: WatermarkSettings) -> Result<Self, Error> {
+        let is_svg = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+
+        let mark = if is_svg {
+            let data = std::fs::read(path).context(ReadSnafu)?;
+            rasterize(&data)?
+        } else {
+            image::open(path).context(DecodeSnafu)?.to_rgba8()
+        };
+
+        Ok(Watermark { mark, settings })
+    }
+
+    /// The mark set in `WATERMARK_PATH`, drawn as configured in [`WATERMARK`]. `None` when there
+    /// is no mark set, the renditions are then built without it.
+    pub fn from_env() -> Result<Option<Self>, Error> {
+        match env_path() {
+            Some(path) => Self::open(&path, WATERMARK).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Draws the mark over the image, sized and placed relative to its short edge.
+    pub fn apply(&self, img: &DynamicImage) -> DynamicImage {
+        let mut canvas = img.to_rgb8();
+        let (width, height) = canvas.dimensions();
+        let short_edge = width.min(height) as f32;
+
+        let (mark_width, mark_height) = self.mark.dimensions();
+        let ratio = short_edge * self.settings.scale / mark_width.max(mark_height) as f32;
+        let mark_width = ((mark_width as f32 * ratio).round() as u32).clamp(1, width);
+        let mark_height = ((mark_height as f32 * ratio).round() as u32).clamp(1, height);
+        let mark = image::imageops::resize(&self.mark, mark_width, mark_height, Lanczos3);
+
+        let margin = (short_edge * self.settings.margin).round() as u32;
+        let (x, y) = position(
+            self.settings.position,
+            (width, height),
+            (mark_width, mark_height),
+            margin,
+        );
+
+        for (mx, my, pixel) in mark.enumerate_pixels() {
+            let alpha = pixel.0[3] as f32 / 255.0 * self.settings.opacity;
+            if alpha <= 0.0 {
+                continue;
+            }
+
+            let target = canvas.get_pixel_mut(x + mx, y + my);
+            for c in 0..3 {
+                let blended = pixel.0[c] as f32 * alpha + target.0[c] as f32 * (1.0 - alpha);
+                target.0[c] = blended.round() as u8;
+            }
+        }
+
+        DynamicImage::ImageRgb8(canvas)
+    }
+}
+
+/// Path in `WATERMARK_PATH`, an empty value counts as unset.
+pub fn env_path() -> Option<PathBuf> {
+    std::env::var_os("WATERMARK_PATH")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Top left corner of the mark, kept inside the image even when the margin doesn't fit.
+fn position(
+    position: WatermarkPosition,
+    (width, height): (u32, u32),
+    (mark_width, mark_height): (u32, u32),
+    margin: u32,
+) -> (u32, u32) {
+    let right = width.saturating_sub(mark_width + margin);
+    let bottom = height.saturating_sub(mark_height + margin);
+    let left = margin.min(width - mark_width);
+    let top = margin.min(height - mark_height);
+
+    match position {
+        WatermarkPosition::TopLeft => (left, top),
+        WatermarkPosition::TopRight => (right, top),
+        WatermarkPosition::BottomLeft => (left, bottom),
+        WatermarkPosition::BottomRight => (right, bottom),
+        WatermarkPosition::Center => ((width - mark_width) / 2, (height - mark_height) / 2),
+    }
+}
+
+fn rasterize(data: &[u8]) -> Result<RgbaImage, Error> {
+    let tree = usvg::Tree::from_data(data, &usvg::Options::default()).context(SvgSnafu)?;
+
+    let size = tree.size();
+    let scale = SVG_SIZE / size.width().max(size.height());
+    let width = (size.width() * scale).round() as u32;
+    let height = (size.height() * scale).round() as u32;
+
+    let mut pixmap = tiny_skia::Pixmap::new(width, height).context(SvgSizeSnafu)?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    // The pixmap is premultiplied, the blending above expects straight alpha.
+    let pixels: Vec<u8> = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|p| {
+            let c = p.demultiply();
+            [c.red(), c.green(), c.blue(), c.alpha()]
+        })
+        .collect();
+
+    RgbaImage::from_raw(width, height, pixels).context(SvgSizeSnafu)
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to read the watermark: {}", source))]
+    Read { source: std::io::Error },
+
+    #[snafu(display("Failed to decode the watermark: {}", source))]
+    Decode { source: image::ImageError },
+
+    #[snafu(display("Failed to parse the SVG watermark: {}", source))]
+    Svg { source: usvg::Error },
+
+    #[snafu(display("The SVG watermark has no size"))]
+    SvgSize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, Rgba};
+
+    fn watermark(position: WatermarkPosition, opacity: f32) -> Watermark {
+        Watermark {
+            mark: RgbaImage::from_pixel(10, 10, Rgba([255, 255, 255, 255])),
+            settings: WatermarkSettings {
+                position,
+                opacity,
+                scale: 0.1,
+                margin: 0.05,
+            },
+        }
+    }
+
+    fn black(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_pixel(width, height, Rgb([0, 0, 0])))
+    }
+
+    #[test]
+    fn it_sizes_and_places_relative_to_the_short_edge() {
+        let img = watermark(WatermarkPosition::BottomRight, 1.0)
+            .apply(&black(300, 200))
+            .to_rgb8();
+
+        // 20px mark with a 10px margin, in the bottom right corner.
+        assert_eq!(img.get_pixel(269, 169), &Rgb([0, 0, 0]));
+        assert_eq!(img.get_pixel(270, 170), &Rgb([255, 255, 255]));
+        assert_eq!(img.get_pixel(289, 189), &Rgb([255, 255, 255]));
+        assert_eq!(img.get_pixel(290, 190), &Rgb([0, 0, 0]));
+    }
+
+    #[test]
+    fn it_blends_with_the_opacity() {
+        let img = watermark(WatermarkPosition::TopLeft, 0.5)
+            .apply(&black(200, 200))
+            .to_rgb8();
+
+        assert_eq!(img.get_pixel(15, 15), &Rgb([128, 128, 128]));
+        assert_eq!(img.get_pixel(5, 5), &Rgb([0, 0, 0]));
+    }
+
+    #[test]
+    fn it_keeps_the_mark_inside_small_images() {
+        assert_eq!(
+            position(WatermarkPosition::BottomRight, (20, 20), (18, 18), 5),
+            (0, 0)
+        );
+        assert_eq!(
+            position(WatermarkPosition::TopLeft, (20, 20), (18, 18), 5),
+            (2, 2)
+        );
+    }
+}
@@ -0,0 +1,323 @@
+//! Publishing a new photo, the same way from the CLI and from the API. The images are built and
+//! uploaded first, the photo is only saved once its files are in the bucket, so no transaction is
+//! held while they are sent.
+use crate::{
+    exiftool,
+    photo::{
+        aws::{Error as AWSError, remove, upload, upload_original},
+        build_images::{Error as BuildImagesError, ImageProcess, finish_build, start_build},
+        orientation::{OrientationError, get_orientation},
+        watermark::{Error as WatermarkError, Watermark},
+    },
+    utils::{
+        GetFujifilmError, GetGearError, SomeFujifilmRecipe, find_some_fujifilm_recipe, get_gear,
+        sha256,
+    },
+};
+use core_victorhqc_com::{
+    aws::{S3, rendition::all_renditions},
+    models::{
+        exif_meta::{
+            ExifMeta, PhotographyDetails,
+            db::Error as ExifMetaDbError,
+            from_exif::{PhotographyDetailsError, TryFromExifData},
+        },
+        photo::{Error as PhotoError, Photo, db::Error as PhotoDbError},
+        photo_rendition::{PhotoRendition, db::Error as PhotoRenditionDbError},
+    },
+    sqlx::{SqlitePool, error::Error as SqlxError},
+};
+use itertools::Itertools;
+use snafu::prelude::*;
+use std::{
+    path::Path,
+    sync::{Arc, mpsc},
+};
+use tokio::task::{JoinError, spawn_blocking};
+
+/// Perceptual hashes this close, out of 64 bits, are most likely the same photo.
+const NEAR_DUPLICATE_DISTANCE: u32 = 8;
+
+/// Answers the questions of [`publish`], a person at the terminal or the fields of an upload.
+pub trait Answers: Send {
+    /// Asked while the images are built.
+    fn title(&mut self) -> String;
+
+    fn tags(&mut self) -> Vec<String>;
+
+    /// Name of a recipe seen for the first time, `None` cancels the upload.
+    fn recipe_name(&mut self) -> Option<String>;
+
+    /// Whether to go on with a photo that looks like some already uploaded, with how many bits
+    /// apart each one is.
+    fn upload_near_duplicate(&mut self, duplicates: &[(u32, Photo)]) -> bool;
+}
+
+/// Tags typed as a comma separated list, lowercase and without repetitions.
+pub fn parse_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .unique()
+        .collect()
+}
+
+/// Builds, uploads and stores a new photo. Reading the file and building the images block, so
+/// they run on their own threads. Nothing is saved unless every step succeeds. With `watermark`
+/// unset the photo opts out of the watermark, the renditions that allow it are then built without
+/// it, now and on every rebuild.
+pub async fn publish<A: Answers>(
+    pool: &SqlitePool,
+    src: &Path,
+    s3: &S3,
+    watermark: bool,
+    answers: &mut A,
+) -> Result<Photo, Error> {
+    let mut conn = pool.acquire().await.context(ConnectionSnafu)?;
+
+    if Photo::find_by_filename(&mut conn, src)
+        .await
+        .context(PathPhotoSnafu)?
+        .is_some()
+    {
+        return Err(Error::PhotoExists {
+            path: src.to_string_lossy().to_string(),
+        });
+    }
+
+    let data = {
+        let src = src.to_path_buf();
+        spawn_blocking(move || exiftool::spawn::read_metadata(&src))
+            .await
+            .context(JoinSnafu)?
+            .context(ExiftoolSnafu)?
+    };
+    trace!("Exiftool parsed data: {:?}", data);
+
+    let (orientation, aspect_ratio) = {
+        let src = src.to_path_buf();
+        spawn_blocking(move || get_orientation(&src))
+            .await
+            .context(JoinSnafu)?
+            .context(OrientationSnafu)?
+    };
+
+    let mark = if watermark {
+        spawn_blocking(Watermark::from_env)
+            .await
+            .context(JoinSnafu)?
+            .context(WatermarkSnafu)?
+            .map(Arc::new)
+    } else {
+        None
+    };
+
+    debug!("Building Images to upload");
+    let (tx, rx) = mpsc::channel::<ImageProcess>();
+    let renditions = all_renditions();
+    let main_handle = start_build(src, &renditions, mark, false, tx).context(BuildImagesSnafu)?;
+
+    let title = answers.title();
+    trace!("Title: {}", title);
+    let tags = answers.tags();
+    debug!("Tags: {:?}", tags);
+
+    // A new recipe is named now, so an upload without a name stops before sending anything.
+    let recipe = find_some_fujifilm_recipe(&data, &mut conn, || answers.recipe_name())
+        .await
+        .context(FujifilmRecipeSnafu)?;
+
+    let photography_details =
+        PhotographyDetails::try_from_exif(data.as_slice()).context(PhotographyDetailsSnafu)?;
+    debug!("{:?}", photography_details);
+
+    let mut photo = Photo::new(title, src, orientation).context(NewPhotoSnafu)?;
+    photo.set_aspect_ratio(aspect_ratio);
+    photo.set_watermark(watermark);
+
+    let buffers = spawn_blocking(move || finish_build(rx, main_handle, &renditions))
+        .await
+        .context(JoinSnafu)?
+        .context(BuildImagesSnafu)?;
+
+    // A different filename doesn't mean a different photo, e.g. an export of one already uploaded.
+    let similar = Photo::find_similar(&mut conn, &photo.id, buffers.perceptual_hash, 5)
+        .await
+        .context(SimilarPhotosSnafu)?;
+    let duplicates: Vec<(u32, Photo)> = similar
+        .into_iter()
+        .filter(|(distance, _)| *distance <= NEAR_DUPLICATE_DISTANCE)
+        .collect();
+
+    ensure!(
+        duplicates.is_empty() || answers.upload_near_duplicate(&duplicates),
+        NearDuplicateSnafu {
+            ids: duplicates
+                .into_iter()
+                .map(|(_, p)| p.id)
+                .collect::<Vec<_>>()
+        }
+    );
+
+    // Not needed while the files are sent.
+    drop(conn);
+
+    let (original, original_sha256) = {
+        let src = src.to_path_buf();
+        spawn_blocking(move || {
+            let original = std::fs::read(&src)?;
+            let sha = sha256(&original);
+
+            Ok::<_, std::io::Error>((original, sha))
+        })
+        .await
+        .context(JoinSnafu)?
+        .context(ReadOriginalSnafu)?
+    };
+
+    photo.set_blurhash(buffers.blurhash.clone());
+    photo.set_palette(buffers.palette.clone());
+    photo.set_perceptual_hash(buffers.perceptual_hash);
+    photo.set_color_space(buffers.color_space);
+    photo.set_original_sha256(original_sha256);
+    let photo_renditions = buffers.renditions_of(&photo.id);
+
+    debug!("About to upload to S3");
+    upload(&photo, s3, buffers)
+        .await
+        .map_err(Box::new)
+        .context(UploadSnafu)?;
+    upload_original(&photo, s3, original)
+        .await
+        .map_err(Box::new)
+        .context(UploadSnafu)?;
+    debug!("Uploaded to S3");
+
+    let saved = save(
+        pool,
+        &photo,
+        &tags,
+        recipe,
+        photography_details,
+        photo_renditions,
+    )
+    .await;
+
+    // Nothing points to the files that were just sent, the original is kept like on every removal.
+    if saved.is_err()
+        && let Err(e) = remove(&photo, s3).await
+    {
+        error!("Failed to remove the images of {}: {}", photo.id, e);
+    }
+
+    saved.map(|_| photo)
+}
+
+/// Saves the photo with everything it relates to, in a single transaction.
+async fn save(
+    pool: &SqlitePool,
+    photo: &Photo,
+    tags: &[String],
+    recipe: Option<SomeFujifilmRecipe>,
+    photography_details: PhotographyDetails,
+    renditions: Vec<PhotoRendition>,
+) -> Result<(), Error> {
+    let mut conn = pool.begin().await.context(TxSnafu)?;
+
+    let recipe = match recipe {
+        Some(recipe) => Some(recipe.save(&mut conn).await.context(FujifilmRecipeSnafu)?),
+        None => None,
+    };
+    debug!("{:?}", recipe);
+
+    photo.save(&mut conn).await.context(SavePhotoSnafu)?;
+    debug!("{:?}", photo);
+
+    photo
+        .save_tags(&mut conn, tags)
+        .await
+        .context(AttachTagsSnafu)?;
+
+    let (camera, lens) = get_gear(&photography_details, &mut conn)
+        .await
+        .context(GearSnafu)?;
+
+    let exif = ExifMeta::new(photography_details, photo, &recipe, &camera, &lens);
+    exif.save(&mut conn).await.context(SaveExifSnafu)?;
+    debug!("{:?}", exif);
+
+    for rendition in renditions {
+        rendition
+            .save(&mut conn)
+            .await
+            .context(SaveRenditionSnafu)?;
+    }
+
+    conn.commit().await.context(TxSnafu)
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to get connection: {}", source))]
+    Connection { source: SqlxError },
+
+    #[snafu(display("Failed to get EXIF: {}", source))]
+    Exiftool { source: exiftool::spawn::Error },
+
+    #[snafu(display("Could not find the PhotographyDetails from EXIF: {}", source))]
+    PhotographyDetails { source: PhotographyDetailsError },
+
+    #[snafu(display("Failed to build images: {}", source))]
+    BuildImages { source: BuildImagesError },
+
+    #[snafu(display("A blocking task failed: {}", source))]
+    Join { source: JoinError },
+
+    #[snafu(display("Failed to execute Transaction: {}", source))]
+    Tx { source: SqlxError },
+
+    #[snafu(display("Failed to get Fujifilm Recipe: {}", source))]
+    FujifilmRecipe { source: GetFujifilmError },
+
+    #[snafu(display("Failed to get the camera or lens: {}", source))]
+    Gear { source: GetGearError },
+
+    #[snafu(display("Failed to check for photo by path: {}", source))]
+    PathPhoto { source: PhotoDbError },
+
+    #[snafu(display("Photo with path {} already exists", path))]
+    PhotoExists { path: String },
+
+    #[snafu(display("Failed to look for similar photos: {}", source))]
+    SimilarPhotos { source: PhotoDbError },
+
+    #[snafu(display("Cancelled, the photo looks like {}", ids.join(", ")))]
+    NearDuplicate { ids: Vec<String> },
+
+    #[snafu(display("Failed to create a photo object: {}", source))]
+    NewPhoto { source: PhotoError },
+
+    #[snafu(display("Failed to read the original file: {}", source))]
+    ReadOriginal { source: std::io::Error },
+
+    #[snafu(display("Failed to upload the images: {}", source))]
+    Upload { source: Box<AWSError> },
+
+    #[snafu(display("Failed to save the photo: {}", source))]
+    SavePhoto { source: PhotoDbError },
+
+    #[snafu(display("Failed to save the renditions: {}", source))]
+    SaveRendition { source: PhotoRenditionDbError },
+
+    #[snafu(display("Failed to attach tags to the photo: {}", source))]
+    AttachTags { source: PhotoDbError },
+
+    #[snafu(display("Failed to save the EXIF data: {}", source))]
+    SaveExif { source: ExifMetaDbError },
+
+    #[snafu(display("Failed to get orientation: {}", source))]
+    Orientation { source: OrientationError },
+
+    #[snafu(display("Failed to load the watermark: {}", source))]
+    Watermark { source: WatermarkError },
+}
@@ -0,0 +1,42 @@
+use core_victorhqc_com::{
+    models::{
+        camera::{Camera, db::Error as CameraDbError},
+        exif_meta::PhotographyDetails,
+        lens::{Lens, db::Error as LensDbError},
+    },
+    sqlx::SqliteConnection,
+};
+use snafu::prelude::*;
+
+/// Finds the camera and lens used for the photograph, adding them to the catalogue the first time
+/// they show up.
+pub async fn get_gear<'a>(
+    details: &'a PhotographyDetails,
+    conn: &'a mut SqliteConnection,
+) -> Result<(Camera, Option<Lens>), GetGearError> {
+    let camera = Camera::find_by_name_or_create(conn, &details.camera_name, details.camera_maker)
+        .await
+        .context(CameraSnafu)?;
+    debug!("{:?}", camera);
+
+    let lens = match &details.lens_name {
+        Some(name) => Some(
+            Lens::find_by_name_or_create(conn, name, details.lens_maker)
+                .await
+                .context(LensSnafu)?,
+        ),
+        None => None,
+    };
+    debug!("{:?}", lens);
+
+    Ok((camera, lens))
+}
+
+#[derive(Debug, Snafu)]
+pub enum GetGearError {
+    #[snafu(display("Failed to get the camera: {}", source))]
+    Camera { source: CameraDbError },
+
+    #[snafu(display("Failed to get the lens: {}", source))]
+    Lens { source: LensDbError },
+}
@@ -0,0 +1,101 @@
+use core_victorhqc_com::{
+    models::{
+        exif_meta::CameraMaker,
+        fujifilm::{FujifilmRecipe, db::Error as FujifilmDbError},
+    },
+    sqlx::SqliteConnection,
+};
+use fuji::{
+    exif::{ExifData, FromExifData},
+    recipe::{FujifilmRecipe as _FujifilmRecipe, FujifilmRecipeDetails},
+};
+use snafu::prelude::*;
+
+/// The recipe of a photo, one already in the catalogue or one seen for the first time.
+pub enum SomeFujifilmRecipe {
+    Known(FujifilmRecipe),
+    /// Not saved yet, see [`SomeFujifilmRecipe::save`].
+    New(FujifilmRecipe),
+}
+
+impl SomeFujifilmRecipe {
+    /// Adds a new recipe to the catalogue, a known one is returned as it is.
+    pub async fn save(
+        self,
+        conn: &mut SqliteConnection,
+    ) -> Result<FujifilmRecipe, GetFujifilmError> {
+        match self {
+            SomeFujifilmRecipe::Known(recipe) => Ok(recipe),
+            SomeFujifilmRecipe::New(recipe) => {
+                recipe.save(conn).await.context(FujifilmSaveRecipeSnafu)?;
+
+                Ok(recipe)
+            }
+        }
+    }
+}
+
+/// The recipe the photo was taken with, if any. A recipe seen for the first time is saved with the
+/// name given by `name`, `None` cancels.
+pub async fn get_some_fujifilm_recipe(
+    data: &[ExifData],
+    conn: &mut SqliteConnection,
+    name: impl FnOnce() -> Option<String>,
+) -> Result<Option<FujifilmRecipe>, GetFujifilmError> {
+    match find_some_fujifilm_recipe(data, conn, name).await? {
+        Some(recipe) => Ok(Some(recipe.save(conn).await?)),
+        None => Ok(None),
+    }
+}
+
+/// Same as `get_some_fujifilm_recipe`, but a new recipe is only named, it's up to the caller to
+/// save it along with the photo.
+pub async fn find_some_fujifilm_recipe(
+    data: &[ExifData],
+    conn: &mut SqliteConnection,
+    name: impl FnOnce() -> Option<String>,
+) -> Result<Option<SomeFujifilmRecipe>, GetFujifilmError> {
+    let maker = CameraMaker::from_exif(data).context(MakerSnafu)?;
+    debug!("{:?}", maker);
+
+    if maker != CameraMaker::Fujifilm {
+        return Ok(None);
+    }
+
+    let recipe_details = FujifilmRecipeDetails::from_exif(data);
+    debug!("{:?}", recipe_details);
+
+    // Recipes are optional, not all the photos will have. Only SoC will.
+    let Some(recipe_details) = recipe_details else {
+        return Ok(None);
+    };
+
+    let recipe = FujifilmRecipe::find_by_details(conn, &recipe_details)
+        .await
+        .context(FujifilmFindRecipeSnafu)?;
+    if let Some(recipe) = recipe {
+        return Ok(Some(SomeFujifilmRecipe::Known(recipe)));
+    }
+
+    let recipe_name = name().context(RecipeNameSnafu)?;
+    debug!("Recipe Name: {}", recipe_name);
+
+    let recipe = FujifilmRecipe::new(recipe_name, _FujifilmRecipe::new(recipe_details));
+
+    Ok(Some(SomeFujifilmRecipe::New(recipe)))
+}
+
+#[derive(Debug, Snafu)]
+pub enum GetFujifilmError {
+    #[snafu(display("Could not get Maker from EXIF"))]
+    Maker,
+
+    #[snafu(display("The recipe is new and needs a name"))]
+    RecipeName,
+
+    #[snafu(display("Failed to find recipe: {}", source))]
+    FujifilmFindRecipe { source: FujifilmDbError },
+
+    #[snafu(display("Failed to save the recipe: {}", source))]
+    FujifilmSaveRecipe { source: FujifilmDbError },
+}
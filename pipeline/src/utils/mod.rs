@@ -0,0 +1,11 @@
+mod get_gear;
+mod get_some_fujifilm_recipe;
+mod is_valid_extension;
+mod sha256;
+mod ssim;
+
+pub use get_gear::*;
+pub use get_some_fujifilm_recipe::*;
+pub use is_valid_extension::*;
+pub use sha256::*;
+pub use ssim::*;
@@ -0,0 +1,94 @@
+use image::{DynamicImage, GrayImage};
+
+const BLOCK: u32 = 8;
+const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+/// Structural similarity between two images of the same dimensions, from 0 to 1 where 1 means
+/// they are identical. Only the luma is compared, averaged over 8x8 blocks, which is close enough
+/// to the full SSIM to rank encoder qualities and cheap enough to run several times per image.
+pub fn ssim(reference: &DynamicImage, distorted: &DynamicImage) -> f64 {
+    let a = reference.to_luma8();
+    let b = distorted.to_luma8();
+
+    let (width, height) = a.dimensions();
+    if width == 0 || height == 0 {
+        return 1.0;
+    }
+
+    let block = BLOCK.min(width).min(height);
+
+    let mut total = 0.0;
+    let mut count = 0;
+
+    let mut y = 0;
+    while y + block <= height {
+        let mut x = 0;
+        while x + block <= width {
+            total += block_ssim(&a, &b, x, y, block);
+            count += 1;
+
+            x += block;
+        }
+
+        y += block;
+    }
+
+    total / count as f64
+}
+
+fn block_ssim(a: &GrayImage, b: &GrayImage, x: u32, y: u32, block: u32) -> f64 {
+    let n = (block * block) as f64;
+
+    let (mut sum_a, mut sum_b) = (0.0, 0.0);
+    let (mut sum_aa, mut sum_bb, mut sum_ab) = (0.0, 0.0, 0.0);
+
+    for j in y..y + block {
+        for i in x..x + block {
+            let pa = a.get_pixel(i, j).0[0] as f64;
+            let pb = b.get_pixel(i, j).0[0] as f64;
+
+            sum_a += pa;
+            sum_b += pb;
+            sum_aa += pa * pa;
+            sum_bb += pb * pb;
+            sum_ab += pa * pb;
+        }
+    }
+
+    let mean_a = sum_a / n;
+    let mean_b = sum_b / n;
+    let var_a = sum_aa / n - mean_a * mean_a;
+    let var_b = sum_bb / n - mean_b * mean_b;
+    let covariance = sum_ab / n - mean_a * mean_b;
+
+    ((2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2))
+        / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    fn gradient() -> DynamicImage {
+        DynamicImage::ImageLuma8(GrayImage::from_fn(16, 16, |x, y| {
+            Luma([(x * 8 + y * 7) as u8])
+        }))
+    }
+
+    #[test]
+    fn it_should_be_one_for_identical_images() {
+        let img = gradient();
+
+        assert!((ssim(&img, &img) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn it_should_drop_for_different_images() {
+        let img = gradient();
+        let flat = DynamicImage::ImageLuma8(GrayImage::from_pixel(16, 16, Luma([128])));
+
+        assert!(ssim(&img, &flat) < 0.5);
+    }
+}
@@ -1,6 +1,6 @@
 #!/bin/sh
 
-export RUST_LOG="api_victorhqc_com=error,cli_victorhqc_com=error,core_victorhqc_com=error,sqlx::query=error,rocket=error"
+export RUST_LOG="api_victorhqc_com=error,cli_victorhqc_com=error,core_victorhqc_com=error,pipeline_victorhqc_com=error,sqlx::query=error,rocket=error"
 export DATABASE_URL="sqlite:/<PATH>/victorhqc.com/development.db"
 export ROCKET_CACHED_PHOTO_TAGS="<TAG1>,<TAG2>"
 
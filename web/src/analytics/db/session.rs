@@ -1,10 +1,10 @@
 use crate::analytics::session::Session;
 use core_victorhqc_com::models::Timestamp;
-use log::debug;
 use snafu::prelude::*;
 use sqlx::error::Error as SqlxError;
 use sqlx::{FromRow, SqliteConnection};
 use time::OffsetDateTime;
+use tracing::debug;
 
 #[derive(FromRow)]
 pub struct DBSession {
@@ -1,7 +1,7 @@
 use actix_web::{HttpRequest, Result, http::header};
-use log::debug;
 use serde::Serialize;
 use std::str::FromStr;
+use tracing::debug;
 
 #[derive(Debug, Serialize, Clone, PartialEq, Eq, Hash)]
 pub struct UniqueId(pub String);
@@ -1,16 +1,383 @@
+//! Stores the visits in the background, so registering one never waits for the database. Visits
+//! are queued and written in batches, each in its own transaction.
+use super::db::{session::Error as SessionError, visit::Error as VisitError};
 use super::{session::Session, visit::Visit};
-use log::debug;
-use sqlx::SqlitePool;
-use tokio::sync::mpsc::Receiver;
+use snafu::prelude::*;
+use sqlx::{Error as SqlxError, SqlitePool};
+use std::{
+    error::Error as StdError,
+    fmt::Write,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::{
+        Mutex,
+        mpsc::{Receiver, Sender, channel, error::TrySendError},
+        watch,
+    },
+    task::JoinHandle,
+    time::sleep,
+};
+use tracing::{debug, error, info, warn};
 
-pub async fn store(pool: SqlitePool, mut rx: Receiver<(Session, Visit)>) {
-    let mut conn = pool.acquire().await.unwrap();
+/// Visits waiting to be stored, new ones are dropped while it is full.
+const CAPACITY: usize = 100;
 
-    while let Some((session, visit)) = rx.recv().await {
-        session.create_or_update(&mut conn).await.unwrap();
-        visit.save(&mut conn).await.unwrap();
+/// Most visits written in a single transaction.
+const BATCH_SIZE: usize = 50;
 
-        debug!("Session: {:?}", session);
-        debug!("Visit: {:?}", visit)
+/// Times a batch is written again while the database is busy, waiting twice as long every time.
+const MAX_RETRIES: u32 = 5;
+const RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Wait before starting the recorder again after it panicked.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// Least time between two warnings about dropped visits, a full queue drops many at once.
+const WARNING_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Primary result codes of SQLite when another connection holds the database.
+const SQLITE_BUSY: i32 = 5;
+const SQLITE_LOCKED: i32 = 6;
+
+type Event = (Session, Visit);
+
+/// What happened to the visits since startup.
+#[derive(Debug)]
+struct Stats {
+    stored: AtomicU64,
+    /// Not queued because the queue was full or the recorder stopped.
+    dropped: AtomicU64,
+    /// Lost because their batch couldn't be written.
+    failed: AtomicU64,
+    started: Instant,
+    /// Milliseconds after `started` when dropping a visit is logged again.
+    next_warning: AtomicU64,
+}
+
+impl Stats {
+    fn new() -> Self {
+        Stats {
+            stored: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            started: Instant::now(),
+            next_warning: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether the interval since the last warning passed, only one caller gets `true` for it.
+    fn should_warn(&self) -> bool {
+        let now = self.started.elapsed().as_millis() as u64;
+        let next = self.next_warning.load(Ordering::Relaxed);
+        let later = now + WARNING_INTERVAL.as_millis() as u64;
+
+        now >= next
+            && self
+                .next_warning
+                .compare_exchange(next, later, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+    }
+}
+
+/// Queues visits to be stored, cloned into every worker.
+#[derive(Clone, Debug)]
+pub struct Recorder {
+    sender: Sender<Event>,
+    stats: Arc<Stats>,
+}
+
+impl Recorder {
+    /// Starts storing visits in the pool. The recorder is started again if it panics, until the
+    /// supervisor shuts it down.
+    pub fn start(pool: SqlitePool) -> (Recorder, Supervisor) {
+        let (sender, receiver) = channel::<Event>(CAPACITY);
+        let (shutdown, stopping) = watch::channel(false);
+        let stats = Arc::new(Stats::new());
+
+        // Shared so a restarted recorder keeps the visits that are queued.
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let task = {
+            let stats = stats.clone();
+
+            tokio::spawn(async move {
+                loop {
+                    let recorder = tokio::spawn(run(
+                        pool.clone(),
+                        receiver.clone(),
+                        stopping.clone(),
+                        stats.clone(),
+                    ));
+
+                    match recorder.await {
+                        Ok(()) => break,
+                        Err(e) => {
+                            error!("Analytics recorder stopped unexpectedly, restarting: {}", e);
+                            sleep(RESTART_DELAY).await;
+                        }
+                    }
+                }
+            })
+        };
+
+        let recorder = Recorder {
+            sender,
+            stats: stats.clone(),
+        };
+        let supervisor = Supervisor {
+            shutdown,
+            task,
+            stats,
+        };
+
+        (recorder, supervisor)
+    }
+
+    /// Queues the visit, or drops it when the queue is full instead of waiting. Returns whether
+    /// it was queued.
+    pub fn record(&self, session: Session, visit: Visit) -> bool {
+        let reason = match self.sender.try_send((session, visit)) {
+            Ok(()) => return true,
+            Err(TrySendError::Full(_)) => "full",
+            Err(TrySendError::Closed(_)) => "closed",
+        };
+
+        let dropped = self.stats.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.stats.should_warn() {
+            warn!(reason, dropped, "Dropping analytics visits");
+        }
+
+        false
+    }
+
+    /// The visits stored, dropped and lost since startup, as counters in the Prometheus text
+    /// format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        for (result, counter, help) in [
+            (
+                "stored",
+                &self.stats.stored,
+                "Visits written to the database.",
+            ),
+            (
+                "dropped",
+                &self.stats.dropped,
+                "Visits not queued, the queue was full or closed.",
+            ),
+            (
+                "failed",
+                &self.stats.failed,
+                "Visits lost, their batch couldn't be written.",
+            ),
+        ] {
+            let name = format!("analytics_visits_{}_total", result);
+            let value = counter.load(Ordering::Relaxed);
+
+            writeln!(out, "# HELP {} {}", name, help).unwrap();
+            writeln!(out, "# TYPE {} counter", name).unwrap();
+            writeln!(out, "{} {}", name, value).unwrap();
+        }
+
+        out
+    }
+}
+
+/// Keeps the recorder running, and stops it once the server stopped.
+pub struct Supervisor {
+    shutdown: watch::Sender<bool>,
+    task: JoinHandle<()>,
+    stats: Arc<Stats>,
+}
+
+impl Supervisor {
+    /// Stops queueing visits and waits until the queued ones are stored.
+    pub async fn shutdown(self) {
+        self.shutdown.send_replace(true);
+
+        if let Err(e) = self.task.await {
+            error!("Analytics supervisor failed: {}", e);
+        }
+
+        info!(
+            stored = self.stats.stored.load(Ordering::Relaxed),
+            dropped = self.stats.dropped.load(Ordering::Relaxed),
+            failed = self.stats.failed.load(Ordering::Relaxed),
+            "Analytics recorder stopped"
+        );
+    }
+}
+
+async fn run(
+    pool: SqlitePool,
+    receiver: Arc<Mutex<Receiver<Event>>>,
+    mut stopping: watch::Receiver<bool>,
+    stats: Arc<Stats>,
+) {
+    let mut receiver = receiver.lock().await;
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+
+    loop {
+        // Stored outside of the select, the borrow the watch returns isn't `Send`.
+        let received = tokio::select! {
+            received = receiver.recv_many(&mut batch, BATCH_SIZE) => received,
+            _ = stopping.wait_for(|stop| *stop) => break,
+        };
+
+        // Every recorder was dropped, nothing else can arrive.
+        if received == 0 {
+            return;
+        }
+
+        store(&pool, &mut batch, &stats).await;
+    }
+
+    // New visits are dropped from now on, the queued ones are still received.
+    receiver.close();
+    while receiver.recv_many(&mut batch, BATCH_SIZE).await > 0 {
+        store(&pool, &mut batch, &stats).await;
+    }
+}
+
+/// Writes the batch, again while the database is busy, and empties it.
+async fn store(pool: &SqlitePool, batch: &mut Vec<Event>, stats: &Stats) {
+    let count = batch.len() as u64;
+    let mut retries = 0;
+
+    loop {
+        match store_batch(pool, batch).await {
+            Ok(()) => {
+                stats.stored.fetch_add(count, Ordering::Relaxed);
+                debug!(count, "Stored analytics visits");
+                break;
+            }
+            Err(e) if retries < MAX_RETRIES && is_busy(&e) => {
+                sleep(RETRY_DELAY * 2u32.pow(retries)).await;
+                retries += 1;
+            }
+            Err(e) => {
+                stats.failed.fetch_add(count, Ordering::Relaxed);
+                error!(count, "Failed to store analytics visits: {}", e);
+                break;
+            }
+        }
+    }
+
+    batch.clear();
+}
+
+async fn store_batch(pool: &SqlitePool, batch: &[Event]) -> Result<(), Error> {
+    let mut conn = pool.begin().await.context(TxSnafu)?;
+
+    for (session, visit) in batch {
+        session
+            .create_or_update(&mut conn)
+            .await
+            .context(SessionSnafu)?;
+        visit.save(&mut conn).await.context(VisitSnafu)?;
+    }
+
+    conn.commit().await.context(TxSnafu)
+}
+
+/// Whether the error comes from another connection holding the database, e.g. the CLI.
+fn is_busy(error: &(dyn StdError + 'static)) -> bool {
+    let mut current = Some(error);
+
+    while let Some(e) = current {
+        if let Some(SqlxError::Database(db)) = e.downcast_ref::<SqlxError>()
+            && let Some(Ok(code)) = db.code().map(|c| c.parse::<i32>())
+        {
+            // Extended result codes keep the primary one in the lowest byte.
+            return matches!(code & 0xff, SQLITE_BUSY | SQLITE_LOCKED);
+        }
+
+        current = e.source();
+    }
+
+    false
+}
+
+#[derive(Debug, Snafu)]
+enum Error {
+    #[snafu(display("Failed to execute Transaction: {}", source))]
+    Tx { source: SqlxError },
+
+    #[snafu(display("Failed to store session: {}", source))]
+    Session { source: SessionError },
+
+    #[snafu(display("Failed to store visit: {}", source))]
+    Visit { source: VisitError },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytics::record::UniqueId;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use uuid::Uuid;
+
+    async fn pool() -> SqlitePool {
+        // A single connection that never expires, every new connection would get its own database.
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        pool
+    }
+
+    fn event() -> Event {
+        let session = Session::new(UniqueId(Uuid::new_v4().to_string()), None).unwrap();
+        let visit = Visit::new(&session, "/photography".to_string(), None).unwrap();
+
+        (session, visit)
+    }
+
+    #[actix_web::test]
+    async fn it_stores_queued_visits_on_shutdown() {
+        let pool = pool().await;
+        let (recorder, supervisor) = Recorder::start(pool.clone());
+
+        for _ in 0..10 {
+            let (session, visit) = event();
+            assert!(recorder.record(session, visit));
+        }
+        supervisor.shutdown().await;
+
+        let (session, visit) = event();
+        assert!(!recorder.record(session, visit));
+
+        let (visits,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM visits")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(visits, 10);
+        assert_eq!(recorder.stats.stored.load(Ordering::Relaxed), 10);
+        assert_eq!(recorder.stats.dropped.load(Ordering::Relaxed), 1);
+
+        let metrics = recorder.render();
+        assert!(metrics.contains("# TYPE analytics_visits_stored_total counter"));
+        assert!(metrics.contains("analytics_visits_stored_total 10"));
+        assert!(metrics.contains("analytics_visits_dropped_total 1"));
+        assert!(metrics.contains("analytics_visits_failed_total 0"));
+    }
+
+    #[test]
+    fn it_warns_once_per_interval() {
+        let stats = Stats::new();
+
+        assert!(stats.should_warn());
+        assert!(!stats.should_warn());
     }
 }
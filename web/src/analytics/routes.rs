@@ -7,11 +7,11 @@ use crate::collections::Collection;
 use crate::routes::get_user_agent;
 use crate::state::AppState;
 use actix_web::{HttpRequest, HttpResponse, Responder, Result, get, web};
-use log::debug;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 use std::str::FromStr;
 use std::string::ToString;
+use tracing::debug;
 
 #[get("/analytics")]
 pub async fn register_visit(
@@ -19,7 +19,6 @@ pub async fn register_visit(
     info: web::Query<Info>,
     req: HttpRequest,
 ) -> Result<impl Responder> {
-    let tx = data.analytics_sender.clone();
     let route = Route::from_str(&info.path).ok();
     let ua = get_user_agent(&req);
 
@@ -37,7 +36,11 @@ pub async fn register_visit(
         let session = Session::new(client_id, Some(ua.get().to_string())).unwrap();
         let visit = Visit::new(&session, route.to_string(), referer).unwrap();
 
-        tx.send((session, visit)).await.unwrap();
+        // Dropped while the recorder is behind, the page works the same without it and the
+        // browser has nothing to retry.
+        if !data.analytics.record(session, visit) {
+            return Ok(HttpResponse::NoContent().finish());
+        }
 
         return Ok(HttpResponse::Created().finish());
     }
@@ -45,6 +48,14 @@ pub async fn register_visit(
     Ok(HttpResponse::BadRequest().finish())
 }
 
+/// What happened to the visits, in the Prometheus text format. Nginx only serves it locally.
+#[get("/metrics")]
+pub async fn metrics(data: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(data.analytics.render())
+}
+
 #[derive(Debug, Deserialize)]
 struct Info {
     #[serde(rename(deserialize = "p"))]
@@ -55,6 +66,9 @@ struct Info {
 pub enum Route {
     Index,
     Photography,
+    Gear,
+    Recipes,
+    Recipe(String),
     Collection(Collection),
     Photo(String),
 }
@@ -77,6 +91,11 @@ impl FromStr for Route {
                     }
                 }
                 "photo" => Ok(Route::Photo(id.to_string())),
+                "photography" if *id == "gear" => Ok(Route::Gear),
+                "photography" if *id == "recipes" => match parts.get(3) {
+                    Some(recipe) => Ok(Route::Recipe(recipe.to_string())),
+                    None => Ok(Route::Recipes),
+                },
                 _ => Err(format!("Invalid route: {}", s)),
             };
         }
@@ -94,6 +113,9 @@ impl Display for Route {
         match self {
             Route::Index => write!(f, "/"),
             Route::Photography => write!(f, "/photography"),
+            Route::Gear => write!(f, "/photography/gear"),
+            Route::Recipes => write!(f, "/photography/recipes"),
+            Route::Recipe(r) => write!(f, "/photography/recipes/{}", r),
             Route::Collection(c) => write!(f, "/collection/{}", c),
             Route::Photo(p) => write!(f, "/photo/{}", p),
         }
@@ -9,3 +9,35 @@ use graphql_client::GraphQLQuery;
     response_derives = "Debug, Clone, Serialize, PartialEq"
 )]
 pub struct GetPortfolio;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "../api/schema.gql",
+    query_path = "src/gql/queries/get-gear.graphql",
+    response_derives = "Debug, Clone, Serialize, PartialEq"
+)]
+pub struct GetGear;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "../api/schema.gql",
+    query_path = "src/gql/queries/get-recipes.graphql",
+    response_derives = "Debug, Clone, Serialize, PartialEq"
+)]
+pub struct GetRecipes;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "../api/schema.gql",
+    query_path = "src/gql/queries/get-recipe.graphql",
+    response_derives = "Debug, Clone, Serialize, PartialEq"
+)]
+pub struct GetRecipe;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "../api/schema.gql",
+    query_path = "src/gql/queries/get-similar-photos.graphql",
+    response_derives = "Debug, Clone, Serialize, PartialEq"
+)]
+pub struct GetSimilarPhotos;
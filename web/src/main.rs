@@ -1,23 +1,21 @@
-#[macro_use]
-extern crate log;
-
 use crate::state::AppState;
 use actix_files as fs;
 use actix_web::{App, HttpServer, middleware, web};
-use analytics::{session::Session, visit::Visit};
+use analytics::recorder::Recorder;
+use core_victorhqc_com::telemetry;
 use lazy_static::lazy_static;
-use log::info;
 use snafu::prelude::*;
 use sqlx::SqlitePool;
 use std::{collections::HashMap, env, path::PathBuf};
 use tera::Tera;
-use tokio::sync::mpsc;
+use tracing::info;
 use uaparser::UserAgentParser;
 
 mod analytics;
 mod collections;
 mod gql;
 mod prefetch;
+mod request_id;
 mod requests;
 mod routes;
 mod state;
@@ -73,7 +71,7 @@ lazy_static! {
 #[actix_web::main]
 async fn main() -> Result<(), Error> {
     dotenvy::dotenv().ok();
-    pretty_env_logger::init();
+    let _telemetry = telemetry::init("web");
 
     let api_host = env::var("WEB_API_HOST").expect("WEB_API_HOST env variable is missing");
     let root = env::var("WEB_ROOT").unwrap_or("".to_string());
@@ -106,7 +104,9 @@ async fn main() -> Result<(), Error> {
         return Err(Error::MissingTemplates);
     }
 
-    let prefetched = prefetch::fetch_photos().await.context(PrefetchSnafu)?;
+    let prefetched = request_id::scope(prefetch::fetch_photos())
+        .await
+        .context(PrefetchSnafu)?;
 
     info!("Booting Web in port {}", port);
 
@@ -116,20 +116,19 @@ async fn main() -> Result<(), Error> {
     let scripts_path = format!("./{}public", root);
     info!("Serving public files from {}", scripts_path);
 
-    let (tx, rx) = mpsc::channel::<(Session, Visit)>(100);
+    let (recorder, supervisor) = Recorder::start(pool);
 
     let state = AppState {
         api_host,
         prefetched,
         ua_parser: parser,
-        analytics_sender: tx,
+        analytics: recorder,
     };
 
-    tokio::spawn(async move { analytics::recorder::store(pool, rx).await });
-
-    HttpServer::new(move || {
+    let server = HttpServer::new(move || {
         App::new()
             .wrap(middleware::Compress::default())
+            .wrap(middleware::from_fn(request_id::propagate))
             .app_data(web::Data::new(state.clone()))
             .service(
                 fs::Files::new("/static", &static_path)
@@ -143,18 +142,27 @@ async fn main() -> Result<(), Error> {
             )
             .service(routes::index::index)
             .service(routes::portfolio::photography)
+            .service(routes::gear::gear)
+            .service(routes::recipes::recipes)
+            .service(routes::recipes::recipe)
             .service(routes::portfolio::portfolio_collection)
             .service(routes::portfolio::collection_photo)
             .service(routes::portfolio::ajax_collection)
             .service(routes::portfolio::ajax_one_photo)
             .service(analytics::routes::register_visit)
+            .service(analytics::routes::metrics)
     })
     .workers(4)
     .bind(("127.0.0.1", port))
     .context(BindSnafu)?
-    .run()
-    .await
-    .context(StartSnafu)
+    .run();
+
+    // Actix stops the workers on SIGINT or SIGTERM, the visits they queued are stored before
+    // exiting.
+    let result = server.await.context(StartSnafu);
+    supervisor.shutdown().await;
+
+    result
 }
 
 #[derive(Debug, Snafu)]
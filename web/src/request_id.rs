@@ -0,0 +1,59 @@
+//! The id of the request being answered, sent to the API in `X-Request-Id` so the logs of both
+//! services can be matched.
+use actix_web::{
+    Error,
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
+    middleware::Next,
+};
+use core_victorhqc_com::telemetry::{REQUEST_ID_HEADER, request_id};
+use std::future::Future;
+use tracing::{Instrument, info, info_span};
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// The id of the request the current task answers, if any.
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Runs `f` as a request of its own, for the calls made outside of a request, e.g. on startup.
+pub async fn scope<F: Future>(f: F) -> F::Output {
+    let id = request_id(None);
+    let span = info_span!("task", request_id = %id);
+
+    REQUEST_ID.scope(id, f).instrument(span).await
+}
+
+/// Gives every request an id, the one a proxy sent or a new one, and answers with it.
+pub async fn propagate(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let header = req.headers().get(REQUEST_ID_HEADER);
+    let id = request_id(header.and_then(|h| h.to_str().ok()));
+
+    let span = info_span!(
+        "request",
+        request_id = %id,
+        method = %req.method(),
+        path = %req.path()
+    );
+    let mut res = REQUEST_ID
+        .scope(id.clone(), next.call(req))
+        .instrument(span.clone())
+        .await?;
+
+    span.in_scope(|| info!(status = res.status().as_u16(), "request"));
+
+    // Only valid ids are kept, they are always valid header values.
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        let name = HeaderName::from_static("x-request-id");
+        res.headers_mut().insert(name, value);
+    }
+
+    Ok(res)
+}
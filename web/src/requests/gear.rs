@@ -0,0 +1,47 @@
+use super::post;
+use crate::gql::{GetGear, get_gear};
+use graphql_client::{Error as GraphQLError, GraphQLQuery, Response};
+use reqwest::Error as ReqwestError;
+use snafu::prelude::*;
+
+pub async fn get_gear() -> Result<get_gear::ResponseData, Error> {
+    let api_host = std::env::var("WEB_API_HOST").expect("WEB_API_HOST env variable is missing");
+
+    let request_body = GetGear::build_query(get_gear::Variables {});
+
+    let client = reqwest::Client::new();
+    let url = format!("{}/graphql", api_host);
+    let response = post(&client, &url)
+        .json(&request_body)
+        .send()
+        .await
+        .context(RequestSnafu)?;
+
+    let response_body: Response<get_gear::ResponseData> =
+        response.json().await.context(JsonParseSnafu)?;
+
+    if let Some(errors) = response_body.errors {
+        return Err(Error::GQLErrors { errors });
+    }
+
+    if let Some(data) = response_body.data {
+        return Ok(data);
+    }
+
+    Err(Error::NoData)
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Request failed: {:?}", source))]
+    Request { source: ReqwestError },
+
+    #[snafu(display("Request Json Deserialization failed: {:?}", source))]
+    JsonParse { source: ReqwestError },
+
+    #[snafu(display("No data from Request"))]
+    NoData,
+
+    #[snafu(display("Failed to get gear: {:?}", errors))]
+    GQLErrors { errors: Vec<GraphQLError> },
+}
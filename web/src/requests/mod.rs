@@ -1 +1,87 @@
+pub mod gear;
 pub mod photos;
+pub mod recipes;
+
+use crate::request_id;
+use core_victorhqc_com::telemetry::REQUEST_ID_HEADER;
+use graphql_client::{GraphQLQuery, Response};
+use reqwest::{Client, Error as ReqwestError, RequestBuilder};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// What the API answers when it doesn't know the hash of a persisted query.
+const PERSISTED_QUERY_NOT_FOUND: &str = "PersistedQueryNotFound";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedQueryBody<'a, V> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    query: Option<&'static str>,
+    operation_name: &'static str,
+    variables: &'a V,
+    extensions: Extensions<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Extensions<'a> {
+    persisted_query: PersistedQuery<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedQuery<'a> {
+    version: u8,
+    sha256_hash: &'a str,
+}
+
+/// A POST to the API with the id of the request being answered.
+pub fn post(client: &Client, url: &str) -> RequestBuilder {
+    let request = client.post(url);
+
+    match request_id::current() {
+        Some(id) => request.header(REQUEST_ID_HEADER, id),
+        None => request,
+    }
+}
+
+/// Sends the query as an automatic persisted query, only its hash, and sends it again with the
+/// query when the API doesn't know the hash yet.
+pub async fn post_persisted<Q: GraphQLQuery>(
+    client: &Client,
+    url: &str,
+    variables: Q::Variables,
+) -> Result<Response<Q::ResponseData>, ReqwestError> {
+    let request_body = Q::build_query(variables);
+
+    let mut hasher = Sha256::new();
+    hasher.update(request_body.query.as_bytes());
+    let sha256_hash = format!("{:x}", hasher.finalize());
+
+    let mut body = PersistedQueryBody {
+        query: None,
+        operation_name: request_body.operation_name,
+        variables: &request_body.variables,
+        extensions: Extensions {
+            persisted_query: PersistedQuery {
+                version: 1,
+                sha256_hash: &sha256_hash,
+            },
+        },
+    };
+
+    let response: Response<Q::ResponseData> =
+        post(client, url).json(&body).send().await?.json().await?;
+
+    let not_found = response
+        .errors
+        .iter()
+        .flatten()
+        .any(|e| e.message == PERSISTED_QUERY_NOT_FOUND);
+    if !not_found {
+        return Ok(response);
+    }
+
+    body.query = Some(request_body.query);
+    post(client, url).json(&body).send().await?.json().await
+}
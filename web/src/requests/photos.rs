@@ -1,9 +1,11 @@
-use crate::gql::{GetPortfolio, get_portfolio};
+use super::{post, post_persisted};
+use crate::gql::{GetPortfolio, GetSimilarPhotos, get_portfolio, get_similar_photos};
 use graphql_client::{Error as GraphQLError, GraphQLQuery, Response};
 use reqwest::Error as ReqwestError;
 use snafu::prelude::*;
 
 type PortfolioPhotos = get_portfolio::GetPortfolioPhotos;
+type SimilarPhotos = get_similar_photos::GetSimilarPhotosPhotoSimilar;
 
 pub async fn get_photos_from_tag(name: &str) -> Result<Vec<PortfolioPhotos>, Error> {
     let api_host = std::env::var("WEB_API_HOST").expect("WEB_API_HOST env variable is missing");
@@ -13,17 +15,42 @@ pub async fn get_photos_from_tag(name: &str) -> Result<Vec<PortfolioPhotos>, Err
         max: Some(50),
     };
 
-    let request_body = GetPortfolio::build_query(variables);
+    let client = reqwest::Client::new();
+    let url = format!("{}/graphql", api_host);
+    let response_body = post_persisted::<GetPortfolio>(&client, &url, variables)
+        .await
+        .context(RequestSnafu)?;
+
+    if let Some(errors) = response_body.errors {
+        return Err(Error::GQLErrors { errors });
+    }
+
+    if let Some(data) = response_body.data {
+        return Ok(data.photos);
+    }
+
+    Err(Error::NoData)
+}
+
+pub async fn get_similar_photos(id: &str, limit: i64) -> Result<Vec<SimilarPhotos>, Error> {
+    let api_host = std::env::var("WEB_API_HOST").expect("WEB_API_HOST env variable is missing");
+
+    let variables = get_similar_photos::Variables {
+        id: id.to_string(),
+        limit: Some(limit),
+    };
+
+    let request_body = GetSimilarPhotos::build_query(variables);
 
     let client = reqwest::Client::new();
-    let response = client
-        .post(format!("{}/graphql", api_host))
+    let url = format!("{}/graphql", api_host);
+    let response = post(&client, &url)
         .json(&request_body)
         .send()
         .await
         .context(RequestSnafu)?;
 
-    let response_body: Response<get_portfolio::ResponseData> =
+    let response_body: Response<get_similar_photos::ResponseData> =
         response.json().await.context(JsonParseSnafu)?;
 
     if let Some(errors) = response_body.errors {
@@ -31,7 +58,7 @@ pub async fn get_photos_from_tag(name: &str) -> Result<Vec<PortfolioPhotos>, Err
     }
 
     if let Some(data) = response_body.data {
-        return Ok(data.photos);
+        return Ok(data.photo.similar);
     }
 
     Err(Error::NoData)
@@ -0,0 +1,61 @@
+use super::post;
+use crate::gql::{GetRecipe, GetRecipes, get_recipe, get_recipes};
+use graphql_client::{Error as GraphQLError, GraphQLQuery, Response};
+use reqwest::Error as ReqwestError;
+use serde::{Serialize, de::DeserializeOwned};
+use snafu::prelude::*;
+
+pub async fn get_recipes() -> Result<get_recipes::ResponseData, Error> {
+    let request_body = GetRecipes::build_query(get_recipes::Variables {});
+
+    send(&request_body).await
+}
+
+pub async fn get_recipe(id: &str) -> Result<get_recipe::ResponseData, Error> {
+    let request_body = GetRecipe::build_query(get_recipe::Variables { id: id.to_string() });
+
+    send(&request_body).await
+}
+
+async fn send<B, D>(request_body: &B) -> Result<D, Error>
+where
+    B: Serialize,
+    D: DeserializeOwned,
+{
+    let api_host = std::env::var("WEB_API_HOST").expect("WEB_API_HOST env variable is missing");
+
+    let client = reqwest::Client::new();
+    let url = format!("{}/graphql", api_host);
+    let response = post(&client, &url)
+        .json(request_body)
+        .send()
+        .await
+        .context(RequestSnafu)?;
+
+    let response_body: Response<D> = response.json().await.context(JsonParseSnafu)?;
+
+    if let Some(errors) = response_body.errors {
+        return Err(Error::GQLErrors { errors });
+    }
+
+    if let Some(data) = response_body.data {
+        return Ok(data);
+    }
+
+    Err(Error::NoData)
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Request failed: {:?}", source))]
+    Request { source: ReqwestError },
+
+    #[snafu(display("Request Json Deserialization failed: {:?}", source))]
+    JsonParse { source: ReqwestError },
+
+    #[snafu(display("No data from Request"))]
+    NoData,
+
+    #[snafu(display("Failed to get recipes: {:?}", errors))]
+    GQLErrors { errors: Vec<GraphQLError> },
+}
@@ -1,10 +1,10 @@
 use crate::utils::device;
 use crate::{TEMPLATES, analytics::routes::Route, state::AppState};
 use actix_web::{ResponseError, Result, web};
-use log::{debug, error};
 use snafu::prelude::*;
 use strum_macros::Display;
 use tera::Context;
+use tracing::{debug, error, info_span};
 use uaparser::Parser;
 
 #[derive(Debug, Display, serde::Serialize)]
@@ -44,8 +44,9 @@ pub fn render_content(args: RenderArgs) -> Result<String> {
     args.ctx.insert("is_production", &is_production);
     args.ctx.insert("is_mobile", &is_mobile);
 
-    let content = TEMPLATES
-        .render(format!("{}.{}", args.route, args.kind).as_str(), args.ctx)
+    let template = format!("{}.{}", args.route, args.kind);
+    let content = info_span!("render", template = %template)
+        .in_scope(|| TEMPLATES.render(&template, args.ctx))
         .context(TemplateSnafu {
             route: args.route.to_string(),
         })?;
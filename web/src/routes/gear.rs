@@ -0,0 +1,166 @@
+use super::context::{RenderArgs, TemplateKind, render_content};
+use super::get_user_agent;
+use crate::{
+    analytics,
+    gql::get_gear::{GearStatsFields, GetGearCameras, GetGearLenses},
+    requests,
+    state::AppState,
+};
+use actix_web::{HttpRequest, HttpResponse, Responder, Result, error::ResponseError, get, web};
+use snafu::prelude::*;
+use std::cmp::Reverse;
+use tera::Context;
+use tracing::error;
+
+#[derive(Debug, serde::Serialize)]
+struct Gear {
+    id: String,
+    display_name: String,
+    mount: Option<String>,
+    specs: Option<String>,
+    photo_count: i64,
+    most_used_focal_length: Option<String>,
+    focal_lengths: Vec<Bar>,
+    apertures: Vec<Bar>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Bar {
+    label: String,
+    count: i64,
+    /// Width of the bar relative to the most used value.
+    percentage: i64,
+}
+
+#[get("/photography/gear")]
+pub async fn gear(data: web::Data<AppState>, req: HttpRequest) -> Result<impl Responder> {
+    let ua = get_user_agent(&req);
+    let mut context = Context::new();
+
+    let gear = requests::gear::get_gear().await.context(FetchSnafu)?;
+
+    let mut cameras: Vec<Gear> = gear.cameras.iter().map(Gear::from).collect();
+    let mut lenses: Vec<Gear> = gear.lenses.iter().map(Gear::from).collect();
+
+    cameras.sort_by_key(|g| Reverse(g.photo_count));
+    lenses.sort_by_key(|g| Reverse(g.photo_count));
+
+    context.insert("cameras", &cameras);
+    context.insert("lenses", &lenses);
+
+    let args = RenderArgs {
+        route: "gear",
+        route_to_record: Some(analytics::routes::Route::Gear),
+        kind: TemplateKind::Html,
+        ctx: &mut context,
+        data: &data,
+        user_agent: ua.get(),
+    };
+    let content = render_content(args)?;
+
+    Ok(HttpResponse::Ok().body(content))
+}
+
+impl Gear {
+    fn new(
+        id: &str,
+        display_name: &str,
+        mount: &Option<String>,
+        specs: Option<String>,
+        stats: &GearStatsFields,
+    ) -> Self {
+        let focal_lengths = build_bars(
+            stats
+                .focal_lengths
+                .iter()
+                .map(|b| (format!("{}mm", b.value), b.count)),
+        );
+        let apertures = build_bars(
+            stats
+                .apertures
+                .iter()
+                .map(|b| (format!("f/{}", b.value), b.count)),
+        );
+
+        Gear {
+            id: id.to_string(),
+            display_name: display_name.to_string(),
+            mount: mount.clone(),
+            specs,
+            photo_count: stats.photo_count,
+            most_used_focal_length: stats.most_used_focal_length.map(|v| format!("{}mm", v)),
+            focal_lengths,
+            apertures,
+        }
+    }
+}
+
+impl From<&GetGearCameras> for Gear {
+    fn from(value: &GetGearCameras) -> Self {
+        Gear::new(
+            &value.id,
+            &value.display_name,
+            &value.mount,
+            None,
+            &value.stats,
+        )
+    }
+}
+
+impl From<&GetGearLenses> for Gear {
+    fn from(value: &GetGearLenses) -> Self {
+        let focal_range = match (value.min_focal_length, value.max_focal_length) {
+            (Some(min), Some(max)) if value.is_zoom => Some(format!("{}-{}mm", min, max)),
+            (Some(min), _) => Some(format!("{}mm", min)),
+            _ => None,
+        };
+        let aperture = value.max_aperture.map(|a| format!("f/{}", a));
+
+        let specs = match (focal_range, aperture) {
+            (Some(f), Some(a)) => Some(format!("{} {}", f, a)),
+            (Some(f), None) => Some(f),
+            (None, Some(a)) => Some(a),
+            (None, None) => None,
+        };
+
+        Gear::new(
+            &value.id,
+            &value.display_name,
+            &value.mount,
+            specs,
+            &value.stats,
+        )
+    }
+}
+
+fn build_bars(values: impl Iterator<Item = (String, i64)>) -> Vec<Bar> {
+    let values: Vec<(String, i64)> = values.collect();
+    let max = values.iter().map(|(_, c)| *c).max().unwrap_or(0);
+
+    values
+        .into_iter()
+        .map(|(label, count)| Bar {
+            percentage: if max > 0 { count * 100 / max } else { 0 },
+            label,
+            count,
+        })
+        .collect()
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to fetch gear: {:?}", source))]
+    Fetch { source: requests::gear::Error },
+}
+
+impl ResponseError for Error {
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            Error::Fetch { source } => {
+                error!("Failed to fetch gear: {:?}", source);
+
+                HttpResponse::BadGateway().body("Could not load the gear")
+            }
+        }
+    }
+}
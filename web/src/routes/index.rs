@@ -6,9 +6,9 @@ use crate::{
     state::AppState,
 };
 use actix_web::{HttpRequest, HttpResponse, Responder, Result, get, web};
-use log::debug;
 use rand::seq::IndexedRandom;
 use tera::Context;
+use tracing::debug;
 
 #[get("/")]
 pub async fn index(data: web::Data<AppState>, req: HttpRequest) -> Result<impl Responder> {
@@ -18,7 +18,8 @@ pub async fn index(data: web::Data<AppState>, req: HttpRequest) -> Result<impl R
     let prefetched = &data.prefetched;
     let portfolio_photos = prefetched.get(&Collection::Portfolio).unwrap();
 
-    // Only landscape photos for the main stack
+    // Only landscape photos for the main stack, the slides are 3:2 so squares and panoramas would
+    // stick out of the pile.
     let filtered_photos: Vec<&GetPortfolioPhotos> = portfolio_photos
         .iter()
         .filter(|photo| photo.orientation == Orientation::LANDSCAPE)
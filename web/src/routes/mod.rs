@@ -1,6 +1,8 @@
 mod context;
+pub mod gear;
 pub mod index;
 pub mod portfolio;
+pub mod recipes;
 
 mod user_agent;
 pub use user_agent::*;
@@ -3,14 +3,21 @@ use super::get_user_agent;
 use crate::{
     analytics,
     collections::{COLLECTIONS, Collection},
-    gql::get_portfolio::GetPortfolioPhotos,
+    gql::{get_portfolio::GetPortfolioPhotos, get_similar_photos::GetSimilarPhotosPhotoSimilar},
     prefetch::PrefetchedCollection,
+    requests,
     state::AppState,
 };
 use actix_web::{HttpRequest, HttpResponse, Responder, Result, error::ResponseError, get, web};
 use snafu::prelude::*;
 use std::str::FromStr;
 use tera::Context;
+use tracing::{debug, error};
+
+/// How many photos the "more like this" strip shows.
+const SIMILAR_PHOTOS: usize = 6;
+/// How many similar photos are asked for, some may not be in the collection being browsed.
+const SIMILAR_PHOTOS_TO_FETCH: i64 = 18;
 
 #[derive(Debug, serde::Serialize)]
 struct PortfolioPhoto {
@@ -144,12 +151,14 @@ pub async fn ajax_one_photo(
 
     let collection = get_collection(&active_collection, &data.prefetched).await?;
     let photo = collection.iter().find(|p| p.photo.id == id).unwrap();
+    let similar = get_similar(&id, &collection).await;
 
     context.insert(
         "collection_route",
         &CollectionRoute::from(&active_collection),
     );
     context.insert("photo", &photo);
+    context.insert("similar_photos", &similar);
 
     let args = RenderArgs {
         route: "_ajax/one_photo",
@@ -180,6 +189,7 @@ pub async fn collection_photo(
     let collection = get_collection(&active_collection, &data.prefetched).await?;
     debug!("Portfolio Photos for tag {}: {:?}", name, collection);
     let photo = collection.iter().find(|p| p.photo.id == id).unwrap();
+    let similar = get_similar(&id, &collection).await;
 
     context.insert(
         "collection_route",
@@ -187,6 +197,7 @@ pub async fn collection_photo(
     );
     context.insert("available_collections", &build_collection_routes());
     context.insert("photo", &photo);
+    context.insert("similar_photos", &similar);
 
     let args = RenderArgs {
         route: "photo",
@@ -238,6 +249,22 @@ async fn get_collection(
     Ok(result)
 }
 
+/// Photos that look like the given one, only from the collection being browsed so the links stay
+/// in it. The strip is optional, the photo is still shown when the API fails to answer.
+async fn get_similar(id: &str, collection: &[PortfolioPhoto]) -> Vec<GetSimilarPhotosPhotoSimilar> {
+    match requests::photos::get_similar_photos(id, SIMILAR_PHOTOS_TO_FETCH).await {
+        Ok(similar) => similar
+            .into_iter()
+            .filter(|s| collection.iter().any(|p| p.photo.id == s.id))
+            .take(SIMILAR_PHOTOS)
+            .collect(),
+        Err(e) => {
+            error!("Failed to get the photos similar to {}: {:?}", id, e);
+            Vec::new()
+        }
+    }
+}
+
 fn build_collection_routes() -> Vec<CollectionRoute> {
     COLLECTIONS.iter().map(CollectionRoute::from).collect()
 }
@@ -0,0 +1,88 @@
+use super::context::{RenderArgs, TemplateKind, render_content};
+use super::get_user_agent;
+use crate::{analytics, requests, state::AppState};
+use actix_web::{HttpRequest, HttpResponse, Responder, Result, error::ResponseError, get, web};
+use snafu::prelude::*;
+use std::cmp::Reverse;
+use tera::Context;
+use tracing::error;
+
+#[get("/photography/recipes")]
+pub async fn recipes(data: web::Data<AppState>, req: HttpRequest) -> Result<impl Responder> {
+    let ua = get_user_agent(&req);
+    let mut context = Context::new();
+
+    let mut recipes = requests::recipes::get_recipes()
+        .await
+        .context(FetchSnafu)?
+        .fujifilm_recipes;
+
+    recipes.sort_by_key(|r| Reverse(r.photos.len()));
+
+    context.insert("recipes", &recipes);
+
+    let args = RenderArgs {
+        route: "recipes",
+        route_to_record: Some(analytics::routes::Route::Recipes),
+        kind: TemplateKind::Html,
+        ctx: &mut context,
+        data: &data,
+        user_agent: ua.get(),
+    };
+    let content = render_content(args)?;
+
+    Ok(HttpResponse::Ok().body(content))
+}
+
+#[get("/photography/recipes/{id}")]
+pub async fn recipe(
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<impl Responder> {
+    let ua = get_user_agent(&req);
+    let id = path.into_inner();
+    let mut context = Context::new();
+
+    let recipe = requests::recipes::get_recipe(&id)
+        .await
+        .context(FetchSnafu)?
+        .fujifilm_recipe
+        .context(NotFoundSnafu { id: &id })?;
+
+    context.insert("recipe", &recipe);
+
+    let args = RenderArgs {
+        route: "recipe",
+        route_to_record: Some(analytics::routes::Route::Recipe(id)),
+        kind: TemplateKind::Html,
+        ctx: &mut context,
+        data: &data,
+        user_agent: ua.get(),
+    };
+    let content = render_content(args)?;
+
+    Ok(HttpResponse::Ok().body(content))
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Failed to fetch recipes: {:?}", source))]
+    Fetch { source: requests::recipes::Error },
+
+    #[snafu(display("Recipe {} does not exist", id))]
+    NotFound { id: String },
+}
+
+impl ResponseError for Error {
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            Error::Fetch { source } => {
+                error!("Failed to fetch recipes: {:?}", source);
+
+                HttpResponse::BadGateway().body("Could not load the recipes")
+            }
+            Error::NotFound { .. } => HttpResponse::NotFound().body(self.to_string()),
+        }
+    }
+}
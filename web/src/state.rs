@@ -1,6 +1,5 @@
-use crate::analytics::{session::Session, visit::Visit};
+use crate::analytics::recorder::Recorder;
 use crate::prefetch::PrefetchedCollection;
-use tokio::sync::mpsc::Sender;
 use uaparser::UserAgentParser;
 
 #[derive(Debug, Clone)]
@@ -8,5 +7,5 @@ pub struct AppState {
     pub api_host: String,
     pub prefetched: PrefetchedCollection,
     pub ua_parser: UserAgentParser,
-    pub analytics_sender: Sender<(Session, Visit)>,
+    pub analytics: Recorder,
 }
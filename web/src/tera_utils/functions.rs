@@ -1,8 +1,9 @@
-use fuji::recipe::{FilmSimulation, MonochromaticFilter};
-use log::debug;
+use core_victorhqc_com::models::fujifilm::swatch_filename;
+use fuji::recipe::FilmSimulation;
 use sha2::{Digest, Sha256};
 use std::{collections::HashMap, str::FromStr};
 use tera::{Function, Result, Value, from_value, to_value};
+use tracing::debug;
 use url::Url;
 
 pub fn get_gravatar() -> impl Function {
@@ -36,32 +37,7 @@ pub fn get_film_simulation_image() -> impl Function {
         let name = from_value::<String>(name.clone()).unwrap();
         let film_sim = FilmSimulation::from_str(&name).expect("Invalid film sim");
 
-        let img = match film_sim {
-            FilmSimulation::ProviaStandard => "provia_1.png",
-            FilmSimulation::RealaAce => "realaace_1.png",
-            FilmSimulation::VelviaVivid => "velvia_1.png",
-            FilmSimulation::AstiaSoft => "astia_1.png",
-            FilmSimulation::ClassicChrome => "classicchrome_1.png",
-            FilmSimulation::ClassicNeg => "classicneg_1.png",
-            FilmSimulation::ProNegHi => "proneghi_1.png",
-            FilmSimulation::ProNegStd => "pronegstd_1.png",
-            FilmSimulation::NostalgicNeg => "nostalgicneg_1.png",
-            FilmSimulation::EternaCinema => "eterna_1.png",
-            FilmSimulation::BleachBypass => "eternabb_1.png",
-            FilmSimulation::Sepia => "sepia_1.png",
-            FilmSimulation::Monochrome { filter } => match filter {
-                MonochromaticFilter::Std => "monostd_1.png",
-                MonochromaticFilter::Red => "monor_1.png",
-                MonochromaticFilter::Green => "monog_1.png",
-                MonochromaticFilter::Yellow => "monoye_1.png",
-            },
-            FilmSimulation::Acros { filter } => match filter {
-                MonochromaticFilter::Std => "acrosstd_1.png",
-                MonochromaticFilter::Red => "acrosr_1.png",
-                MonochromaticFilter::Green => "acrosg_1.png",
-                MonochromaticFilter::Yellow => "acrosye_1.png",
-            },
-        };
+        let img = swatch_filename(&film_sim);
 
         Ok(to_value(img).unwrap())
     })
@@ -73,12 +49,18 @@ pub fn parse_film_simulation_name() -> impl Function {
         let name = from_value::<String>(name.clone()).unwrap();
         let film_sim = FilmSimulation::from_str(&name).expect("Invalid film sim");
 
-        let mc = args.get("monochromatic_color").unwrap();
-        let mc = from_value::<String>(mc.clone()).unwrap();
+        // Null when the sensor doesn't support monochromatic colors.
+        let mc = args
+            .get("monochromatic_color")
+            .and_then(|mc| from_value::<String>(mc.clone()).ok())
+            .unwrap_or_default();
 
         let val = match film_sim {
-            FilmSimulation::Monochrome { filter: _ } => format!("{} ({})", name, mc),
-            FilmSimulation::Acros { filter: _ } => format!("{} ({})", name, mc),
+            FilmSimulation::Monochrome { filter: _ } | FilmSimulation::Acros { filter: _ }
+                if !mc.is_empty() =>
+            {
+                format!("{} ({})", name, mc)
+            }
             _ => name,
         };
 